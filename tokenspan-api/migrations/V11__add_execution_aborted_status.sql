@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- ALTER TYPE ... ADD VALUE cannot run inside a transaction block on older postgres,
+-- so the enum is recreated instead.
+ALTER TYPE execution_status RENAME TO execution_status_old;
+
+CREATE TYPE execution_status AS ENUM ('pending', 'running', 'success', 'failed', 'aborted');
+
+ALTER TABLE executions
+    ALTER COLUMN status TYPE execution_status USING status::text::execution_status;
+
+DROP TYPE execution_status_old;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+CREATE TYPE provider_kind AS ENUM ('openai');
+
+ALTER TABLE providers
+    ADD COLUMN kind provider_kind NOT NULL DEFAULT 'openai';
@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+ALTER TYPE provider_kind RENAME TO provider_kind_old;
+
+CREATE TYPE provider_kind AS ENUM ('openai', 'anthropic', 'gemini');
+
+ALTER TABLE providers
+    ALTER COLUMN kind DROP DEFAULT,
+    ALTER COLUMN kind TYPE provider_kind USING kind::text::provider_kind,
+    ALTER COLUMN kind SET DEFAULT 'openai';
+
+DROP TYPE provider_kind_old;
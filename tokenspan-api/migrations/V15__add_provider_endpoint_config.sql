@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+CREATE TYPE provider_auth_scheme AS ENUM ('native', 'bearer', 'header', 'query', 'none');
+
+ALTER TABLE providers
+    ADD COLUMN auth_scheme  provider_auth_scheme NOT NULL DEFAULT 'native',
+    ADD COLUMN auth_name    TEXT,
+    ADD COLUMN headers      jsonb[]              NOT NULL DEFAULT '{}',
+    ADD COLUMN query_params jsonb[]              NOT NULL DEFAULT '{}',
+    ADD COLUMN url_template TEXT;
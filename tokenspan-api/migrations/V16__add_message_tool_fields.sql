@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE messages
+    ADD COLUMN name         TEXT,
+    ADD COLUMN tool_call_id TEXT,
+    ADD COLUMN tool_calls   jsonb[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,40 @@
+-- Add up migration script here
+
+-- Budgets are checked against executions by user, API key and thread, so the
+-- execution needs to know which key and thread it ran with.
+ALTER TABLE executions
+    ADD COLUMN thread_id  uuid,
+    ADD COLUMN api_key_id uuid;
+
+UPDATE executions
+SET thread_id = thread_versions.thread_id
+FROM thread_versions
+WHERE thread_versions.id = executions.thread_version_id;
+
+ALTER TABLE executions
+    ALTER COLUMN thread_id SET NOT NULL;
+
+CREATE INDEX idx_executions_thread_id_created_at ON executions (thread_id, created_at);
+CREATE INDEX idx_executions_api_key_id_created_at ON executions (api_key_id, created_at);
+CREATE INDEX idx_executions_executed_by_id_created_at ON executions (executed_by_id, created_at);
+
+CREATE TYPE budget_scope AS ENUM ('user', 'api_key', 'thread');
+CREATE TYPE budget_period AS ENUM ('daily', 'monthly');
+CREATE TYPE budget_enforcement AS ENUM ('hard', 'soft');
+
+CREATE TABLE budgets
+(
+    id          uuid PRIMARY KEY,
+    scope       budget_scope       NOT NULL,
+    scope_id    uuid               NOT NULL,
+    period      budget_period      NOT NULL,
+    token_limit BIGINT,
+    cost_limit  DOUBLE PRECISION,
+    currency    TEXT               NOT NULL DEFAULT 'USD',
+    enforcement budget_enforcement NOT NULL DEFAULT 'hard',
+    created_at  TIMESTAMP          NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMP          NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_budgets_scope_id ON budgets (scope_id);
+CREATE INDEX idx_budgets_created_at ON budgets (created_at);
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE executions
+    ADD COLUMN attempts jsonb[] NOT NULL DEFAULT '{}';
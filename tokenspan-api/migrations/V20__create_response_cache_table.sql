@@ -0,0 +1,11 @@
+-- Add up migration script here
+ALTER TABLE executions
+    ADD COLUMN cache_hit BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE response_cache
+(
+    key        TEXT PRIMARY KEY,
+    response   jsonb     NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,33 @@
+-- Add up migration script here
+CREATE TABLE datasets
+(
+    id          uuid PRIMARY KEY,
+    owner_id    uuid      NOT NULL,
+    thread_id   uuid,
+    name        TEXT      NOT NULL,
+    description TEXT,
+    created_at  TIMESTAMP NOT NULL,
+    updated_at  TIMESTAMP NOT NULL,
+
+    CONSTRAINT fk_datasets_owner_id FOREIGN KEY (owner_id) REFERENCES users (id),
+    CONSTRAINT fk_datasets_thread_id FOREIGN KEY (thread_id) REFERENCES threads (id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_datasets_owner_id ON datasets (owner_id);
+CREATE INDEX idx_datasets_thread_id ON datasets (thread_id);
+CREATE INDEX idx_datasets_created_at ON datasets (created_at);
+
+CREATE TABLE dataset_rows
+(
+    id              uuid PRIMARY KEY,
+    dataset_id      uuid      NOT NULL,
+    index           INT       NOT NULL,
+    variables       jsonb     NOT NULL,
+    expected_output TEXT,
+    created_at      TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP NOT NULL,
+
+    CONSTRAINT fk_dataset_rows_dataset_id FOREIGN KEY (dataset_id) REFERENCES datasets (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_dataset_rows_dataset_id_index ON dataset_rows (dataset_id, index);
@@ -0,0 +1,50 @@
+-- Add up migration script here
+CREATE TYPE run_status AS ENUM ('running', 'completed', 'cancelled');
+CREATE TYPE run_item_status AS ENUM ('pending', 'running', 'succeeded', 'failed', 'cancelled');
+
+CREATE TABLE runs
+(
+    id                uuid PRIMARY KEY,
+    owner_id          uuid       NOT NULL,
+    thread_version_id uuid       NOT NULL,
+    parameter_id      uuid       NOT NULL,
+    api_key_id        uuid       NOT NULL,
+    dataset_id        uuid,
+    concurrency       INT        NOT NULL,
+    status            run_status NOT NULL,
+    total             INT        NOT NULL,
+    succeeded         INT        NOT NULL DEFAULT 0,
+    failed            INT        NOT NULL DEFAULT 0,
+    finished_at       TIMESTAMP,
+    created_at        TIMESTAMP  NOT NULL,
+    updated_at        TIMESTAMP  NOT NULL,
+
+    CONSTRAINT fk_runs_owner_id FOREIGN KEY (owner_id) REFERENCES users (id),
+    CONSTRAINT fk_runs_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE CASCADE,
+    CONSTRAINT fk_runs_dataset_id FOREIGN KEY (dataset_id) REFERENCES datasets (id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_runs_owner_id ON runs (owner_id);
+CREATE INDEX idx_runs_thread_version_id ON runs (thread_version_id);
+CREATE INDEX idx_runs_created_at ON runs (created_at);
+
+-- One item per input row. The variables and expected output are copied so a
+-- run keeps its inputs when the dataset is edited afterwards.
+CREATE TABLE run_items
+(
+    id              uuid PRIMARY KEY,
+    run_id          uuid            NOT NULL,
+    index           INT             NOT NULL,
+    dataset_row_id  uuid,
+    variables       jsonb           NOT NULL,
+    expected_output TEXT,
+    status          run_item_status NOT NULL,
+    execution_id    uuid,
+    error           TEXT,
+    created_at      TIMESTAMP       NOT NULL,
+    updated_at      TIMESTAMP       NOT NULL,
+
+    CONSTRAINT fk_run_items_run_id FOREIGN KEY (run_id) REFERENCES runs (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_run_items_run_id_index ON run_items (run_id, index);
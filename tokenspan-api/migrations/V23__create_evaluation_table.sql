@@ -0,0 +1,48 @@
+-- Add up migration script here
+CREATE TYPE evaluator_kind AS ENUM (
+    'exact_match',
+    'contains',
+    'regex',
+    'json_valid',
+    'json_schema',
+    'max_length',
+    'latency',
+    'cost',
+    'similarity'
+);
+
+CREATE TABLE evaluators
+(
+    id                uuid PRIMARY KEY,
+    thread_version_id uuid             NOT NULL,
+    name              TEXT             NOT NULL,
+    kind              evaluator_kind   NOT NULL,
+    value             TEXT,
+    threshold         DOUBLE PRECISION,
+    weight            DOUBLE PRECISION NOT NULL DEFAULT 1,
+    created_at        TIMESTAMP        NOT NULL,
+    updated_at        TIMESTAMP        NOT NULL,
+
+    CONSTRAINT fk_evaluators_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_evaluators_thread_version_id ON evaluators (thread_version_id);
+
+CREATE TABLE evaluations
+(
+    id                uuid PRIMARY KEY,
+    execution_id      uuid             NOT NULL,
+    thread_version_id uuid             NOT NULL,
+    expected_output   TEXT,
+    results           jsonb[]          NOT NULL DEFAULT '{}',
+    score             DOUBLE PRECISION NOT NULL,
+    passed            BOOLEAN          NOT NULL,
+    created_at        TIMESTAMP        NOT NULL,
+    updated_at        TIMESTAMP        NOT NULL,
+
+    CONSTRAINT fk_evaluations_execution_id FOREIGN KEY (execution_id) REFERENCES executions (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_evaluations_execution_id ON evaluations (execution_id);
+CREATE INDEX idx_evaluations_thread_version_id ON evaluations (thread_version_id);
+CREATE INDEX idx_evaluations_created_at ON evaluations (created_at);
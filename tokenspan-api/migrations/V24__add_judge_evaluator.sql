@@ -0,0 +1,14 @@
+-- Add up migration script here
+ALTER TYPE evaluator_kind ADD VALUE 'judge';
+
+ALTER TABLE evaluators
+    ADD COLUMN judge_parameter_id uuid,
+    ADD COLUMN judge_api_key_id   uuid,
+    ADD COLUMN judge_max_score    DOUBLE PRECISION;
+
+-- A judge's execution points at the execution it graded, so its cost can be
+-- counted along with it.
+ALTER TABLE executions
+    ADD COLUMN parent_execution_id uuid;
+
+CREATE INDEX idx_executions_parent_execution_id ON executions (parent_execution_id);
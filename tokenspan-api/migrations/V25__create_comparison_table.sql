@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TABLE comparisons
+(
+    id               uuid PRIMARY KEY,
+    owner_id         uuid      NOT NULL,
+    dataset_id       uuid,
+    baseline_run_id  uuid      NOT NULL,
+    candidate_run_id uuid      NOT NULL,
+    created_at       TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP NOT NULL,
+
+    CONSTRAINT fk_comparisons_owner_id FOREIGN KEY (owner_id) REFERENCES users (id),
+    CONSTRAINT fk_comparisons_dataset_id FOREIGN KEY (dataset_id) REFERENCES datasets (id) ON DELETE SET NULL,
+    CONSTRAINT fk_comparisons_baseline_run_id FOREIGN KEY (baseline_run_id) REFERENCES runs (id) ON DELETE CASCADE,
+    CONSTRAINT fk_comparisons_candidate_run_id FOREIGN KEY (candidate_run_id) REFERENCES runs (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_comparisons_owner_id ON comparisons (owner_id);
+CREATE INDEX idx_comparisons_created_at ON comparisons (created_at);
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE thread_versions
+    ADD COLUMN variables jsonb[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,21 @@
+-- Add up migration script here
+
+-- A label names one published version of a thread, so callers can execute it
+-- by name and the label can be moved without touching them.
+CREATE TABLE thread_version_labels
+(
+    id                uuid PRIMARY KEY,
+    name              TEXT      NOT NULL,
+    thread_id         uuid      NOT NULL,
+    thread_version_id uuid      NOT NULL,
+    owner_id          uuid      NOT NULL,
+    created_at        TIMESTAMP NOT NULL,
+    updated_at        TIMESTAMP NOT NULL,
+
+    CONSTRAINT fk_thread_version_labels_thread_id FOREIGN KEY (thread_id) REFERENCES threads (id) ON DELETE CASCADE,
+    CONSTRAINT fk_thread_version_labels_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE CASCADE,
+    CONSTRAINT fk_thread_version_labels_owner_id FOREIGN KEY (owner_id) REFERENCES users (id)
+);
+
+CREATE UNIQUE INDEX idx_thread_version_labels_thread_id_name ON thread_version_labels (thread_id, name);
+CREATE INDEX idx_thread_version_labels_thread_version_id ON thread_version_labels (thread_version_id);
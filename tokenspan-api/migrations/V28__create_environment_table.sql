@@ -0,0 +1,42 @@
+-- Add up migration script here
+CREATE TYPE promotion_action AS ENUM ('promote', 'rollback');
+
+CREATE TABLE environments
+(
+    id                uuid PRIMARY KEY,
+    name              TEXT      NOT NULL,
+    thread_id         uuid      NOT NULL,
+    thread_version_id uuid,
+    owner_id          uuid      NOT NULL,
+    created_at        TIMESTAMP NOT NULL,
+    updated_at        TIMESTAMP NOT NULL,
+
+    CONSTRAINT fk_environments_thread_id FOREIGN KEY (thread_id) REFERENCES threads (id) ON DELETE CASCADE,
+    CONSTRAINT fk_environments_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE SET NULL,
+    CONSTRAINT fk_environments_owner_id FOREIGN KEY (owner_id) REFERENCES users (id)
+);
+
+CREATE UNIQUE INDEX idx_environments_thread_id_name ON environments (thread_id, name);
+
+-- Every change of an environment's version. `parent_id` is the promotion a
+-- rollback from this one returns to, so repeated rollbacks walk back through
+-- the promotions instead of flipping between two versions.
+CREATE TABLE environment_promotions
+(
+    id                          uuid PRIMARY KEY,
+    environment_id              uuid             NOT NULL,
+    thread_version_id           uuid             NOT NULL,
+    previous_thread_version_id  uuid,
+    action                      promotion_action NOT NULL,
+    parent_id                   uuid,
+    promoted_by_id              uuid             NOT NULL,
+    created_at                  TIMESTAMP        NOT NULL,
+    updated_at                  TIMESTAMP        NOT NULL,
+
+    CONSTRAINT fk_environment_promotions_environment_id FOREIGN KEY (environment_id) REFERENCES environments (id) ON DELETE CASCADE,
+    CONSTRAINT fk_environment_promotions_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE CASCADE,
+    CONSTRAINT fk_environment_promotions_parent_id FOREIGN KEY (parent_id) REFERENCES environment_promotions (id) ON DELETE SET NULL,
+    CONSTRAINT fk_environment_promotions_promoted_by_id FOREIGN KEY (promoted_by_id) REFERENCES users (id)
+);
+
+CREATE INDEX idx_environment_promotions_environment_id ON environment_promotions (environment_id, created_at);
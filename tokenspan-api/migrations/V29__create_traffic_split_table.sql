@@ -0,0 +1,30 @@
+-- Add up migration script here
+CREATE TYPE traffic_split_status AS ENUM ('active', 'ended');
+
+CREATE TABLE traffic_splits
+(
+    id             uuid PRIMARY KEY,
+    name           TEXT                 NOT NULL,
+    thread_id      uuid                 NOT NULL,
+    environment_id uuid                 NOT NULL,
+    arms           jsonb[]              NOT NULL DEFAULT '{}',
+    status         traffic_split_status NOT NULL,
+    owner_id       uuid                 NOT NULL,
+    ended_at       TIMESTAMP,
+    created_at     TIMESTAMP            NOT NULL,
+    updated_at     TIMESTAMP            NOT NULL,
+
+    CONSTRAINT fk_traffic_splits_thread_id FOREIGN KEY (thread_id) REFERENCES threads (id) ON DELETE CASCADE,
+    CONSTRAINT fk_traffic_splits_environment_id FOREIGN KEY (environment_id) REFERENCES environments (id) ON DELETE CASCADE,
+    CONSTRAINT fk_traffic_splits_owner_id FOREIGN KEY (owner_id) REFERENCES users (id)
+);
+
+CREATE INDEX idx_traffic_splits_environment_id ON traffic_splits (environment_id);
+CREATE UNIQUE INDEX idx_traffic_splits_environment_id_active ON traffic_splits (environment_id) WHERE status = 'active';
+
+ALTER TABLE executions
+    ADD COLUMN traffic_split_id uuid,
+    ADD COLUMN traffic_arm      TEXT,
+    ADD CONSTRAINT fk_executions_traffic_split_id FOREIGN KEY (traffic_split_id) REFERENCES traffic_splits (id) ON DELETE SET NULL;
+
+CREATE INDEX idx_executions_traffic_split_id ON executions (traffic_split_id);
@@ -0,0 +1,13 @@
+-- Add up migration script here
+-- Semvers are now unique within a thread. Older duplicates keep their number,
+-- with the row's version appended as build metadata.
+UPDATE thread_versions tv
+SET semver = tv.semver || CASE WHEN position('+' IN tv.semver) > 0 THEN '.' ELSE '+' END || tv.version
+WHERE EXISTS (SELECT 1
+              FROM thread_versions other
+              WHERE other.thread_id = tv.thread_id
+                AND other.semver = tv.semver
+                AND other.version < tv.version);
+
+DROP INDEX idx_thread_versions_semver;
+CREATE UNIQUE INDEX idx_thread_versions_thread_id_semver ON thread_versions (thread_id, semver);
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+-- Published thread versions are read-only. Every time an admin lifts that for
+-- a while is kept here, with why and until when, and who locked it again.
+CREATE TABLE thread_version_unlocks
+(
+    id                uuid PRIMARY KEY,
+    thread_version_id uuid      NOT NULL,
+    reason            TEXT      NOT NULL,
+    unlocked_by_id    uuid      NOT NULL,
+    expires_at        TIMESTAMP NOT NULL,
+    locked_at         TIMESTAMP,
+    locked_by_id      uuid,
+    created_at        TIMESTAMP NOT NULL,
+    updated_at        TIMESTAMP NOT NULL,
+
+    CONSTRAINT fk_thread_version_unlocks_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE CASCADE,
+    CONSTRAINT fk_thread_version_unlocks_unlocked_by_id FOREIGN KEY (unlocked_by_id) REFERENCES users (id),
+    CONSTRAINT fk_thread_version_unlocks_locked_by_id FOREIGN KEY (locked_by_id) REFERENCES users (id)
+);
+
+CREATE INDEX idx_thread_version_unlocks_thread_version_id ON thread_version_unlocks (thread_version_id, created_at);
@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+-- Deleting a published version takes an unlock and is recorded on it, so the
+-- unlock is kept once the version is gone.
+ALTER TABLE thread_version_unlocks
+    DROP CONSTRAINT fk_thread_version_unlocks_thread_version_id,
+    ADD COLUMN deleted_at    TIMESTAMP,
+    ADD COLUMN deleted_by_id uuid,
+    ADD CONSTRAINT fk_thread_version_unlocks_deleted_by_id FOREIGN KEY (deleted_by_id) REFERENCES users (id);
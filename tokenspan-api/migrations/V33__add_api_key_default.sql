@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- The key used for a provider when an execution does not name one.
+ALTER TABLE api_keys
+    ADD COLUMN IF NOT EXISTS is_default BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_api_keys_default ON api_keys (owner_id, provider_id) WHERE is_default;
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- A version is run by slug with its default parameter. Versions made before
+-- that keep at most their oldest default and take their oldest parameter when
+-- they have none.
+UPDATE parameters p
+SET is_default = FALSE
+WHERE p.is_default
+  AND EXISTS (SELECT 1
+              FROM parameters other
+              WHERE other.thread_version_id = p.thread_version_id
+                AND other.is_default
+                AND (other.created_at, other.id) < (p.created_at, p.id));
+
+UPDATE parameters
+SET is_default = TRUE
+WHERE id IN (SELECT DISTINCT ON (thread_version_id) id
+             FROM parameters p
+             WHERE NOT EXISTS (SELECT 1
+                               FROM parameters other
+                               WHERE other.thread_version_id = p.thread_version_id
+                                 AND other.is_default)
+             ORDER BY thread_version_id, created_at, id);
+
+CREATE UNIQUE INDEX idx_parameters_default ON parameters (thread_version_id) WHERE is_default;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+-- The promotion history is an audit trail, so a version an environment serves
+-- or was ever promoted to cannot be deleted. NO ACTION rather than RESTRICT so
+-- deleting a whole thread, which removes its environments too, still works.
+ALTER TABLE environments
+    DROP CONSTRAINT fk_environments_thread_version_id,
+    ADD CONSTRAINT fk_environments_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE NO ACTION;
+
+ALTER TABLE environment_promotions
+    DROP CONSTRAINT fk_environment_promotions_thread_version_id,
+    DROP CONSTRAINT fk_environment_promotions_parent_id,
+    ADD CONSTRAINT fk_environment_promotions_thread_version_id FOREIGN KEY (thread_version_id) REFERENCES thread_versions (id) ON DELETE NO ACTION,
+    ADD CONSTRAINT fk_environment_promotions_previous_thread_version_id FOREIGN KEY (previous_thread_version_id) REFERENCES thread_versions (id) ON DELETE NO ACTION,
+    ADD CONSTRAINT fk_environment_promotions_parent_id FOREIGN KEY (parent_id) REFERENCES environment_promotions (id) ON DELETE NO ACTION;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- The parameter a copy made on publish descends from, shared by all its later
+-- copies, so two versions can be diffed parameter by parameter even when names
+-- repeat. Parameters made before this have none and are matched by name.
+ALTER TABLE parameters
+    ADD COLUMN origin_id uuid;
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use data_encoding::HEXLOWER;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use reqwest_eventsource::{Event, EventSource};
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::domains::models::{
+    AuthScheme, ExecutionToolCallDelta, Function, Model, Parameter, Provider, ProviderKind, Usage,
+};
+use crate::prompts::{ChatMessage, ToolCall};
+
+/// A provider-agnostic chat completion request.
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub model: Model,
+    pub messages: Vec<ChatMessage>,
+    pub parameter: Parameter,
+    pub functions: Vec<Function>,
+}
+
+impl ChatRequest {
+    /// Hex SHA-256 over everything that shapes the provider's answer: the rendered
+    /// messages, the model, the parameter values and the tools. Ids and timestamps
+    /// of the parameter and functions are left out so copies share a key.
+    pub fn cache_key(&self) -> String {
+        let key = json!({
+            "model": self.model.id,
+            "messages": self.messages,
+            "parameter": {
+                "temperature": self.parameter.temperature,
+                "max_tokens": self.parameter.max_tokens,
+                "stop_sequences": self.parameter.stop_sequences,
+                "top_p": self.parameter.top_p,
+                "frequency_penalty": self.parameter.frequency_penalty,
+                "presence_penalty": self.parameter.presence_penalty,
+                "extra": self.parameter.extra,
+            },
+            "tools": self
+                .functions
+                .iter()
+                .map(|function| json!({
+                    "name": function.name,
+                    "description": function.description,
+                    "parameters": function.parameters,
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        HEXLOWER.encode(digest::digest(&digest::SHA256, key.to_string().as_bytes()).as_ref())
+    }
+}
+
+/// A chat completion normalized back from the provider's wire format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatResponse {
+    pub messages: Vec<ChatMessage>,
+    pub tool_calls: Vec<ToolCall>,
+    pub usage: Option<Usage>,
+    pub finish_reason: Option<String>,
+    /// The untouched provider response, stored on the execution for debugging.
+    pub raw: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+pub enum ChatStreamEvent {
+    Content(String),
+    ToolCall(ExecutionToolCallDelta),
+    Usage(Usage),
+    Finish(String),
+}
+
+pub type ChatStream = BoxStream<'static, Result<ChatStreamEvent>>;
+
+#[async_trait::async_trait]
+pub trait ProviderAdapter {
+    fn kind(&self) -> ProviderKind;
+
+    async fn chat_completion(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatResponse>;
+
+    /// Adapters without native streaming fall back to a single chunk built from
+    /// the complete response.
+    async fn chat_completion_stream(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatStream> {
+        let response = self.chat_completion(provider, api_key, request).await?;
+
+        let mut events = vec![];
+        for message in response.messages {
+            if !message.content.is_empty() {
+                events.push(Ok(ChatStreamEvent::Content(message.content)));
+            }
+        }
+        for (index, tool_call) in response.tool_calls.into_iter().enumerate() {
+            events.push(Ok(ChatStreamEvent::ToolCall(ExecutionToolCallDelta {
+                index: index as i32,
+                id: Some(tool_call.id),
+                name: Some(tool_call.name),
+                arguments: Some(tool_call.arguments),
+            })));
+        }
+        if let Some(usage) = response.usage {
+            events.push(Ok(ChatStreamEvent::Usage(usage)));
+        }
+        if let Some(finish_reason) = response.finish_reason {
+            events.push(Ok(ChatStreamEvent::Finish(finish_reason)));
+        }
+
+        Ok(futures::stream::iter(events).boxed())
+    }
+}
+
+pub type ProviderAdapterDyn = Arc<dyn ProviderAdapter + Send + Sync>;
+
+/// Maps each `ProviderKind` to the adapter that speaks its API.
+#[derive(Clone, Default)]
+pub struct ProviderAdapterRegistry {
+    adapters: HashMap<ProviderKind, ProviderAdapterDyn>,
+}
+
+impl ProviderAdapterRegistry {
+    pub fn register(mut self, adapter: ProviderAdapterDyn) -> Self {
+        self.adapters.insert(adapter.kind(), adapter);
+        self
+    }
+
+    pub fn get(&self, kind: ProviderKind) -> Result<ProviderAdapterDyn> {
+        self.adapters.get(&kind).cloned().ok_or(anyhow::anyhow!(
+            "no adapter registered for provider kind {}",
+            kind
+        ))
+    }
+}
+
+/// How an adapter authenticates when the provider keeps `AuthScheme::Native`.
+pub enum NativeAuth {
+    Bearer,
+    Header(&'static str),
+}
+
+/// Builds a POST to the provider, applying its auth scheme, static headers, query
+/// parameters and URL template. `path` is the adapter's default path below
+/// `base_url`; templates can refer to it as `{path}`, next to `{base_url}`,
+/// `{model}` and `{api_key}`.
+pub fn provider_request(
+    client: &reqwest::Client,
+    provider: &Provider,
+    api_key: &str,
+    model: &str,
+    path: &str,
+    native_auth: NativeAuth,
+) -> Result<reqwest::RequestBuilder> {
+    let substitute = |value: &str| value.replace("{api_key}", api_key);
+    let url = match &provider.url_template {
+        Some(template) => substitute(
+            &template
+                .replace("{base_url}", provider.base_url.trim_end_matches('/'))
+                .replace("{model}", model)
+                .replace("{path}", path),
+        ),
+        None => format!("{}/{}", provider.base_url.trim_end_matches('/'), path),
+    };
+    let auth_name = || {
+        provider.auth_name.clone().ok_or(anyhow::anyhow!(
+            "provider {} uses {} auth but has no auth name",
+            provider.slug,
+            provider.auth_scheme
+        ))
+    };
+
+    let mut builder = client.post(url);
+    builder = match (provider.auth_scheme, native_auth) {
+        (AuthScheme::Native, NativeAuth::Bearer) | (AuthScheme::Bearer, _) => {
+            builder.bearer_auth(api_key)
+        }
+        (AuthScheme::Native, NativeAuth::Header(name)) => builder.header(name, api_key),
+        (AuthScheme::Header, _) => builder.header(auth_name()?, api_key),
+        (AuthScheme::Query, _) => builder.query(&[(auth_name()?, api_key)]),
+        (AuthScheme::None, _) => builder,
+    };
+    for header in &provider.headers {
+        builder = builder.header(&header.name, substitute(&header.value));
+    }
+    let query = provider
+        .query_params
+        .iter()
+        .map(|param| (param.name.as_str(), substitute(&param.value)))
+        .collect::<Vec<_>>();
+
+    Ok(builder.query(&query))
+}
+
+#[derive(Debug, Error)]
+pub enum ProviderError {
+    #[error("provider returned {status}: {body}")]
+    Status {
+        status: u16,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+}
+
+impl ProviderError {
+    pub fn status(&self) -> u16 {
+        match self {
+            ProviderError::Status { status, .. } => *status,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ProviderError::Status { retry_after, .. } => *retry_after,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        let status = self.status();
+        status == 429 || status >= 500
+    }
+}
+
+/// Parses `Retry-After` given either in seconds or as an HTTP date.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now())
+        .to_std()
+        .ok()
+        .or(Some(Duration::ZERO))
+}
+
+/// Reads a JSON body, turning non-success statuses into a `ProviderError` that
+/// carries the provider's own message.
+pub async fn read_json(response: reqwest::Response) -> Result<serde_json::Value> {
+    let status = response.status();
+    let retry_after = retry_after(response.headers());
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(ProviderError::Status {
+            status: status.as_u16(),
+            body,
+            retry_after,
+        }
+        .into());
+    }
+
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Turns a server-sent event source into a `ChatStream`, using `parse` to map each
+/// `(event, data)` pair into zero or more normalized events.
+pub fn sse_stream<F>(mut event_source: EventSource, mut parse: F) -> ChatStream
+where
+    F: FnMut(&str, &str) -> Result<Vec<ChatStreamEvent>> + Send + 'static,
+{
+    let stream = async_stream::stream! {
+        while let Some(event) = event_source.next().await {
+            let message = match event {
+                Ok(Event::Open) => continue,
+                Ok(Event::Message(message)) => message,
+                Err(reqwest_eventsource::Error::StreamEnded) => break,
+                Err(reqwest_eventsource::Error::InvalidStatusCode(status)) => {
+                    yield Err(ProviderError::Status {
+                        status: status.as_u16(),
+                        body: String::new(),
+                        retry_after: None,
+                    }
+                    .into());
+                    break;
+                }
+                Err(e) => {
+                    yield Err(anyhow::anyhow!(e));
+                    break;
+                }
+            };
+
+            match parse(&message.event, &message.data) {
+                Ok(events) => {
+                    for event in events {
+                        yield Ok(event);
+                    }
+                }
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+        event_source.close();
+    };
+
+    stream.boxed()
+}
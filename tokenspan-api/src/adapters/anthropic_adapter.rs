@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use reqwest_eventsource::RequestBuilderExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::adapters::{
+    provider_request, read_json, sse_stream, ChatRequest, ChatResponse, ChatStream,
+    ChatStreamEvent, NativeAuth, ProviderAdapter, ProviderAdapterDyn,
+};
+use crate::domains::models::{ExecutionToolCallDelta, Provider, ProviderKind, Usage};
+use crate::prompts::{ChatMessage, PromptRole, ToolCall};
+
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Talks to the Anthropic Messages API.
+#[derive(Default)]
+pub struct AnthropicAdapter {
+    client: reqwest::Client,
+}
+
+impl AnthropicAdapter {
+    /// System prompts go into the top-level `system` field. Consecutive messages
+    /// from the same role are merged into one turn, because the API expects user
+    /// and assistant turns to alternate. Tool results are sent as user turns.
+    pub fn build_request(request: &ChatRequest) -> Value {
+        let system = request
+            .messages
+            .iter()
+            .filter(|message| message.role == PromptRole::System)
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let mut messages: Vec<Value> = vec![];
+        for message in request
+            .messages
+            .iter()
+            .filter(|message| message.role != PromptRole::System)
+        {
+            let role = match message.role {
+                PromptRole::Assistant => "assistant",
+                _ => "user",
+            };
+            let mut blocks = vec![];
+            if message.role == PromptRole::Tool {
+                blocks.push(json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id,
+                    "content": message.content,
+                }));
+            } else if !message.content.is_empty() || message.tool_calls.is_empty() {
+                blocks.push(json!({ "type": "text", "text": message.content }));
+            }
+            for tool_call in &message.tool_calls {
+                blocks.push(json!({
+                    "type": "tool_use",
+                    "id": tool_call.id,
+                    "name": tool_call.name,
+                    "input": serde_json::from_str::<Value>(&tool_call.arguments)
+                        .unwrap_or_else(|_| json!({})),
+                }));
+            }
+
+            match messages.last_mut() {
+                Some(last) if last["role"] == role => {
+                    if let Some(content) = last["content"].as_array_mut() {
+                        content.extend(blocks);
+                    }
+                }
+                _ => messages.push(json!({ "role": role, "content": blocks })),
+            }
+        }
+
+        let parameter = &request.parameter;
+        let mut body = json!({
+            "model": request.model.name,
+            "messages": messages,
+            "max_tokens": parameter.max_tokens,
+            "temperature": parameter.temperature,
+            "top_p": parameter.top_p,
+        });
+        if !system.is_empty() {
+            body["system"] = json!(system);
+        }
+        if !parameter.stop_sequences.is_empty() {
+            body["stop_sequences"] = json!(parameter.stop_sequences);
+        }
+        if !request.functions.is_empty() {
+            body["tools"] = request
+                .functions
+                .iter()
+                .map(|function| {
+                    json!({
+                        "name": function.name,
+                        "description": function.description,
+                        "input_schema": function.parameters,
+                    })
+                })
+                .collect();
+        }
+
+        body
+    }
+
+    fn request_builder(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        body: &Value,
+    ) -> Result<reqwest::RequestBuilder> {
+        let builder = provider_request(
+            &self.client,
+            provider,
+            api_key,
+            body["model"].as_str().unwrap_or_default(),
+            "messages",
+            NativeAuth::Header("x-api-key"),
+        )?;
+
+        Ok(builder
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(body))
+    }
+}
+
+#[derive(Deserialize)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: i32,
+    #[serde(default)]
+    output_tokens: i32,
+}
+
+impl From<AnthropicUsage> for Usage {
+    fn from(value: AnthropicUsage) -> Self {
+        Self {
+            input_tokens: value.input_tokens,
+            output_tokens: value.output_tokens,
+            total_tokens: value.input_tokens + value.output_tokens,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct MessageDelta {
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MessageStart {
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: MessageStart,
+    },
+    ContentBlockStart {
+        index: i32,
+        content_block: ContentBlock,
+    },
+    ContentBlockDelta {
+        index: i32,
+        delta: StreamDelta,
+    },
+    MessageDelta {
+        delta: MessageDelta,
+        usage: Option<AnthropicUsage>,
+    },
+    Error {
+        error: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[async_trait::async_trait]
+impl ProviderAdapter for AnthropicAdapter {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Anthropic
+    }
+
+    async fn chat_completion(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatResponse> {
+        let body = Self::build_request(request);
+        let response = self
+            .request_builder(provider, api_key, &body)?
+            .send()
+            .await?;
+        let raw = read_json(response).await?;
+        let response: MessagesResponse = serde_json::from_value(raw.clone())?;
+
+        let mut content = String::new();
+        let mut tool_calls = vec![];
+        for block in response.content {
+            match block {
+                ContentBlock::Text { text } => content.push_str(&text),
+                ContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    id,
+                    name,
+                    arguments: input.to_string(),
+                }),
+                ContentBlock::Other => {}
+            }
+        }
+
+        Ok(ChatResponse {
+            messages: vec![ChatMessage::assistant(content)],
+            tool_calls,
+            usage: response.usage.map(Usage::from),
+            finish_reason: response.stop_reason,
+            raw,
+        })
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatStream> {
+        let mut body = Self::build_request(request);
+        body["stream"] = json!(true);
+        let event_source = self
+            .request_builder(provider, api_key, &body)?
+            .eventsource()?;
+
+        // Input tokens arrive with `message_start`, output tokens with `message_delta`.
+        let mut input_tokens = 0;
+        Ok(sse_stream(event_source, move |_, data| {
+            let event = match serde_json::from_str::<StreamEvent>(data)? {
+                StreamEvent::MessageStart { message } => {
+                    input_tokens = message.usage.map(|u| u.input_tokens).unwrap_or_default();
+                    return Ok(vec![]);
+                }
+                StreamEvent::ContentBlockStart {
+                    index,
+                    content_block: ContentBlock::ToolUse { id, name, .. },
+                } => ChatStreamEvent::ToolCall(ExecutionToolCallDelta {
+                    index,
+                    id: Some(id),
+                    name: Some(name),
+                    arguments: None,
+                }),
+                StreamEvent::ContentBlockDelta {
+                    delta: StreamDelta::TextDelta { text },
+                    ..
+                } => ChatStreamEvent::Content(text),
+                StreamEvent::ContentBlockDelta {
+                    index,
+                    delta: StreamDelta::InputJsonDelta { partial_json },
+                } => ChatStreamEvent::ToolCall(ExecutionToolCallDelta {
+                    index,
+                    id: None,
+                    name: None,
+                    arguments: Some(partial_json),
+                }),
+                StreamEvent::MessageDelta { delta, usage } => {
+                    let mut events = vec![];
+                    if let Some(usage) = usage {
+                        events.push(ChatStreamEvent::Usage(Usage::from(AnthropicUsage {
+                            input_tokens,
+                            output_tokens: usage.output_tokens,
+                        })));
+                    }
+                    if let Some(stop_reason) = delta.stop_reason {
+                        events.push(ChatStreamEvent::Finish(stop_reason));
+                    }
+                    return Ok(events);
+                }
+                StreamEvent::Error { error } => {
+                    return Err(anyhow::anyhow!("provider returned an error: {}", error))
+                }
+                _ => return Ok(vec![]),
+            };
+
+            Ok(vec![event])
+        }))
+    }
+}
+
+impl From<AnthropicAdapter> for ProviderAdapterDyn {
+    fn from(value: AnthropicAdapter) -> Self {
+        Arc::new(value) as Self
+    }
+}
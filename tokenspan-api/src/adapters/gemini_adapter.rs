@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use reqwest_eventsource::RequestBuilderExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::adapters::{
+    provider_request, read_json, sse_stream, ChatRequest, ChatResponse, ChatStream,
+    ChatStreamEvent, NativeAuth, ProviderAdapter, ProviderAdapterDyn,
+};
+use crate::domains::models::{ExecutionToolCallDelta, Provider, ProviderKind, Usage};
+use crate::prompts::{ChatMessage, PromptRole, ToolCall};
+
+/// Talks to the Gemini `generateContent` API.
+#[derive(Default)]
+pub struct GeminiAdapter {
+    client: reqwest::Client,
+}
+
+impl GeminiAdapter {
+    /// System prompts become `systemInstruction`, assistant turns use the `model` role,
+    /// tool results become `functionResponse` parts and consecutive turns from the
+    /// same role are merged into one `contents` entry.
+    pub fn build_request(request: &ChatRequest) -> Value {
+        let system = request
+            .messages
+            .iter()
+            .filter(|message| message.role == PromptRole::System)
+            .map(|message| json!({ "text": message.content }))
+            .collect::<Vec<_>>();
+
+        let mut contents: Vec<Value> = vec![];
+        for (index, message) in request
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.role != PromptRole::System)
+        {
+            let role = match message.role {
+                PromptRole::Assistant => "model",
+                _ => "user",
+            };
+            let mut parts = vec![];
+            if message.role == PromptRole::Tool {
+                // Function responses are matched by name, so look it up from the
+                // nearest call with the id, in case an older turn reused it.
+                let name = request.messages[..index]
+                    .iter()
+                    .rev()
+                    .flat_map(|message| message.tool_calls.iter())
+                    .find(|tool_call| Some(&tool_call.id) == message.tool_call_id.as_ref())
+                    .map(|tool_call| tool_call.name.clone())
+                    .unwrap_or_default();
+                let response = match serde_json::from_str::<Value>(&message.content) {
+                    Ok(response @ Value::Object(_)) => response,
+                    Ok(response) => json!({ "content": response }),
+                    Err(_) => json!({ "content": message.content }),
+                };
+                parts.push(json!({ "functionResponse": { "name": name, "response": response } }));
+            } else if !message.content.is_empty() || message.tool_calls.is_empty() {
+                parts.push(json!({ "text": message.content }));
+            }
+            for tool_call in &message.tool_calls {
+                parts.push(json!({
+                    "functionCall": {
+                        "name": tool_call.name,
+                        "args": serde_json::from_str::<Value>(&tool_call.arguments)
+                            .unwrap_or_else(|_| json!({})),
+                    }
+                }));
+            }
+
+            match contents.last_mut() {
+                Some(last) if last["role"] == role => {
+                    if let Some(last_parts) = last["parts"].as_array_mut() {
+                        last_parts.extend(parts);
+                    }
+                }
+                _ => contents.push(json!({ "role": role, "parts": parts })),
+            }
+        }
+
+        let parameter = &request.parameter;
+        let mut generation_config = json!({
+            "maxOutputTokens": parameter.max_tokens,
+            "temperature": parameter.temperature,
+            "topP": parameter.top_p,
+        });
+        if !parameter.stop_sequences.is_empty() {
+            generation_config["stopSequences"] = json!(parameter.stop_sequences);
+        }
+        if parameter.frequency_penalty != 0.0 {
+            generation_config["frequencyPenalty"] = json!(parameter.frequency_penalty);
+        }
+        if parameter.presence_penalty != 0.0 {
+            generation_config["presencePenalty"] = json!(parameter.presence_penalty);
+        }
+
+        let mut body = json!({
+            "contents": contents,
+            "generationConfig": generation_config,
+        });
+        if !system.is_empty() {
+            body["systemInstruction"] = json!({ "parts": system });
+        }
+        if !request.functions.is_empty() {
+            let declarations = request
+                .functions
+                .iter()
+                .map(|function| {
+                    json!({
+                        "name": function.name,
+                        "description": function.description,
+                        "parameters": function.parameters,
+                    })
+                })
+                .collect::<Vec<_>>();
+            body["tools"] = json!([{ "functionDeclarations": declarations }]);
+        }
+
+        body
+    }
+
+    /// The model is part of the URL rather than the body.
+    fn request_builder(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+        method: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let builder = provider_request(
+            &self.client,
+            provider,
+            api_key,
+            &request.model.name,
+            &format!("models/{}:{}", request.model.name, method),
+            NativeAuth::Header("x-goog-api-key"),
+        )?;
+
+        Ok(builder.json(&Self::build_request(request)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: i32,
+    #[serde(default)]
+    candidates_token_count: i32,
+    #[serde(default)]
+    total_token_count: i32,
+}
+
+impl From<UsageMetadata> for Usage {
+    fn from(value: UsageMetadata) -> Self {
+        Self {
+            input_tokens: value.prompt_token_count,
+            output_tokens: value.candidates_token_count,
+            total_tokens: value.total_token_count,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    text: Option<String>,
+    function_call: Option<FunctionCall>,
+}
+
+#[derive(Deserialize, Default)]
+struct Content {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    #[serde(default)]
+    content: Content,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    usage_metadata: Option<UsageMetadata>,
+}
+
+impl GenerateContentResponse {
+    /// Gemini does not assign ids to function calls, so each gets a random one.
+    fn into_parts(self) -> (String, Vec<ToolCall>, Option<String>, Option<Usage>) {
+        let mut content = String::new();
+        let mut tool_calls = vec![];
+        let mut finish_reason = None;
+        if let Some(candidate) = self.candidates.into_iter().next() {
+            for part in candidate.content.parts {
+                if let Some(text) = part.text {
+                    content.push_str(&text);
+                }
+                if let Some(function_call) = part.function_call {
+                    tool_calls.push(ToolCall {
+                        id: tool_call_id(),
+                        name: function_call.name,
+                        arguments: function_call.args.to_string(),
+                    });
+                }
+            }
+            finish_reason = candidate.finish_reason;
+        }
+
+        (
+            content,
+            tool_calls,
+            finish_reason,
+            self.usage_metadata.map(Usage::from),
+        )
+    }
+}
+
+/// Unique across the conversation, so a tool result never pairs with a call
+/// from an earlier turn.
+fn tool_call_id() -> String {
+    format!("call_{}", Uuid::new_v4().simple())
+}
+
+#[async_trait::async_trait]
+impl ProviderAdapter for GeminiAdapter {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Gemini
+    }
+
+    async fn chat_completion(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatResponse> {
+        let response = self
+            .request_builder(provider, api_key, request, "generateContent")?
+            .send()
+            .await?;
+        let raw = read_json(response).await?;
+        let response: GenerateContentResponse = serde_json::from_value(raw.clone())?;
+        let (content, tool_calls, finish_reason, usage) = response.into_parts();
+
+        Ok(ChatResponse {
+            messages: vec![ChatMessage::assistant(content)],
+            tool_calls,
+            usage,
+            finish_reason,
+            raw,
+        })
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatStream> {
+        let event_source = self
+            .request_builder(provider, api_key, request, "streamGenerateContent")?
+            .query(&[("alt", "sse")])
+            .eventsource()?;
+
+        // Every chunk repeats the running usage totals, so only the last one counts.
+        let mut tool_call_index = 0;
+        Ok(sse_stream(event_source, move |_, data| {
+            let chunk = serde_json::from_str::<GenerateContentResponse>(data)?;
+            let (content, tool_calls, finish_reason, usage) = chunk.into_parts();
+
+            let mut events = vec![];
+            if !content.is_empty() {
+                events.push(ChatStreamEvent::Content(content));
+            }
+            for tool_call in tool_calls {
+                events.push(ChatStreamEvent::ToolCall(ExecutionToolCallDelta {
+                    index: tool_call_index,
+                    id: Some(tool_call.id),
+                    name: Some(tool_call.name),
+                    arguments: Some(tool_call.arguments),
+                }));
+                tool_call_index += 1;
+            }
+            if let Some(usage) = usage {
+                events.push(ChatStreamEvent::Usage(usage));
+            }
+            if let Some(finish_reason) = finish_reason {
+                events.push(ChatStreamEvent::Finish(finish_reason));
+            }
+
+            Ok(events)
+        }))
+    }
+}
+
+impl From<GeminiAdapter> for ProviderAdapterDyn {
+    fn from(value: GeminiAdapter) -> Self {
+        Arc::new(value) as Self
+    }
+}
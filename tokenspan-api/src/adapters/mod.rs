@@ -0,0 +1,13 @@
+pub use adapter::*;
+pub use anthropic_adapter::*;
+pub use gemini_adapter::*;
+pub use openai_adapter::*;
+pub use retry::*;
+pub use tool_handler::*;
+
+mod adapter;
+mod anthropic_adapter;
+mod gemini_adapter;
+mod openai_adapter;
+mod retry;
+mod tool_handler;
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_openai::types::{
+    ChatChoiceStream, ChatCompletionRequestMessage, ChatCompletionToolArgs, ChatCompletionToolType,
+    CompletionUsage, CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
+    CreateChatCompletionResponse, FunctionObjectArgs,
+};
+use reqwest_eventsource::RequestBuilderExt;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::adapters::{
+    provider_request, read_json, sse_stream, ChatRequest, ChatResponse, ChatStream,
+    ChatStreamEvent, NativeAuth, ProviderAdapter, ProviderAdapterDyn,
+};
+use crate::domains::models::{ExecutionToolCallDelta, Provider, ProviderKind, Usage};
+use crate::prompts::{ChatMessage, ToolCall};
+
+#[derive(Default)]
+pub struct OpenAIAdapter {
+    client: reqwest::Client,
+}
+
+impl OpenAIAdapter {
+    fn build_request(request: &ChatRequest) -> Result<CreateChatCompletionRequest> {
+        let mut messages: Vec<ChatCompletionRequestMessage> = vec![];
+        for message in request.messages.iter().cloned() {
+            messages.push(message.try_into()?);
+        }
+
+        let parameter = request.parameter.clone();
+        let mut chat_request = CreateChatCompletionRequestArgs::default()
+            .model(request.model.name.clone())
+            .max_tokens(parameter.max_tokens as u16)
+            .temperature(parameter.temperature)
+            .top_p(parameter.top_p)
+            .frequency_penalty(parameter.frequency_penalty)
+            .presence_penalty(parameter.presence_penalty)
+            .stop(parameter.stop_sequences)
+            .messages(messages)
+            .build()
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        let mut tools = vec![];
+        if !request.functions.is_empty() {
+            for function in request.functions.iter().cloned() {
+                let tool_args = ChatCompletionToolArgs::default()
+                    .r#type(ChatCompletionToolType::Function)
+                    .function(
+                        FunctionObjectArgs::default()
+                            .name(function.name)
+                            .description(function.description)
+                            .parameters(function.parameters)
+                            .build()
+                            .map_err(|e| anyhow::anyhow!(e.to_string()))?,
+                    )
+                    .build()
+                    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+                tools.push(tool_args);
+            }
+
+            chat_request.tools = Some(tools);
+        }
+
+        Ok(chat_request)
+    }
+
+    fn request_builder(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<reqwest::RequestBuilder> {
+        provider_request(
+            &self.client,
+            provider,
+            api_key,
+            &request.model.name,
+            "chat/completions",
+            NativeAuth::Bearer,
+        )
+    }
+}
+
+fn to_usage(usage: CompletionUsage) -> Usage {
+    Usage {
+        input_tokens: usage.prompt_tokens as i32,
+        output_tokens: usage.completion_tokens as i32,
+        total_tokens: usage.total_tokens as i32,
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionChunk {
+    #[serde(default)]
+    choices: Vec<ChatChoiceStream>,
+    usage: Option<CompletionUsage>,
+}
+
+impl ChatCompletionChunk {
+    fn into_events(self) -> Vec<ChatStreamEvent> {
+        let mut events = vec![];
+        for choice in self.choices {
+            if let Some(content) = choice.delta.content.filter(|c| !c.is_empty()) {
+                events.push(ChatStreamEvent::Content(content));
+            }
+            for tool_call in choice.delta.tool_calls.unwrap_or_default() {
+                let (name, arguments) = tool_call
+                    .function
+                    .map(|function| (function.name, function.arguments))
+                    .unwrap_or_default();
+                events.push(ChatStreamEvent::ToolCall(ExecutionToolCallDelta {
+                    index: tool_call.index,
+                    id: tool_call.id,
+                    name,
+                    arguments,
+                }));
+            }
+            if let Some(finish_reason) = choice.finish_reason {
+                if let Some(finish_reason) = json!(finish_reason).as_str() {
+                    events.push(ChatStreamEvent::Finish(finish_reason.to_string()));
+                }
+            }
+        }
+        if let Some(usage) = self.usage {
+            events.push(ChatStreamEvent::Usage(to_usage(usage)));
+        }
+
+        events
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderAdapter for OpenAIAdapter {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::OpenAI
+    }
+
+    async fn chat_completion(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatResponse> {
+        let chat_request = Self::build_request(request)?;
+        let response = self
+            .request_builder(provider, api_key, request)?
+            .json(&chat_request)
+            .send()
+            .await?;
+        let raw = read_json(response).await?;
+        let response: CreateChatCompletionResponse = serde_json::from_value(raw.clone())?;
+
+        let choice = response.choices.first();
+        let messages = choice
+            .and_then(|choice| choice.message.content.clone())
+            .map(|content| vec![ChatMessage::assistant(content)])
+            .unwrap_or_default();
+        let tool_calls = choice
+            .and_then(|choice| choice.message.tool_calls.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tool_call| ToolCall {
+                id: tool_call.id,
+                name: tool_call.function.name,
+                arguments: tool_call.function.arguments,
+            })
+            .collect();
+        let finish_reason = choice
+            .and_then(|choice| choice.finish_reason)
+            .and_then(|finish_reason| json!(finish_reason).as_str().map(String::from));
+
+        Ok(ChatResponse {
+            messages,
+            tool_calls,
+            usage: response.usage.clone().map(to_usage),
+            finish_reason,
+            raw,
+        })
+    }
+
+    /// Streams through raw JSON because `stream_options` is not modelled by
+    /// async-openai yet and is the only way to get usage back from a stream.
+    async fn chat_completion_stream(
+        &self,
+        provider: &Provider,
+        api_key: &str,
+        request: &ChatRequest,
+    ) -> Result<ChatStream> {
+        let mut body = serde_json::to_value(Self::build_request(request)?)?;
+        body["stream"] = json!(true);
+        body["stream_options"] = json!({ "include_usage": true });
+
+        let event_source = self
+            .request_builder(provider, api_key, request)?
+            .json(&body)
+            .eventsource()?;
+
+        Ok(sse_stream(event_source, |_, data| {
+            if data == "[DONE]" {
+                return Ok(vec![]);
+            }
+
+            Ok(serde_json::from_str::<ChatCompletionChunk>(data)?.into_events())
+        }))
+    }
+}
+
+impl From<OpenAIAdapter> for ProviderAdapterDyn {
+    fn from(value: OpenAIAdapter) -> Self {
+        Arc::new(value) as Self
+    }
+}
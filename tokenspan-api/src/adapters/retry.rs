@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use async_graphql::InputObject;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::adapters::ProviderError;
+use crate::app::REQUEST_TIMEOUT;
+
+/// Most attempts a policy can make per model.
+pub const MAX_RETRY_ATTEMPTS: i32 = 5;
+
+/// How much of an execution can pass before no more retries are started, so it
+/// finishes, and is saved with its attempts, inside the request timeout.
+pub const RETRY_WINDOW: Duration = REQUEST_TIMEOUT.saturating_sub(Duration::from_secs(2));
+
+/// How often a provider call is retried and how long to wait in between. The
+/// default makes a single attempt.
+#[derive(Debug, Clone, Deserialize, InputObject)]
+#[graphql(input_name = "RetryPolicyInput")]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts per model, including the first one, up to `MAX_RETRY_ATTEMPTS`.
+    #[graphql(default = 1)]
+    pub max_attempts: i32,
+    #[graphql(default = 500)]
+    pub initial_backoff_ms: i64,
+    /// Upper bound for a single wait, at most the retry window. A `Retry-After`
+    /// longer than this gives up on the model instead of waiting.
+    #[graphql(default = 5000)]
+    pub max_backoff_ms: i64,
+    #[graphql(default = 2.0)]
+    pub multiplier: f64,
+    /// Randomizes each wait between half and all of the computed backoff.
+    #[graphql(default = true)]
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 5000,
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Checks a caller's policy fits in the retry window.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_RETRY_ATTEMPTS).contains(&self.max_attempts) {
+            return Err(format!(
+                "max_attempts must be between 1 and {}",
+                MAX_RETRY_ATTEMPTS
+            ));
+        }
+        let window_ms = RETRY_WINDOW.as_millis() as i64;
+        if !(0..=window_ms).contains(&self.max_backoff_ms) {
+            return Err(format!(
+                "max_backoff_ms must be between 0 and {}",
+                window_ms
+            ));
+        }
+        if !(0..=self.max_backoff_ms).contains(&self.initial_backoff_ms) {
+            return Err("initial_backoff_ms must be between 0 and max_backoff_ms".to_string());
+        }
+        if !(self.multiplier >= 1.0 && self.multiplier.is_finite()) {
+            return Err("multiplier must be at least 1".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts.max(1) as u32
+    }
+
+    /// How long to wait after the given failed attempt (starting at 1) before the
+    /// next one, or `None` if the error should not be retried.
+    pub fn backoff(&self, attempt: u32, error: &anyhow::Error) -> Option<Duration> {
+        if attempt >= self.max_attempts() || !is_retryable(error) {
+            return None;
+        }
+
+        let max_backoff = Duration::from_millis(self.max_backoff_ms.max(0) as u64);
+        if let Some(retry_after) = error
+            .downcast_ref::<ProviderError>()
+            .and_then(|e| e.retry_after())
+        {
+            return (retry_after <= max_backoff).then_some(retry_after);
+        }
+
+        let backoff = self.initial_backoff_ms.max(0) as f64
+            * self.multiplier.max(1.0).powi(attempt as i32 - 1);
+        let backoff = Duration::from_millis(backoff as u64).min(max_backoff);
+        if !self.jitter {
+            return Some(backoff);
+        }
+
+        let half = backoff / 2;
+        Some(half + half.mul_f64(rand::thread_rng().gen::<f64>()))
+    }
+}
+
+/// Rate limits, server errors, timeouts and connection failures are worth another
+/// attempt; anything else will fail the same way again.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    if let Some(e) = error.downcast_ref::<ProviderError>() {
+        return e.is_retryable();
+    }
+    if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+        return e.is_timeout() || e.is_connect();
+    }
+
+    false
+}
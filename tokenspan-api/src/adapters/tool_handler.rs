@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::adapters::read_json;
+use crate::domains::models::Function;
+
+/// Answers a tool call made by the model during an execution.
+#[async_trait::async_trait]
+pub trait ToolHandler {
+    async fn call(&self, function: &Function, arguments: Value) -> Result<Value>;
+}
+
+pub type ToolHandlerDyn = Arc<dyn ToolHandler + Send + Sync>;
+
+/// Forwards tool calls to an HTTP endpoint as `{ "name", "arguments" }` and uses
+/// the JSON it returns as the tool result.
+pub struct WebhookToolHandler {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookToolHandler {
+    pub fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for WebhookToolHandler {
+    async fn call(&self, function: &Function, arguments: Value) -> Result<Value> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "name": function.name,
+                "arguments": arguments,
+            }))
+            .send()
+            .await?;
+
+        read_json(response).await
+    }
+}
+
+impl From<WebhookToolHandler> for ToolHandlerDyn {
+    fn from(value: WebhookToolHandler) -> Self {
+        Arc::new(value) as Self
+    }
+}
+
+/// Tool handlers keyed by function name.
+#[derive(Clone, Default)]
+pub struct ToolHandlerRegistry {
+    handlers: HashMap<String, ToolHandlerDyn>,
+}
+
+impl ToolHandlerRegistry {
+    pub fn register(mut self, name: impl Into<String>, handler: ToolHandlerDyn) -> Self {
+        self.handlers.insert(name.into(), handler);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<ToolHandlerDyn> {
+        self.handlers.get(name).cloned()
+    }
+}
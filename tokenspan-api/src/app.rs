@@ -1,7 +1,6 @@
 use std::time::Duration;
 
 use anyhow::Result;
-use async_graphql_axum::GraphQLSubscription;
 use axum::extract::MatchedPath;
 use axum::http::{Request, StatusCode};
 use axum::response::IntoResponse;
@@ -17,10 +16,15 @@ use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
 use crate::configs::{AppConfig, AppEnv};
-use crate::graphql::{build_schema, graphiql, graphql_handler, graphql_sandbox};
+use crate::graphql::{
+    build_schema, graphiql, graphql_handler, graphql_sandbox, graphql_ws_handler,
+};
 use crate::state::AppState;
 use crate::{configs, domains, guards};
 
+/// How long a request may take before it is cut off.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
 async fn handler_404() -> impl IntoResponse {
     (
         StatusCode::NOT_FOUND,
@@ -77,7 +81,7 @@ pub async fn make_app_with_state(config: AppConfig, state: AppState) -> Result<R
         .on_response(trace::DefaultOnResponse::new().level(Level::INFO));
 
     let cors_layer = CorsLayer::permissive();
-    let timeout_layer = TimeoutLayer::new(Duration::from_secs(10));
+    let timeout_layer = TimeoutLayer::new(REQUEST_TIMEOUT);
 
     let schema = build_schema(state.clone()).await;
 
@@ -85,7 +89,7 @@ pub async fn make_app_with_state(config: AppConfig, state: AppState) -> Result<R
         .route("/graphql", get(graphql_sandbox).post(graphql_handler))
         .route("/graphiql", get(graphiql))
         .nest("/api/:version", domains::ApiRouter::new())
-        .route_service("/ws", GraphQLSubscription::new(schema.clone()))
+        .route("/ws", get(graphql_ws_handler))
         .fallback(handler_404)
         .layer(middleware::from_fn_with_state(
             config.clone(),
@@ -1,7 +1,11 @@
+use std::collections::HashMap;
+
 use config::{Config, Environment, File};
 use dotenv::dotenv;
 use serde::Deserialize;
 
+use crate::adapters::RetryPolicy;
+
 #[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
 pub enum AppEnv {
     #[serde(rename = "development")]
@@ -49,6 +53,40 @@ pub struct EncryptionConfig {
     pub secret: String,
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ToolConfig {
+    /// Webhook URLs that answer tool calls, keyed by function name.
+    #[serde(default)]
+    pub handlers: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum CacheBackend {
+    #[default]
+    #[serde(rename = "memory")]
+    Memory,
+    #[serde(rename = "postgres")]
+    Postgres,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    pub backend: CacheBackend,
+    pub ttl: u64,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: CacheBackend::Memory,
+            ttl: 3600,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppConfig {
     pub env: AppEnv,
@@ -57,6 +95,13 @@ pub struct AppConfig {
     pub log: LogConfig,
     pub auth: AuthConfig,
     pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub tool: ToolConfig,
+    /// Retry policy for executions that don't bring their own.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
 }
 
 impl AppConfig {
@@ -18,6 +18,9 @@ pub struct ApiKey {
     pub key: String,
     pub owner_id: Uuid,
     pub provider_id: Uuid,
+    /// Used for the provider when an execution does not name a key. The first
+    /// key of each provider starts out as the default.
+    pub is_default: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
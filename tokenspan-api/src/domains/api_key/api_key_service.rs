@@ -3,7 +3,7 @@ use std::sync::Arc;
 use anyhow::Result;
 use chrono::Utc;
 use dojo_orm::pagination::Pagination;
-use dojo_orm::predicates::*;
+use dojo_orm::prelude::*;
 use dojo_orm::Database;
 use magic_crypt::{MagicCrypt256, MagicCryptTrait};
 use typed_builder::TypedBuilder;
@@ -18,6 +18,8 @@ pub trait ApiKeyServiceExt {
     async fn paginate(&self, args: ApiKeyArgs) -> Result<Pagination<ApiKey>>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<ApiKey>>;
     async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<ApiKey>>;
+    async fn find_by_provider(&self, owner_id: &Uuid, provider_id: &Uuid)
+        -> Result<Option<ApiKey>>;
     async fn create(&self, input: ApiKeyCreateInput, owner_id: Uuid) -> Result<ApiKey>;
     async fn update_by_id(&self, id: &Uuid, input: ApiKeyUpdateInput) -> Result<ApiKey>;
     async fn delete_by_id(&self, id: &Uuid) -> Result<ApiKey>;
@@ -77,13 +79,36 @@ impl ApiKeyServiceExt for ApiKeyService {
             .await
     }
 
+    /// The owner's default key for the provider, or any of their keys for it
+    /// when none is the default.
+    async fn find_by_provider(
+        &self,
+        owner_id: &Uuid,
+        provider_id: &Uuid,
+    ) -> Result<Option<ApiKey>> {
+        self.db
+            .bind::<ApiKey>()
+            .where_by(and(&[
+                equals("owner_id", owner_id),
+                equals("provider_id", provider_id),
+            ]))
+            .order_by(desc("is_default"))
+            .first()
+            .await
+    }
+
     async fn create(&self, input: ApiKeyCreateInput, owner_id: Uuid) -> Result<ApiKey> {
+        let is_default = self
+            .find_by_provider(&owner_id, &input.provider_id)
+            .await?
+            .is_none();
         let input = ApiKey {
             id: Uuid::new_v4(),
             owner_id,
             name: input.name,
             key: self.encrypt(input.key),
             provider_id: input.provider_id,
+            is_default,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
         };
@@ -92,6 +117,20 @@ impl ApiKeyServiceExt for ApiKeyService {
     }
 
     async fn update_by_id(&self, id: &Uuid, input: ApiKeyUpdateInput) -> Result<ApiKey> {
+        if input.is_default == Some(true) {
+            let api_key = self
+                .find_by_id(id)
+                .await?
+                .ok_or(anyhow::anyhow!("API key not found"))?;
+            let conn = self.db.get().await?;
+            conn.execute(
+                "UPDATE api_keys SET is_default = FALSE \
+                 WHERE owner_id = $1 AND provider_id = $2 AND id <> $3 AND is_default",
+                &[&api_key.owner_id, &api_key.provider_id, id],
+            )
+            .await?;
+        }
+
         self.db
             .update(&input)
             .where_by(equals("id", id))
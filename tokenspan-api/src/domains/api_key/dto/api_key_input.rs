@@ -13,4 +13,6 @@ pub struct ApiKeyCreateInput {
 #[derive(InputObject, UpdateModel)]
 pub struct ApiKeyUpdateInput {
     pub name: Option<String>,
+    /// Making a key the default takes the flag from the provider's other keys.
+    pub is_default: Option<bool>,
 }
@@ -0,0 +1,41 @@
+use async_graphql::ErrorExtensions;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::domains::models::BudgetScope;
+
+#[derive(Debug, Error)]
+pub enum BudgetError {
+    #[error("{scope} budget {id} exceeded: {reason}")]
+    Exceeded {
+        id: Uuid,
+        scope: BudgetScope,
+        reason: String,
+    },
+
+    #[error("a budget needs a token limit, a cost limit or both")]
+    NoLimit,
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl ErrorExtensions for BudgetError {
+    fn extend(&self) -> async_graphql::Error {
+        self.extend_with(|err, e| match err {
+            BudgetError::Exceeded { id, scope, reason } => {
+                e.set("code", "BUDGET_EXCEEDED");
+                e.set("budget_id", id.to_string());
+                e.set("scope", scope.to_string());
+                e.set("reason", reason);
+            }
+            BudgetError::NoLimit => {
+                e.set("code", "BAD_REQUEST");
+                e.set("reason", err.to_string());
+            }
+            BudgetError::Unknown(_) => {
+                e.set("code", "INTERNAL_SERVER_ERROR");
+            }
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use dojo_macros::{Model, Type};
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domains::services::BudgetServiceDyn;
+use crate::errors::AppError;
+
+#[derive(SimpleObject, Clone, Debug, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "budgets", sort_keys = ["created_at", "id"])]
+pub struct Budget {
+    pub id: Uuid,
+    pub scope: BudgetScope,
+    /// The user, API key or thread the budget applies to.
+    pub scope_id: Uuid,
+    pub period: BudgetPeriod,
+    pub token_limit: Option<i64>,
+    /// Limit on the summed execution cost, in `currency`.
+    pub cost_limit: Option<f64>,
+    pub currency: String,
+    pub enforcement: BudgetEnforcement,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl Budget {
+    pub async fn usage<'a>(&self, ctx: &Context<'a>) -> Result<BudgetUsage> {
+        let budget_service = ctx
+            .data::<BudgetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let usage = budget_service.usage(self).await?;
+
+        Ok(usage)
+    }
+}
+
+/// What has been spent against a budget in its current period.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct BudgetUsage {
+    pub period_start: NaiveDateTime,
+    pub tokens: i64,
+    pub cost: f64,
+    pub exceeded: bool,
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Deserialize, Type)]
+#[dojo(name = "budget_scope", rename_all = "snake_case")]
+pub enum BudgetScope {
+    #[strum(serialize = "user")]
+    #[serde(rename = "user")]
+    User,
+    #[strum(serialize = "api_key")]
+    #[serde(rename = "api_key")]
+    ApiKey,
+    #[strum(serialize = "thread")]
+    #[serde(rename = "thread")]
+    Thread,
+}
+
+impl BudgetScope {
+    /// The `executions` column that ties an execution to this scope.
+    pub fn column(&self) -> &'static str {
+        match self {
+            BudgetScope::User => "executed_by_id",
+            BudgetScope::ApiKey => "api_key_id",
+            BudgetScope::Thread => "thread_id",
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Deserialize, Type)]
+#[dojo(name = "budget_period", rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    #[strum(serialize = "daily")]
+    #[serde(rename = "daily")]
+    Daily,
+    #[strum(serialize = "monthly")]
+    #[serde(rename = "monthly")]
+    Monthly,
+}
+
+impl BudgetPeriod {
+    /// Start of the period containing `now`, in UTC.
+    pub fn start(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let date = match self {
+            BudgetPeriod::Daily => now.date(),
+            BudgetPeriod::Monthly => {
+                NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap_or(now.date())
+            }
+        };
+
+        date.and_hms_opt(0, 0, 0).unwrap_or(now)
+    }
+}
+
+/// `Hard` budgets block executions once exceeded, `Soft` budgets only log a warning.
+#[derive(
+    Enum, Copy, Clone, Debug, Default, Eq, PartialEq, Display, EnumString, Deserialize, Type,
+)]
+#[dojo(name = "budget_enforcement", rename_all = "lowercase")]
+pub enum BudgetEnforcement {
+    #[default]
+    #[strum(serialize = "hard")]
+    #[serde(rename = "hard")]
+    Hard,
+    #[strum(serialize = "soft")]
+    #[serde(rename = "soft")]
+    Soft,
+}
@@ -0,0 +1,57 @@
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::budget::budget_model::Budget;
+use crate::domains::budget::dto::{BudgetCreateInput, BudgetUpdateInput};
+use crate::domains::models::UserRole;
+use crate::domains::services::BudgetServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct BudgetMutation;
+
+#[Object]
+impl BudgetMutation {
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn create_budget<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: BudgetCreateInput,
+    ) -> Result<Budget> {
+        let budget_service = ctx
+            .data::<BudgetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let budget = budget_service.create(input).await?;
+
+        Ok(budget)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn update_budget<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        input: BudgetUpdateInput,
+    ) -> Result<Budget> {
+        let budget_service = ctx
+            .data::<BudgetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let budget = budget_service.update_by_id(&id, input).await?;
+
+        Ok(budget)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn delete_budget<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Budget> {
+        let budget_service = ctx
+            .data::<BudgetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let budget = budget_service.delete_by_id(&id).await?;
+
+        Ok(budget)
+    }
+}
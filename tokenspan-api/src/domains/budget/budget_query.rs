@@ -0,0 +1,43 @@
+use async_graphql::connection::Connection;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::budget::budget_model::Budget;
+use crate::domains::budget::dto::BudgetArgs;
+use crate::domains::models::UserRole;
+use crate::domains::services::BudgetServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+
+#[derive(Default)]
+pub struct BudgetQuery;
+
+#[Object]
+impl BudgetQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn budgets<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: BudgetArgs,
+    ) -> Result<Connection<Cursor, Budget, AdditionalFields>> {
+        let budget_service = ctx
+            .data::<BudgetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_budget = budget_service.paginate(args).await?;
+
+        Ok(paginated_budget.into())
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn budget<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Option<Budget>> {
+        let budget_service = ctx
+            .data::<BudgetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let budget = budget_service.find_by_id(&id).await?;
+
+        Ok(budget)
+    }
+}
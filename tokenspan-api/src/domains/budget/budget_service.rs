@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::{NaiveDateTime, Utc};
+use dojo_macros::UpdateModel;
+use dojo_orm::pagination::Pagination;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use tracing::warn;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::budget::budget_error::BudgetError;
+use crate::domains::dto::{BudgetArgs, BudgetCreateInput, BudgetUpdateInput};
+use crate::domains::models::{Budget, BudgetEnforcement, BudgetPeriod, BudgetScope, BudgetUsage};
+use crate::state::AppState;
+
+#[async_trait::async_trait]
+pub trait BudgetServiceExt {
+    async fn paginate(&self, args: BudgetArgs) -> Result<Pagination<Budget>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Budget>>;
+    async fn find_by_scope_ids(&self, scope_ids: &[Uuid]) -> Result<Vec<Budget>>;
+    async fn create(&self, input: BudgetCreateInput) -> Result<Budget>;
+    async fn update_by_id(&self, id: &Uuid, input: BudgetUpdateInput) -> Result<Budget>;
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Budget>;
+    async fn usage(&self, budget: &Budget) -> Result<BudgetUsage>;
+    async fn check(&self, scopes: &[(BudgetScope, Uuid)]) -> Result<()>;
+}
+
+pub type BudgetServiceDyn = Arc<dyn BudgetServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for BudgetServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.budget_service.clone()
+    }
+}
+
+/// The limits are written as they are, so the merged values of an update are
+/// given for both.
+#[derive(UpdateModel)]
+struct BudgetChanges {
+    period: Option<BudgetPeriod>,
+    #[dojo(nullable)]
+    token_limit: Option<i64>,
+    #[dojo(nullable)]
+    cost_limit: Option<f64>,
+    currency: Option<String>,
+    enforcement: Option<BudgetEnforcement>,
+    updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(TypedBuilder)]
+pub struct BudgetService {
+    db: Database,
+}
+
+#[async_trait::async_trait]
+impl BudgetServiceExt for BudgetService {
+    async fn paginate(&self, args: BudgetArgs) -> Result<Pagination<Budget>> {
+        let mut predicates = vec![];
+        if let Some(r#where) = &args.r#where {
+            if let Some(scope_id_args) = &r#where.scope_id {
+                if let Some(id) = &scope_id_args.equals {
+                    predicates.push(equals("scope_id", id));
+                }
+            }
+        }
+
+        self.db
+            .bind::<Budget>()
+            .where_by(and(&predicates))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Budget>> {
+        self.db
+            .bind::<Budget>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    async fn find_by_scope_ids(&self, scope_ids: &[Uuid]) -> Result<Vec<Budget>> {
+        self.db
+            .bind::<Budget>()
+            .where_by(in_list("scope_id", &scope_ids))
+            .all()
+            .await
+    }
+
+    async fn create(&self, input: BudgetCreateInput) -> Result<Budget> {
+        if input.token_limit.is_none() && input.cost_limit.is_none() {
+            return Err(BudgetError::NoLimit.into());
+        }
+
+        let input = Budget {
+            id: Uuid::new_v4(),
+            scope: input.scope,
+            scope_id: input.scope_id,
+            period: input.period,
+            token_limit: input.token_limit,
+            cost_limit: input.cost_limit,
+            currency: input.currency,
+            enforcement: input.enforcement,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await
+    }
+
+    async fn update_by_id(&self, id: &Uuid, input: BudgetUpdateInput) -> Result<Budget> {
+        let budget = self
+            .find_by_id(id)
+            .await?
+            .ok_or(anyhow::anyhow!("budget not found"))?;
+        let token_limit = match input.clear_token_limit {
+            true => None,
+            false => input.token_limit.or(budget.token_limit),
+        };
+        let cost_limit = match input.clear_cost_limit {
+            true => None,
+            false => input.cost_limit.or(budget.cost_limit),
+        };
+        if token_limit.is_none() && cost_limit.is_none() {
+            return Err(BudgetError::NoLimit.into());
+        }
+
+        let changes = BudgetChanges {
+            period: input.period,
+            token_limit,
+            cost_limit,
+            currency: input.currency,
+            enforcement: input.enforcement,
+            updated_at: Some(Utc::now().naive_utc()),
+        };
+        self.db
+            .update(&changes)
+            .where_by(equals("id", id))
+            .exec()
+            .await
+    }
+
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Budget> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    /// Sums the stored usage and cost of the executions in the budget's current period.
+    async fn usage(&self, budget: &Budget) -> Result<BudgetUsage> {
+        let period_start = budget.period.start(Utc::now().naive_utc());
+        let query = format!(
+            "SELECT COALESCE(SUM((usage ->> 'total_tokens')::BIGINT), 0)::BIGINT, \
+             COALESCE(SUM((cost ->> 'total')::DOUBLE PRECISION) \
+             FILTER (WHERE cost ->> 'currency' = $3), 0)::DOUBLE PRECISION \
+             FROM executions WHERE {} = $1 AND created_at >= $2",
+            budget.scope.column()
+        );
+
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_one(&query, &[&budget.scope_id, &period_start, &budget.currency])
+            .await?;
+        let tokens: i64 = row.get(0);
+        let cost: f64 = row.get(1);
+
+        let exceeded = budget.token_limit.is_some_and(|limit| tokens >= limit)
+            || budget.cost_limit.is_some_and(|limit| cost >= limit);
+
+        Ok(BudgetUsage {
+            period_start,
+            tokens,
+            cost,
+            exceeded,
+        })
+    }
+
+    /// Fails with `BudgetError::Exceeded` if any hard budget on the given scopes is
+    /// used up. Exceeded soft budgets are only logged.
+    async fn check(&self, scopes: &[(BudgetScope, Uuid)]) -> Result<()> {
+        let scope_ids = scopes.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+        let budgets = self.find_by_scope_ids(&scope_ids).await?;
+
+        for budget in budgets {
+            if !scopes.contains(&(budget.scope, budget.scope_id)) {
+                continue;
+            }
+
+            let usage = self.usage(&budget).await?;
+            if !usage.exceeded {
+                continue;
+            }
+
+            let reason = format!(
+                "used {} tokens and {:.4} {} since {}",
+                usage.tokens, usage.cost, budget.currency, usage.period_start
+            );
+            match budget.enforcement {
+                BudgetEnforcement::Hard => {
+                    return Err(BudgetError::Exceeded {
+                        id: budget.id,
+                        scope: budget.scope,
+                        reason,
+                    }
+                    .into());
+                }
+                BudgetEnforcement::Soft => {
+                    warn!("{} budget {} exceeded: {}", budget.scope, budget.id, reason);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<BudgetService> for BudgetServiceDyn {
+    fn from(value: BudgetService) -> Self {
+        Arc::new(value) as Self
+    }
+}
@@ -0,0 +1,23 @@
+use async_graphql::InputObject;
+use uuid::Uuid;
+
+use dojo_orm::pagination::Cursor;
+
+#[derive(InputObject, Default)]
+pub struct BudgetWhereScopeIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct BudgetWhereArgs {
+    pub scope_id: Option<BudgetWhereScopeIdArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct BudgetArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub r#where: Option<BudgetWhereArgs>,
+}
@@ -0,0 +1,32 @@
+use async_graphql::InputObject;
+use uuid::Uuid;
+
+use crate::domains::models::{BudgetEnforcement, BudgetPeriod, BudgetScope};
+
+#[derive(InputObject)]
+pub struct BudgetCreateInput {
+    pub scope: BudgetScope,
+    pub scope_id: Uuid,
+    pub period: BudgetPeriod,
+    pub token_limit: Option<i64>,
+    pub cost_limit: Option<f64>,
+    #[graphql(default_with = "String::from(\"USD\")")]
+    pub currency: String,
+    #[graphql(default)]
+    pub enforcement: BudgetEnforcement,
+}
+
+#[derive(InputObject)]
+pub struct BudgetUpdateInput {
+    pub period: Option<BudgetPeriod>,
+    pub token_limit: Option<i64>,
+    pub cost_limit: Option<f64>,
+    /// Removes the token limit. A budget keeps at least one limit.
+    #[graphql(default)]
+    pub clear_token_limit: bool,
+    /// Removes the cost limit. A budget keeps at least one limit.
+    #[graphql(default)]
+    pub clear_cost_limit: bool,
+    pub currency: Option<String>,
+    pub enforcement: Option<BudgetEnforcement>,
+}
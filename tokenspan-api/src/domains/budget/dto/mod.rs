@@ -0,0 +1,5 @@
+pub use budget_args::*;
+pub use budget_input::*;
+
+mod budget_args;
+mod budget_input;
@@ -0,0 +1,9 @@
+pub use budget_mutation::BudgetMutation;
+pub use budget_query::BudgetQuery;
+
+pub mod budget_error;
+pub mod budget_model;
+mod budget_mutation;
+mod budget_query;
+pub mod budget_service;
+pub mod dto;
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ComparisonError {
+    #[error("comparison not found")]
+    NotFound,
+
+    #[error("run not found")]
+    RunNotFound,
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,134 @@
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use chrono::NaiveDateTime;
+use dojo_macros::Model;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domains::models::{Run, RunItemStatus};
+use crate::domains::services::{ComparisonServiceDyn, RunServiceDyn};
+use crate::errors::AppError;
+
+/// Two runs over the same dataset, to be compared row by row.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "comparisons", sort_keys = ["created_at", "id"])]
+pub struct Comparison {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub dataset_id: Option<Uuid>,
+    pub baseline_run_id: Uuid,
+    pub candidate_run_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl Comparison {
+    pub async fn baseline_run<'a>(&self, ctx: &Context<'a>) -> Result<Option<Run>> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let run = run_service.find_by_id(&self.baseline_run_id).await?;
+
+        Ok(run)
+    }
+
+    pub async fn candidate_run<'a>(&self, ctx: &Context<'a>) -> Result<Option<Run>> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let run = run_service.find_by_id(&self.candidate_run_id).await?;
+
+        Ok(run)
+    }
+
+    /// Built from the runs' current state, so it fills in while they progress.
+    pub async fn report<'a>(&self, ctx: &Context<'a>) -> Result<ComparisonReport> {
+        let comparison_service = ctx
+            .data::<ComparisonServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let report = comparison_service
+            .report(&self.baseline_run_id, &self.candidate_run_id)
+            .await?;
+
+        Ok(report)
+    }
+}
+
+/// Which side scored higher on a row, from the candidate's point of view.
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ComparisonOutcome {
+    Win,
+    Loss,
+    Tie,
+}
+
+/// One side of a compared row.
+#[derive(SimpleObject, Clone, Debug, Default)]
+pub struct ComparisonSide {
+    pub status: Option<RunItemStatus>,
+    pub execution_id: Option<Uuid>,
+    pub output: Option<String>,
+    /// Evaluation score; unset until the row is evaluated.
+    pub score: Option<f64>,
+    pub passed: Option<bool>,
+    pub cost: Option<f64>,
+    /// Seconds spent calling the provider.
+    pub latency: Option<f64>,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ComparisonRow {
+    pub index: i32,
+    pub variables: serde_json::Value,
+    pub expected_output: Option<String>,
+    pub baseline: ComparisonSide,
+    pub candidate: ComparisonSide,
+    /// Candidate score minus baseline score.
+    pub score_delta: Option<f64>,
+    /// Unset unless both sides are scored.
+    pub outcome: Option<ComparisonOutcome>,
+}
+
+/// Aggregates over one side's rows.
+#[derive(SimpleObject, Clone, Debug, Default)]
+pub struct ComparisonTotals {
+    pub succeeded: i32,
+    pub failed: i32,
+    pub mean_score: Option<f64>,
+    pub pass_rate: Option<f64>,
+    /// Cost of the executions themselves, without judges.
+    pub cost: f64,
+    pub mean_latency: Option<f64>,
+}
+
+/// Mean score of the evaluators with the same name on both sides.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct EvaluatorComparison {
+    pub name: String,
+    pub baseline_mean_score: Option<f64>,
+    pub candidate_mean_score: Option<f64>,
+    pub delta: Option<f64>,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ComparisonReport {
+    pub rows: Vec<ComparisonRow>,
+    pub baseline: ComparisonTotals,
+    pub candidate: ComparisonTotals,
+    pub wins: i32,
+    pub losses: i32,
+    pub ties: i32,
+    /// Mean of the row score deltas.
+    pub mean_score_delta: Option<f64>,
+    pub cost_delta: f64,
+    pub mean_latency_delta: Option<f64>,
+    pub evaluators: Vec<EvaluatorComparison>,
+    /// Two-sided sign test on wins against losses; unset without any.
+    pub p_value: Option<f64>,
+    /// Whether `p_value` is below 0.05.
+    pub significant: bool,
+}
@@ -0,0 +1,49 @@
+use async_graphql::{Context, ErrorExtensions, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::comparison::comparison_model::Comparison;
+use crate::domains::comparison::dto::ComparisonCreateInput;
+use crate::domains::models::{ParsedToken, UserRole};
+use crate::domains::services::ComparisonServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct ComparisonMutation;
+
+#[Object]
+impl ComparisonMutation {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_comparison<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: ComparisonCreateInput,
+    ) -> Result<Comparison> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let comparison_service = ctx
+            .data::<ComparisonServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let comparison = comparison_service
+            .create(input, parsed_token.user_id)
+            .await?;
+
+        Ok(comparison)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_comparison<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Comparison> {
+        let comparison_service = ctx
+            .data::<ComparisonServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let comparison = comparison_service.delete_by_id(&id).await?;
+
+        Ok(comparison)
+    }
+}
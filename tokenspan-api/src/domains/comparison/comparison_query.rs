@@ -0,0 +1,62 @@
+use async_graphql::connection::Connection;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::comparison::comparison_model::{Comparison, ComparisonReport};
+use crate::domains::comparison::dto::ComparisonArgs;
+use crate::domains::models::UserRole;
+use crate::domains::services::ComparisonServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+
+#[derive(Default)]
+pub struct ComparisonQuery;
+
+#[Object]
+impl ComparisonQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn comparisons<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: ComparisonArgs,
+    ) -> Result<Connection<Cursor, Comparison, AdditionalFields>> {
+        let comparison_service = ctx
+            .data::<ComparisonServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_comparison = comparison_service.paginate(args).await?;
+
+        Ok(paginated_comparison.into())
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn comparison<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Option<Comparison>> {
+        let comparison_service = ctx
+            .data::<ComparisonServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let comparison = comparison_service.find_by_id(&id).await?;
+
+        Ok(comparison)
+    }
+
+    /// Compares any two existing runs, matching their rows by index.
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn compare_runs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        baseline_run_id: Uuid,
+        candidate_run_id: Uuid,
+    ) -> Result<ComparisonReport> {
+        let comparison_service = ctx
+            .data::<ComparisonServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let report = comparison_service
+            .report(&baseline_run_id, &candidate_run_id)
+            .await?;
+
+        Ok(report)
+    }
+}
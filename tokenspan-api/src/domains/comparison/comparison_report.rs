@@ -0,0 +1,217 @@
+use std::collections::{BTreeMap, HashMap};
+
+use uuid::Uuid;
+
+use crate::domains::models::{
+    ComparisonOutcome, ComparisonReport, ComparisonRow, ComparisonSide, ComparisonTotals,
+    Evaluation, EvaluatorComparison, Execution, RunItem, RunItemStatus,
+};
+use crate::domains::services::execution_output;
+
+/// Score differences smaller than this count as a tie.
+const TIE_EPSILON: f64 = 1e-6;
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Lines up the two runs' items by index. `executions` and `evaluations` are keyed
+/// by execution id and only need to hold what the items point at.
+pub fn build_report(
+    baseline_items: &[RunItem],
+    candidate_items: &[RunItem],
+    executions: &HashMap<Uuid, Execution>,
+    evaluations: &HashMap<Uuid, Evaluation>,
+) -> ComparisonReport {
+    let mut pairs: BTreeMap<i32, (Option<&RunItem>, Option<&RunItem>)> = BTreeMap::new();
+    for item in baseline_items {
+        pairs.entry(item.index).or_default().0 = Some(item);
+    }
+    for item in candidate_items {
+        pairs.entry(item.index).or_default().1 = Some(item);
+    }
+
+    let side = |item: Option<&RunItem>| {
+        let Some(item) = item else {
+            return ComparisonSide::default();
+        };
+        let execution = item
+            .execution_id
+            .and_then(|execution_id| executions.get(&execution_id));
+        let evaluation = item
+            .execution_id
+            .and_then(|execution_id| evaluations.get(&execution_id));
+
+        ComparisonSide {
+            status: Some(item.status),
+            execution_id: item.execution_id,
+            output: execution.map(|execution| execution_output(execution).to_string()),
+            score: evaluation.map(|evaluation| evaluation.score),
+            passed: evaluation.map(|evaluation| evaluation.passed),
+            cost: execution.and_then(|execution| execution.cost.as_ref().map(|cost| cost.total)),
+            latency: execution.map(|execution| execution.elapsed.api_call),
+        }
+    };
+
+    let rows = pairs
+        .into_iter()
+        .map(|(index, (baseline, candidate))| {
+            let source = baseline.or(candidate);
+            let baseline = side(baseline);
+            let candidate = side(candidate);
+            let score_delta = baseline
+                .score
+                .zip(candidate.score)
+                .map(|(baseline, candidate)| candidate - baseline);
+            let outcome = score_delta.map(|delta| {
+                if delta.abs() < TIE_EPSILON {
+                    ComparisonOutcome::Tie
+                } else if delta > 0.0 {
+                    ComparisonOutcome::Win
+                } else {
+                    ComparisonOutcome::Loss
+                }
+            });
+
+            ComparisonRow {
+                index,
+                variables: source
+                    .map(|item| item.variables.clone())
+                    .unwrap_or_default(),
+                expected_output: source.and_then(|item| item.expected_output.clone()),
+                baseline,
+                candidate,
+                score_delta,
+                outcome,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let count = |outcome: ComparisonOutcome| {
+        rows.iter()
+            .filter(|row| row.outcome == Some(outcome))
+            .count() as i32
+    };
+    let wins = count(ComparisonOutcome::Win);
+    let losses = count(ComparisonOutcome::Loss);
+    let ties = count(ComparisonOutcome::Tie);
+    let p_value = sign_test(wins as u64, losses as u64);
+
+    let baseline = totals(rows.iter().map(|row| &row.baseline));
+    let candidate = totals(rows.iter().map(|row| &row.candidate));
+    let mean_latency_delta = baseline
+        .mean_latency
+        .zip(candidate.mean_latency)
+        .map(|(baseline, candidate)| candidate - baseline);
+
+    ComparisonReport {
+        wins,
+        losses,
+        ties,
+        mean_score_delta: mean(rows.iter().filter_map(|row| row.score_delta)),
+        cost_delta: candidate.cost - baseline.cost,
+        mean_latency_delta,
+        evaluators: compare_evaluators(baseline_items, candidate_items, evaluations),
+        p_value,
+        significant: p_value.is_some_and(|p_value| p_value < SIGNIFICANCE_LEVEL),
+        baseline,
+        candidate,
+        rows,
+    }
+}
+
+/// Two-sided exact sign test: the chance of a split at least this uneven if
+/// either side were equally likely to win each row.
+pub fn sign_test(wins: u64, losses: u64) -> Option<f64> {
+    let n = wins + losses;
+    if n == 0 {
+        return None;
+    }
+
+    // Binomial(n, 0.5) probabilities are summed in log space, since 0.5^n
+    // underflows for large datasets.
+    let k = wins.min(losses);
+    let mut ln_probability = n as f64 * 0.5f64.ln();
+    let mut tail = ln_probability.exp();
+    for i in 1..=k {
+        ln_probability += ((n - i + 1) as f64).ln() - (i as f64).ln();
+        tail += ln_probability.exp();
+    }
+
+    Some((2.0 * tail).min(1.0))
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+    (count > 0).then(|| sum / count as f64)
+}
+
+fn totals<'a>(sides: impl Iterator<Item = &'a ComparisonSide> + Clone) -> ComparisonTotals {
+    let count = |status: RunItemStatus| {
+        sides
+            .clone()
+            .filter(|side| side.status == Some(status))
+            .count() as i32
+    };
+
+    ComparisonTotals {
+        succeeded: count(RunItemStatus::Succeeded),
+        failed: count(RunItemStatus::Failed),
+        mean_score: mean(sides.clone().filter_map(|side| side.score)),
+        pass_rate: mean(sides.clone().filter_map(|side| side.passed).map(|passed| {
+            if passed {
+                1.0
+            } else {
+                0.0
+            }
+        })),
+        cost: sides.clone().filter_map(|side| side.cost).sum(),
+        mean_latency: mean(sides.filter_map(|side| side.latency)),
+    }
+}
+
+fn compare_evaluators(
+    baseline_items: &[RunItem],
+    candidate_items: &[RunItem],
+    evaluations: &HashMap<Uuid, Evaluation>,
+) -> Vec<EvaluatorComparison> {
+    let scores = |items: &[RunItem]| {
+        let mut scores: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let results = items
+            .iter()
+            .filter_map(|item| item.execution_id)
+            .filter_map(|execution_id| evaluations.get(&execution_id))
+            .flat_map(|evaluation| &evaluation.results);
+        for result in results {
+            scores
+                .entry(result.name.clone())
+                .or_default()
+                .push(result.score);
+        }
+        scores
+    };
+    let baseline = scores(baseline_items);
+    let candidate = scores(candidate_items);
+
+    let mut names = baseline.keys().chain(candidate.keys()).collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|name| {
+            let baseline_mean_score = baseline
+                .get(name)
+                .and_then(|scores| mean(scores.iter().copied()));
+            let candidate_mean_score = candidate
+                .get(name)
+                .and_then(|scores| mean(scores.iter().copied()));
+
+            EvaluatorComparison {
+                name: name.clone(),
+                delta: baseline_mean_score
+                    .zip(candidate_mean_score)
+                    .map(|(baseline, candidate)| candidate - baseline),
+                baseline_mean_score,
+                candidate_mean_score,
+            }
+        })
+        .collect()
+}
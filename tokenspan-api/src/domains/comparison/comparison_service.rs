@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::Utc;
+use dojo_orm::pagination::Pagination;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::comparison::comparison_error::ComparisonError;
+use crate::domains::comparison::comparison_report::build_report;
+use crate::domains::dto::{
+    ComparisonArgs, ComparisonCreateInput, ComparisonTargetInput, RunCreateInput,
+};
+use crate::domains::models::{Comparison, ComparisonReport, Evaluation};
+use crate::domains::services::{EvaluationServiceDyn, ExecutionServiceDyn, RunServiceDyn};
+use crate::state::AppState;
+
+/// Ids looked up per query when loading a report's executions and evaluations.
+const LOOKUP_CHUNK_SIZE: usize = 500;
+
+#[async_trait::async_trait]
+pub trait ComparisonServiceExt {
+    async fn paginate(&self, args: ComparisonArgs) -> Result<Pagination<Comparison>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Comparison>>;
+    async fn create(&self, input: ComparisonCreateInput, owner_id: Uuid) -> Result<Comparison>;
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Comparison>;
+    async fn report(
+        &self,
+        baseline_run_id: &Uuid,
+        candidate_run_id: &Uuid,
+    ) -> Result<ComparisonReport>;
+}
+
+pub type ComparisonServiceDyn = Arc<dyn ComparisonServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for ComparisonServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.comparison_service.clone()
+    }
+}
+
+#[derive(TypedBuilder)]
+pub struct ComparisonService {
+    db: Database,
+    run_service: RunServiceDyn,
+    execution_service: ExecutionServiceDyn,
+    evaluation_service: EvaluationServiceDyn,
+}
+
+impl ComparisonService {
+    fn run_input(
+        dataset_id: Uuid,
+        target: ComparisonTargetInput,
+        concurrency: i32,
+    ) -> RunCreateInput {
+        RunCreateInput {
+            thread_version_id: target.thread_version_id,
+            parameter_id: target.parameter_id,
+            api_key_id: target.api_key_id,
+            dataset_id: Some(dataset_id),
+            rows: None,
+            concurrency,
+            tools: vec![],
+            max_tool_iterations: None,
+            retry: None,
+            fallbacks: vec![],
+            bypass_cache: false,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ComparisonServiceExt for ComparisonService {
+    async fn paginate(&self, args: ComparisonArgs) -> Result<Pagination<Comparison>> {
+        let mut predicates = vec![];
+        if let Some(r#where) = &args.r#where {
+            if let Some(owner_id_args) = &r#where.owner_id {
+                if let Some(id) = &owner_id_args.equals {
+                    predicates.push(equals("owner_id", id));
+                }
+            }
+            if let Some(dataset_id_args) = &r#where.dataset_id {
+                if let Some(id) = &dataset_id_args.equals {
+                    predicates.push(equals("dataset_id", id));
+                }
+            }
+        }
+
+        self.db
+            .bind::<Comparison>()
+            .where_by(and(&predicates))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Comparison>> {
+        self.db
+            .bind::<Comparison>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    /// Starts one run per side over the dataset.
+    async fn create(&self, input: ComparisonCreateInput, owner_id: Uuid) -> Result<Comparison> {
+        let baseline_run = self
+            .run_service
+            .create(
+                Self::run_input(input.dataset_id, input.baseline, input.concurrency),
+                owner_id,
+            )
+            .await?;
+        let candidate_run = match self
+            .run_service
+            .create(
+                Self::run_input(input.dataset_id, input.candidate, input.concurrency),
+                owner_id,
+            )
+            .await
+        {
+            Ok(run) => run,
+            Err(e) => {
+                self.run_service.cancel_by_id(&baseline_run.id).await?;
+                return Err(e);
+            }
+        };
+
+        let input = Comparison {
+            id: Uuid::new_v4(),
+            owner_id,
+            dataset_id: Some(input.dataset_id),
+            baseline_run_id: baseline_run.id,
+            candidate_run_id: candidate_run.id,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await
+    }
+
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Comparison> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    async fn report(
+        &self,
+        baseline_run_id: &Uuid,
+        candidate_run_id: &Uuid,
+    ) -> Result<ComparisonReport> {
+        for run_id in [baseline_run_id, candidate_run_id] {
+            self.run_service
+                .find_by_id(run_id)
+                .await?
+                .ok_or(ComparisonError::RunNotFound)?;
+        }
+
+        let baseline_items = self.run_service.find_items(baseline_run_id).await?;
+        let candidate_items = self.run_service.find_items(candidate_run_id).await?;
+        let execution_ids = baseline_items
+            .iter()
+            .chain(&candidate_items)
+            .filter_map(|item| item.execution_id)
+            .collect::<Vec<_>>();
+
+        let mut executions = HashMap::new();
+        let mut evaluations: HashMap<Uuid, Evaluation> = HashMap::new();
+        for ids in execution_ids.chunks(LOOKUP_CHUNK_SIZE) {
+            for execution in self.execution_service.find_by_ids(ids).await? {
+                executions.insert(execution.id, execution);
+            }
+            // Only the latest evaluation of each execution counts.
+            for evaluation in self.evaluation_service.find_by_execution_ids(ids).await? {
+                let is_latest = evaluations
+                    .get(&evaluation.execution_id)
+                    .is_none_or(|latest| latest.created_at < evaluation.created_at);
+                if is_latest {
+                    evaluations.insert(evaluation.execution_id, evaluation);
+                }
+            }
+        }
+
+        Ok(build_report(
+            &baseline_items,
+            &candidate_items,
+            &executions,
+            &evaluations,
+        ))
+    }
+}
+
+impl From<ComparisonService> for ComparisonServiceDyn {
+    fn from(value: ComparisonService) -> Self {
+        Arc::new(value) as Self
+    }
+}
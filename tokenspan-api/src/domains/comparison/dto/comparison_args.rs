@@ -0,0 +1,28 @@
+use async_graphql::InputObject;
+use dojo_orm::pagination::Cursor;
+use uuid::Uuid;
+
+#[derive(InputObject, Default)]
+pub struct ComparisonWhereOwnerIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct ComparisonWhereDatasetIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct ComparisonWhereArgs {
+    pub owner_id: Option<ComparisonWhereOwnerIdArgs>,
+    pub dataset_id: Option<ComparisonWhereDatasetIdArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct ComparisonArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub r#where: Option<ComparisonWhereArgs>,
+}
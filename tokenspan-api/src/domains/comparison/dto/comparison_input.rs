@@ -0,0 +1,22 @@
+use async_graphql::InputObject;
+use uuid::Uuid;
+
+/// The thread version and parameter one side of a comparison runs.
+#[derive(InputObject)]
+pub struct ComparisonTargetInput {
+    pub thread_version_id: Uuid,
+    pub parameter_id: Uuid,
+    pub api_key_id: Uuid,
+}
+
+#[derive(InputObject)]
+pub struct ComparisonCreateInput {
+    pub dataset_id: Uuid,
+    /// Usually the published version.
+    pub baseline: ComparisonTargetInput,
+    /// The draft, or another parameter, being considered instead.
+    pub candidate: ComparisonTargetInput,
+    /// Concurrency of each of the two runs.
+    #[graphql(default = 4)]
+    pub concurrency: i32,
+}
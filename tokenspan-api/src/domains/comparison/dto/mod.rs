@@ -0,0 +1,5 @@
+mod comparison_args;
+mod comparison_input;
+
+pub use comparison_args::*;
+pub use comparison_input::*;
@@ -0,0 +1,10 @@
+pub use comparison_mutation::*;
+pub use comparison_query::*;
+
+pub mod comparison_error;
+pub mod comparison_model;
+mod comparison_mutation;
+mod comparison_query;
+pub mod comparison_report;
+pub mod comparison_service;
+pub mod dto;
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DatasetError {
+    #[error("dataset not found")]
+    NotFound,
+
+    #[error("line {line}: {reason}")]
+    InvalidRow { line: usize, reason: String },
+
+    #[error("a dataset holds at most {0} rows")]
+    TooManyRows(usize),
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
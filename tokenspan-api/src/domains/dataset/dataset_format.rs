@@ -0,0 +1,164 @@
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::domains::dataset::dataset_error::DatasetError;
+use crate::domains::models::{DatasetFormat, DatasetRow};
+
+const EXPECTED_OUTPUT: &str = "expected_output";
+
+/// The content of a row read from an import, before it belongs to a dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowData {
+    pub variables: Map<String, Value>,
+    pub expected_output: Option<String>,
+}
+
+pub fn parse_rows(format: DatasetFormat, content: &str) -> Result<Vec<RowData>, DatasetError> {
+    match format {
+        DatasetFormat::Jsonl => parse_jsonl(content),
+        DatasetFormat::Csv => parse_csv(content),
+    }
+}
+
+pub fn write_rows(format: DatasetFormat, rows: &[DatasetRow]) -> Result<String> {
+    match format {
+        DatasetFormat::Jsonl => Ok(rows
+            .iter()
+            .map(|row| {
+                json!({
+                    "variables": row.variables,
+                    EXPECTED_OUTPUT: row.expected_output,
+                })
+                .to_string()
+            })
+            .map(|line| line + "\n")
+            .collect()),
+        DatasetFormat::Csv => write_csv(rows),
+    }
+}
+
+/// Variables are substituted into messages as strings; `null` is empty and
+/// other JSON values keep their JSON text.
+pub fn to_variable(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// The variables of a JSON object as the template engine takes them, or `None`
+/// when it is not an object.
+pub fn to_variables(variables: &Value) -> Option<HashMap<String, String>> {
+    variables.as_object().map(|variables| {
+        variables
+            .iter()
+            .map(|(name, value)| (name.clone(), to_variable(value)))
+            .collect()
+    })
+}
+
+fn parse_jsonl(content: &str) -> Result<Vec<RowData>, DatasetError> {
+    let mut rows = vec![];
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |reason: String| DatasetError::InvalidRow {
+            line: index + 1,
+            reason,
+        };
+
+        let mut object = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => return Err(invalid("expected a JSON object".to_string())),
+            Err(e) => return Err(invalid(e.to_string())),
+        };
+        let expected_output = match object.remove(EXPECTED_OUTPUT) {
+            None | Some(Value::Null) => None,
+            Some(Value::String(output)) => Some(output),
+            Some(output) => Some(output.to_string()),
+        };
+        let variables = match object.remove("variables") {
+            Some(Value::Object(variables)) if object.is_empty() => variables,
+            Some(_) if object.is_empty() => {
+                return Err(invalid("variables must be a JSON object".to_string()))
+            }
+            Some(variables) => {
+                object.insert("variables".to_string(), variables);
+                object
+            }
+            None => object,
+        };
+
+        rows.push(RowData {
+            variables: variables
+                .into_iter()
+                .map(|(name, value)| (name, Value::String(to_variable(&value))))
+                .collect(),
+            expected_output,
+        });
+    }
+
+    Ok(rows)
+}
+
+fn parse_csv(content: &str) -> Result<Vec<RowData>, DatasetError> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| DatasetError::InvalidRow {
+            line: 1,
+            reason: e.to_string(),
+        })?
+        .clone();
+
+    let mut rows = vec![];
+    for record in reader.records() {
+        let record = record.map_err(|e| DatasetError::InvalidRow {
+            line: e.position().map(|p| p.line() as usize).unwrap_or_default(),
+            reason: e.to_string(),
+        })?;
+
+        let mut variables = Map::new();
+        let mut expected_output = None;
+        for (name, value) in headers.iter().zip(record.iter()) {
+            if name == EXPECTED_OUTPUT {
+                expected_output = Some(value.to_string()).filter(|value| !value.is_empty());
+            } else {
+                variables.insert(name.to_string(), Value::String(value.to_string()));
+            }
+        }
+
+        rows.push(RowData {
+            variables,
+            expected_output,
+        });
+    }
+
+    Ok(rows)
+}
+
+fn write_csv(rows: &[DatasetRow]) -> Result<String> {
+    let names = rows
+        .iter()
+        .filter_map(|row| row.variables.as_object())
+        .flat_map(|variables| variables.keys().cloned())
+        .collect::<BTreeSet<_>>();
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(names.iter().map(String::as_str).chain([EXPECTED_OUTPUT]))?;
+    for row in rows {
+        let variables = row.variable_map();
+        let values = names
+            .iter()
+            .map(|name| variables.get(name).cloned().unwrap_or_default())
+            .chain([row.expected_output.clone().unwrap_or_default()])
+            .collect::<Vec<_>>();
+        writer.write_record(&values)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use async_graphql::connection::Connection;
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use chrono::NaiveDateTime;
+use dojo_macros::Model;
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domains::dto::DatasetRowArgs;
+use crate::domains::models::Thread;
+use crate::domains::services::{to_variables, DatasetServiceDyn, ThreadServiceDyn};
+use crate::errors::AppError;
+
+/// A named collection of test cases, optionally tied to the thread it exercises.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "datasets", sort_keys = ["created_at", "id"])]
+pub struct Dataset {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub thread_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl Dataset {
+    pub async fn thread<'a>(&self, ctx: &Context<'a>) -> Result<Option<Thread>> {
+        let Some(thread_id) = &self.thread_id else {
+            return Ok(None);
+        };
+
+        let thread_service = ctx
+            .data::<ThreadServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let thread = thread_service.find_by_id(thread_id).await?;
+
+        Ok(thread)
+    }
+
+    pub async fn rows<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: DatasetRowArgs,
+    ) -> Result<Connection<Cursor, DatasetRow, AdditionalFields>> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_row = dataset_service.paginate_rows(&self.id, args).await?;
+
+        Ok(paginated_row.into())
+    }
+}
+
+/// One test case: the variables to render the thread with and, optionally, the
+/// output it is expected to produce.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[dojo(name = "dataset_rows", sort_keys = ["index", "id"])]
+pub struct DatasetRow {
+    pub id: Uuid,
+    pub dataset_id: Uuid,
+    pub index: i32,
+    /// A JSON object of string values, keyed by variable name.
+    pub variables: serde_json::Value,
+    pub expected_output: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl DatasetRow {
+    pub fn variable_map(&self) -> HashMap<String, String> {
+        to_variables(&self.variables).unwrap_or_default()
+    }
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DatasetFormat {
+    /// One JSON object per line, either `{"variables": {..}, "expected_output": ..}`
+    /// or a flat object whose `expected_output` key is split off.
+    Jsonl,
+    /// A header row of variable names; an `expected_output` column is split off.
+    Csv,
+}
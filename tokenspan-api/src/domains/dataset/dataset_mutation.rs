@@ -0,0 +1,129 @@
+use async_graphql::{Context, ErrorExtensions, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::dataset::dataset_model::{Dataset, DatasetFormat, DatasetRow};
+use crate::domains::dataset::dto::{
+    DatasetCreateInput, DatasetRowCreateInput, DatasetRowUpdateInput, DatasetUpdateInput,
+};
+use crate::domains::models::{ParsedToken, UserRole};
+use crate::domains::services::DatasetServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct DatasetMutation;
+
+#[Object]
+impl DatasetMutation {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_dataset<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: DatasetCreateInput,
+    ) -> Result<Dataset> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let dataset = dataset_service.create(input, parsed_token.user_id).await?;
+
+        Ok(dataset)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn update_dataset<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        input: DatasetUpdateInput,
+    ) -> Result<Dataset> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let dataset = dataset_service.update_by_id(&id, input).await?;
+
+        Ok(dataset)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_dataset<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Dataset> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let dataset = dataset_service.delete_by_id(&id).await?;
+
+        Ok(dataset)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_dataset_row<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: DatasetRowCreateInput,
+    ) -> Result<DatasetRow> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let row = dataset_service.create_row(input).await?;
+
+        Ok(row)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn update_dataset_row<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        input: DatasetRowUpdateInput,
+    ) -> Result<DatasetRow> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let row = dataset_service.update_row_by_id(&id, input).await?;
+
+        Ok(row)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_dataset_row<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<DatasetRow> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let row = dataset_service.delete_row_by_id(&id).await?;
+
+        Ok(row)
+    }
+
+    /// Adds rows from a JSONL or CSV document, after the existing rows unless
+    /// `replace` is set.
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn import_dataset_rows<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        format: DatasetFormat,
+        content: String,
+        #[graphql(default)] replace: bool,
+    ) -> Result<Vec<DatasetRow>> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let rows = dataset_service
+            .import_rows(&id, format, &content, replace)
+            .await?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,60 @@
+use async_graphql::connection::Connection;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::dataset::dataset_model::{Dataset, DatasetFormat};
+use crate::domains::dataset::dto::DatasetArgs;
+use crate::domains::models::UserRole;
+use crate::domains::services::DatasetServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+
+#[derive(Default)]
+pub struct DatasetQuery;
+
+#[Object]
+impl DatasetQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn datasets<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: DatasetArgs,
+    ) -> Result<Connection<Cursor, Dataset, AdditionalFields>> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_dataset = dataset_service.paginate(args).await?;
+
+        Ok(paginated_dataset.into())
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn dataset<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Option<Dataset>> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let dataset = dataset_service.find_by_id(&id).await?;
+
+        Ok(dataset)
+    }
+
+    /// The dataset's rows serialized in the given format, in row order.
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn export_dataset<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        format: DatasetFormat,
+    ) -> Result<String> {
+        let dataset_service = ctx
+            .data::<DatasetServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let content = dataset_service.export_rows(&id, format).await?;
+
+        Ok(content)
+    }
+}
@@ -0,0 +1,287 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::Utc;
+use dojo_macros::UpdateModel;
+use dojo_orm::pagination::Pagination;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::dataset::dataset_error::DatasetError;
+use crate::domains::dataset::dataset_format::{parse_rows, write_rows, RowData};
+use crate::domains::dto::{
+    DatasetArgs, DatasetCreateInput, DatasetRowArgs, DatasetRowCreateInput, DatasetRowUpdateInput,
+    DatasetUpdateInput,
+};
+use crate::domains::models::{Dataset, DatasetFormat, DatasetRow};
+use crate::state::AppState;
+
+/// Upper bound on rows per dataset, so a dataset can always be loaded in one go.
+pub const MAX_DATASET_ROWS: usize = 10000;
+
+#[async_trait::async_trait]
+pub trait DatasetServiceExt {
+    async fn paginate(&self, args: DatasetArgs) -> Result<Pagination<Dataset>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Dataset>>;
+    async fn create(&self, input: DatasetCreateInput, owner_id: Uuid) -> Result<Dataset>;
+    async fn update_by_id(&self, id: &Uuid, input: DatasetUpdateInput) -> Result<Dataset>;
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Dataset>;
+    async fn paginate_rows(
+        &self,
+        dataset_id: &Uuid,
+        args: DatasetRowArgs,
+    ) -> Result<Pagination<DatasetRow>>;
+    async fn find_rows(&self, dataset_id: &Uuid) -> Result<Vec<DatasetRow>>;
+    async fn create_row(&self, input: DatasetRowCreateInput) -> Result<DatasetRow>;
+    async fn update_row_by_id(&self, id: &Uuid, input: DatasetRowUpdateInput)
+        -> Result<DatasetRow>;
+    async fn delete_row_by_id(&self, id: &Uuid) -> Result<DatasetRow>;
+    async fn import_rows(
+        &self,
+        dataset_id: &Uuid,
+        format: DatasetFormat,
+        content: &str,
+        replace: bool,
+    ) -> Result<Vec<DatasetRow>>;
+    async fn export_rows(&self, dataset_id: &Uuid, format: DatasetFormat) -> Result<String>;
+}
+
+pub type DatasetServiceDyn = Arc<dyn DatasetServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for DatasetServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.dataset_service.clone()
+    }
+}
+
+#[derive(UpdateModel)]
+struct DatasetRowChanges {
+    variables: Option<serde_json::Value>,
+    expected_output: Option<String>,
+    index: Option<i32>,
+}
+
+#[derive(TypedBuilder)]
+pub struct DatasetService {
+    db: Database,
+}
+
+impl DatasetService {
+    async fn count_rows(&self, dataset_id: &Uuid) -> Result<usize> {
+        let count = self
+            .db
+            .bind::<DatasetRow>()
+            .where_by(equals("dataset_id", dataset_id))
+            .count()
+            .await?;
+
+        Ok(count as usize)
+    }
+
+    async fn delete_rows(&self, dataset_id: &Uuid) -> Result<()> {
+        let conn = self.db.get().await?;
+        conn.execute(
+            "DELETE FROM dataset_rows WHERE dataset_id = $1",
+            &[dataset_id],
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl DatasetServiceExt for DatasetService {
+    async fn paginate(&self, args: DatasetArgs) -> Result<Pagination<Dataset>> {
+        let mut predicates = vec![];
+        if let Some(r#where) = &args.r#where {
+            if let Some(owner_id_args) = &r#where.owner_id {
+                if let Some(id) = &owner_id_args.equals {
+                    predicates.push(equals("owner_id", id));
+                }
+            }
+            if let Some(thread_id_args) = &r#where.thread_id {
+                if let Some(id) = &thread_id_args.equals {
+                    predicates.push(equals("thread_id", id));
+                }
+            }
+        }
+
+        self.db
+            .bind::<Dataset>()
+            .where_by(and(&predicates))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Dataset>> {
+        self.db
+            .bind::<Dataset>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    async fn create(&self, input: DatasetCreateInput, owner_id: Uuid) -> Result<Dataset> {
+        let input = Dataset {
+            id: Uuid::new_v4(),
+            owner_id,
+            thread_id: input.thread_id,
+            name: input.name,
+            description: input.description,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await
+    }
+
+    async fn update_by_id(&self, id: &Uuid, input: DatasetUpdateInput) -> Result<Dataset> {
+        self.db
+            .update(&input)
+            .where_by(equals("id", id))
+            .exec()
+            .await
+    }
+
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Dataset> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    async fn paginate_rows(
+        &self,
+        dataset_id: &Uuid,
+        args: DatasetRowArgs,
+    ) -> Result<Pagination<DatasetRow>> {
+        self.db
+            .bind::<DatasetRow>()
+            .where_by(equals("dataset_id", dataset_id))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_rows(&self, dataset_id: &Uuid) -> Result<Vec<DatasetRow>> {
+        self.db
+            .bind::<DatasetRow>()
+            .where_by(equals("dataset_id", dataset_id))
+            .order_by(asc("index"))
+            .limit(MAX_DATASET_ROWS as i64)
+            .await
+    }
+
+    async fn create_row(&self, input: DatasetRowCreateInput) -> Result<DatasetRow> {
+        let count = self.count_rows(&input.dataset_id).await?;
+        if count >= MAX_DATASET_ROWS {
+            return Err(DatasetError::TooManyRows(MAX_DATASET_ROWS).into());
+        }
+
+        let input = DatasetRow {
+            id: Uuid::new_v4(),
+            dataset_id: input.dataset_id,
+            index: input.index.unwrap_or(count as i32),
+            variables: serde_json::to_value(input.variables)?,
+            expected_output: input.expected_output,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await
+    }
+
+    async fn update_row_by_id(
+        &self,
+        id: &Uuid,
+        input: DatasetRowUpdateInput,
+    ) -> Result<DatasetRow> {
+        let input = DatasetRowChanges {
+            variables: input.variables.map(serde_json::to_value).transpose()?,
+            expected_output: input.expected_output,
+            index: input.index,
+        };
+
+        self.db
+            .update(&input)
+            .where_by(equals("id", id))
+            .exec()
+            .await
+    }
+
+    async fn delete_row_by_id(&self, id: &Uuid) -> Result<DatasetRow> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    /// Appends the parsed rows after the existing ones, or replaces them all when
+    /// `replace` is set. Nothing is written if any line fails to parse.
+    async fn import_rows(
+        &self,
+        dataset_id: &Uuid,
+        format: DatasetFormat,
+        content: &str,
+        replace: bool,
+    ) -> Result<Vec<DatasetRow>> {
+        self.find_by_id(dataset_id)
+            .await?
+            .ok_or(DatasetError::NotFound)?;
+
+        let parsed = parse_rows(format, content)?;
+        let offset = if replace {
+            0
+        } else {
+            self.count_rows(dataset_id).await?
+        };
+        if offset + parsed.len() > MAX_DATASET_ROWS {
+            return Err(DatasetError::TooManyRows(MAX_DATASET_ROWS).into());
+        }
+
+        let rows = parsed
+            .into_iter()
+            .enumerate()
+            .map(
+                |(
+                    index,
+                    RowData {
+                        variables,
+                        expected_output,
+                    },
+                )| DatasetRow {
+                    id: Uuid::new_v4(),
+                    dataset_id: *dataset_id,
+                    index: (offset + index) as i32,
+                    variables: serde_json::Value::Object(variables),
+                    expected_output,
+                    created_at: Utc::now().naive_utc(),
+                    updated_at: Utc::now().naive_utc(),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        if replace {
+            self.delete_rows(dataset_id).await?;
+        }
+        let mut created = vec![];
+        for chunk in rows.chunks(1000) {
+            created.extend(self.db.insert_many(chunk).exec().await?);
+        }
+
+        Ok(created)
+    }
+
+    async fn export_rows(&self, dataset_id: &Uuid, format: DatasetFormat) -> Result<String> {
+        self.find_by_id(dataset_id)
+            .await?
+            .ok_or(DatasetError::NotFound)?;
+
+        let rows = self.find_rows(dataset_id).await?;
+
+        write_rows(format, &rows)
+    }
+}
+
+impl From<DatasetService> for DatasetServiceDyn {
+    fn from(value: DatasetService) -> Self {
+        Arc::new(value) as Self
+    }
+}
@@ -0,0 +1,36 @@
+use async_graphql::InputObject;
+use dojo_orm::pagination::Cursor;
+use uuid::Uuid;
+
+#[derive(InputObject, Default)]
+pub struct DatasetWhereOwnerIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct DatasetWhereThreadIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct DatasetWhereArgs {
+    pub owner_id: Option<DatasetWhereOwnerIdArgs>,
+    pub thread_id: Option<DatasetWhereThreadIdArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct DatasetArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub r#where: Option<DatasetWhereArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct DatasetRowArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+}
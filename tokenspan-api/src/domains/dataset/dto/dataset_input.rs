@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use async_graphql::InputObject;
+use dojo_macros::UpdateModel;
+use uuid::Uuid;
+
+#[derive(InputObject)]
+pub struct DatasetCreateInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub thread_id: Option<Uuid>,
+}
+
+#[derive(InputObject, UpdateModel)]
+pub struct DatasetUpdateInput {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub thread_id: Option<Uuid>,
+}
+
+#[derive(InputObject)]
+pub struct DatasetRowCreateInput {
+    pub dataset_id: Uuid,
+    pub variables: HashMap<String, String>,
+    pub expected_output: Option<String>,
+    /// Appended after the last row when unset.
+    pub index: Option<i32>,
+}
+
+#[derive(InputObject)]
+pub struct DatasetRowUpdateInput {
+    pub variables: Option<HashMap<String, String>>,
+    pub expected_output: Option<String>,
+    pub index: Option<i32>,
+}
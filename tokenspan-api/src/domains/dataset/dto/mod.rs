@@ -0,0 +1,5 @@
+pub use dataset_args::*;
+pub use dataset_input::*;
+
+mod dataset_args;
+mod dataset_input;
@@ -0,0 +1,10 @@
+pub use dataset_mutation::*;
+pub use dataset_query::*;
+
+pub mod dataset_error;
+pub mod dataset_format;
+pub mod dataset_model;
+mod dataset_mutation;
+mod dataset_query;
+pub mod dataset_service;
+pub mod dto;
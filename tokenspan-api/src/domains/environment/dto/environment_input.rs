@@ -0,0 +1,16 @@
+use async_graphql::InputObject;
+use uuid::Uuid;
+
+#[derive(InputObject)]
+pub struct EnvironmentCreateInput {
+    pub thread_id: Uuid,
+    pub name: String,
+}
+
+#[derive(InputObject)]
+pub struct EnvironmentPromoteInput {
+    pub thread_version_id: Uuid,
+    /// Created on first promotion when the thread has no environment by this
+    /// name yet.
+    pub environment: String,
+}
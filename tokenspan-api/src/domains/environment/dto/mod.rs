@@ -0,0 +1,5 @@
+mod environment_args;
+mod environment_input;
+
+pub use environment_args::*;
+pub use environment_input::*;
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EnvironmentError {
+    #[error("environment not found")]
+    NotFound,
+
+    #[error("invalid environment name `{0}`")]
+    InvalidName(String),
+
+    #[error("only published versions can be promoted")]
+    NotPublished,
+
+    #[error("the version is already in the environment")]
+    AlreadyPromoted,
+
+    #[error("the environment has no earlier version to roll back to")]
+    NothingToRollBack,
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,130 @@
+use async_graphql::connection::Connection;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use chrono::NaiveDateTime;
+use dojo_macros::{Model, Type};
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domains::dto::EnvironmentPromotionArgs;
+use crate::domains::loaders::{ThreadVersionLoader, UserLoader};
+use crate::domains::models::{ThreadVersion, TrafficSplit, User};
+use crate::domains::services::{EnvironmentServiceDyn, TrafficSplitServiceDyn};
+use crate::errors::AppError;
+
+/// A named deployment target of a thread, such as `production`, pointing at
+/// one of its published versions.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "environments", sort_keys = ["created_at", "id"])]
+pub struct Environment {
+    pub id: Uuid,
+    pub name: String,
+    pub thread_id: Uuid,
+    /// Unset until a version is first promoted.
+    pub thread_version_id: Option<Uuid>,
+    pub owner_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl Environment {
+    pub async fn thread_version<'a>(&self, ctx: &Context<'a>) -> Result<Option<ThreadVersion>> {
+        let Some(thread_version_id) = self.thread_version_id else {
+            return Ok(None);
+        };
+        let thread_version_loader = ctx
+            .data::<DataLoader<ThreadVersionLoader>>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let thread_version = thread_version_loader.load_one(thread_version_id).await?;
+
+        Ok(thread_version)
+    }
+
+    /// The split routing the environment's runs, while one is active.
+    pub async fn traffic_split<'a>(&self, ctx: &Context<'a>) -> Result<Option<TrafficSplit>> {
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let traffic_split = traffic_split_service
+            .find_active_by_environment_id(&self.id)
+            .await?;
+
+        Ok(traffic_split)
+    }
+
+    pub async fn promotions<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: EnvironmentPromotionArgs,
+    ) -> Result<Connection<Cursor, EnvironmentPromotion, AdditionalFields>> {
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_promotion = environment_service
+            .paginate_promotions(&self.id, args)
+            .await?;
+
+        Ok(paginated_promotion.into())
+    }
+}
+
+/// One change of an environment's version.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "environment_promotions", sort_keys = ["created_at", "id"])]
+pub struct EnvironmentPromotion {
+    pub id: Uuid,
+    pub environment_id: Uuid,
+    /// The version the environment points at after this change.
+    pub thread_version_id: Uuid,
+    pub previous_thread_version_id: Option<Uuid>,
+    pub action: PromotionAction,
+    /// The promotion a rollback from this one returns to.
+    pub parent_id: Option<Uuid>,
+    pub promoted_by_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl EnvironmentPromotion {
+    pub async fn thread_version<'a>(&self, ctx: &Context<'a>) -> Result<Option<ThreadVersion>> {
+        let thread_version_loader = ctx
+            .data::<DataLoader<ThreadVersionLoader>>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let thread_version = thread_version_loader
+            .load_one(self.thread_version_id)
+            .await?;
+
+        Ok(thread_version)
+    }
+
+    pub async fn promoted_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let user_loader = ctx
+            .data::<DataLoader<UserLoader>>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let user = user_loader.load_one(self.promoted_by_id).await?;
+
+        Ok(user)
+    }
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Deserialize, Type)]
+#[dojo(name = "promotion_action", rename_all = "lowercase")]
+pub enum PromotionAction {
+    #[strum(serialize = "promote")]
+    #[serde(rename = "promote")]
+    Promote,
+    #[strum(serialize = "rollback")]
+    #[serde(rename = "rollback")]
+    Rollback,
+}
@@ -0,0 +1,95 @@
+use async_graphql::{Context, ErrorExtensions, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::environment::dto::{EnvironmentCreateInput, EnvironmentPromoteInput};
+use crate::domains::environment::environment_model::{Environment, EnvironmentPromotion};
+use crate::domains::models::{ParsedToken, UserRole};
+use crate::domains::services::EnvironmentServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct EnvironmentMutation;
+
+#[Object]
+impl EnvironmentMutation {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_environment<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: EnvironmentCreateInput,
+    ) -> Result<Environment> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let environment = environment_service
+            .create(input, parsed_token.user_id)
+            .await?;
+
+        Ok(environment)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_environment<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Environment> {
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let environment = environment_service.delete_by_id(&id).await?;
+
+        Ok(environment)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn promote_thread_version<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: EnvironmentPromoteInput,
+    ) -> Result<EnvironmentPromotion> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let promotion = environment_service
+            .promote(input, parsed_token.user_id)
+            .await?;
+
+        Ok(promotion)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn rollback_environment<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+    ) -> Result<EnvironmentPromotion> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let promotion = environment_service
+            .rollback(&id, parsed_token.user_id)
+            .await?;
+
+        Ok(promotion)
+    }
+}
@@ -0,0 +1,44 @@
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::environment::environment_model::Environment;
+use crate::domains::models::UserRole;
+use crate::domains::services::EnvironmentServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct EnvironmentQuery;
+
+#[Object]
+impl EnvironmentQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn environments<'a>(
+        &self,
+        ctx: &Context<'a>,
+        thread_id: Uuid,
+    ) -> Result<Vec<Environment>> {
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let environments = environment_service.find_by_thread_id(&thread_id).await?;
+
+        Ok(environments)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn environment<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+    ) -> Result<Option<Environment>> {
+        let environment_service = ctx
+            .data::<EnvironmentServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let environment = environment_service.find_by_id(&id).await?;
+
+        Ok(environment)
+    }
+}
@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::Utc;
+use dojo_macros::UpdateModel;
+use dojo_orm::pagination::Pagination;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::dto::{
+    EnvironmentCreateInput, EnvironmentPromoteInput, EnvironmentPromotionArgs,
+};
+use crate::domains::errors::EnvironmentError;
+use crate::domains::models::{
+    Environment, EnvironmentPromotion, PromotionAction, ThreadVersionStatus,
+};
+use crate::domains::services::ThreadVersionServiceDyn;
+use crate::state::AppState;
+
+#[async_trait::async_trait]
+pub trait EnvironmentServiceExt {
+    async fn find_by_thread_id(&self, thread_id: &Uuid) -> Result<Vec<Environment>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Environment>>;
+    async fn find_by_name(&self, thread_id: &Uuid, name: &str) -> Result<Option<Environment>>;
+    async fn create(&self, input: EnvironmentCreateInput, owner_id: Uuid) -> Result<Environment>;
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Environment>;
+    async fn promote(
+        &self,
+        input: EnvironmentPromoteInput,
+        promoted_by_id: Uuid,
+    ) -> Result<EnvironmentPromotion>;
+    async fn rollback(&self, id: &Uuid, promoted_by_id: Uuid) -> Result<EnvironmentPromotion>;
+    async fn paginate_promotions(
+        &self,
+        environment_id: &Uuid,
+        args: EnvironmentPromotionArgs,
+    ) -> Result<Pagination<EnvironmentPromotion>>;
+}
+
+pub type EnvironmentServiceDyn = Arc<dyn EnvironmentServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for EnvironmentServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.environment_service.clone()
+    }
+}
+
+#[derive(UpdateModel)]
+struct EnvironmentChanges {
+    thread_version_id: Option<Uuid>,
+    updated_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(TypedBuilder)]
+pub struct EnvironmentService {
+    db: Database,
+    thread_version_service: ThreadVersionServiceDyn,
+}
+
+impl EnvironmentService {
+    async fn find_latest_promotion(
+        &self,
+        environment_id: &Uuid,
+    ) -> Result<Option<EnvironmentPromotion>> {
+        self.db
+            .bind::<EnvironmentPromotion>()
+            .where_by(equals("environment_id", environment_id))
+            .order_by(desc("created_at"))
+            .first()
+            .await
+    }
+
+    /// Records the change and points the environment at its version.
+    async fn record(
+        &self,
+        environment: &Environment,
+        thread_version_id: Uuid,
+        action: PromotionAction,
+        parent_id: Option<Uuid>,
+        promoted_by_id: Uuid,
+    ) -> Result<EnvironmentPromotion> {
+        let now = Utc::now().naive_utc();
+        let input = EnvironmentPromotion {
+            id: Uuid::new_v4(),
+            environment_id: environment.id,
+            thread_version_id,
+            previous_thread_version_id: environment.thread_version_id,
+            action,
+            parent_id,
+            promoted_by_id,
+            created_at: now,
+            updated_at: now,
+        };
+        let promotion = self.db.insert(&input).exec().await?;
+
+        let changes = EnvironmentChanges {
+            thread_version_id: Some(thread_version_id),
+            updated_at: Some(now),
+        };
+        self.db
+            .update::<Environment, _>(&changes)
+            .where_by(equals("id", &environment.id))
+            .exec()
+            .await?;
+
+        Ok(promotion)
+    }
+}
+
+#[async_trait::async_trait]
+impl EnvironmentServiceExt for EnvironmentService {
+    async fn find_by_thread_id(&self, thread_id: &Uuid) -> Result<Vec<Environment>> {
+        self.db
+            .bind::<Environment>()
+            .where_by(equals("thread_id", thread_id))
+            .order_by(asc("name"))
+            .all()
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Environment>> {
+        self.db
+            .bind::<Environment>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    async fn find_by_name(&self, thread_id: &Uuid, name: &str) -> Result<Option<Environment>> {
+        self.db
+            .bind::<Environment>()
+            .where_by(and(&[
+                equals("thread_id", thread_id),
+                equals("name", &name),
+            ]))
+            .first()
+            .await
+    }
+
+    async fn create(&self, input: EnvironmentCreateInput, owner_id: Uuid) -> Result<Environment> {
+        validate_name(&input.name)?;
+
+        let input = Environment {
+            id: Uuid::new_v4(),
+            name: input.name,
+            thread_id: input.thread_id,
+            thread_version_id: None,
+            owner_id,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await
+    }
+
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Environment> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    /// Points the environment at a published version of its thread, creating
+    /// the environment when the thread has none by that name.
+    async fn promote(
+        &self,
+        input: EnvironmentPromoteInput,
+        promoted_by_id: Uuid,
+    ) -> Result<EnvironmentPromotion> {
+        let thread_version = self
+            .thread_version_service
+            .find_by_id(&input.thread_version_id)
+            .await?
+            .ok_or(anyhow::anyhow!("thread version not found"))?;
+        if thread_version.status != ThreadVersionStatus::Published {
+            return Err(EnvironmentError::NotPublished.into());
+        }
+
+        let environment = match self
+            .find_by_name(&thread_version.thread_id, &input.environment)
+            .await?
+        {
+            Some(environment) => environment,
+            None => {
+                let create_input = EnvironmentCreateInput {
+                    thread_id: thread_version.thread_id,
+                    name: input.environment,
+                };
+                self.create(create_input, promoted_by_id).await?
+            }
+        };
+        if environment.thread_version_id == Some(thread_version.id) {
+            return Err(EnvironmentError::AlreadyPromoted.into());
+        }
+
+        let parent_id = self
+            .find_latest_promotion(&environment.id)
+            .await?
+            .map(|promotion| promotion.id);
+
+        self.record(
+            &environment,
+            thread_version.id,
+            PromotionAction::Promote,
+            parent_id,
+            promoted_by_id,
+        )
+        .await
+    }
+
+    /// Returns the environment to the version it had before its latest
+    /// promotion. Rolling back again keeps walking back through the history.
+    async fn rollback(&self, id: &Uuid, promoted_by_id: Uuid) -> Result<EnvironmentPromotion> {
+        let environment = self
+            .find_by_id(id)
+            .await?
+            .ok_or(EnvironmentError::NotFound)?;
+
+        let latest = self
+            .find_latest_promotion(&environment.id)
+            .await?
+            .ok_or(EnvironmentError::NothingToRollBack)?;
+        let parent_id = latest
+            .parent_id
+            .ok_or(EnvironmentError::NothingToRollBack)?;
+        let parent = self
+            .db
+            .bind::<EnvironmentPromotion>()
+            .where_by(equals("id", &parent_id))
+            .first()
+            .await?
+            .ok_or(EnvironmentError::NothingToRollBack)?;
+
+        self.record(
+            &environment,
+            parent.thread_version_id,
+            PromotionAction::Rollback,
+            parent.parent_id,
+            promoted_by_id,
+        )
+        .await
+    }
+
+    async fn paginate_promotions(
+        &self,
+        environment_id: &Uuid,
+        args: EnvironmentPromotionArgs,
+    ) -> Result<Pagination<EnvironmentPromotion>> {
+        self.db
+            .bind::<EnvironmentPromotion>()
+            .where_by(equals("environment_id", environment_id))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), EnvironmentError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(EnvironmentError::InvalidName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+impl From<EnvironmentService> for EnvironmentServiceDyn {
+    fn from(value: EnvironmentService) -> Self {
+        Arc::new(value) as Self
+    }
+}
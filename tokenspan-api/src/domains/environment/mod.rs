@@ -0,0 +1,9 @@
+pub use environment_mutation::*;
+pub use environment_query::*;
+
+pub mod dto;
+pub mod environment_error;
+pub mod environment_model;
+mod environment_mutation;
+mod environment_query;
+pub mod environment_service;
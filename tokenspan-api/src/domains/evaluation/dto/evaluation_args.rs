@@ -0,0 +1,47 @@
+use async_graphql::InputObject;
+use dojo_orm::pagination::Cursor;
+use uuid::Uuid;
+
+#[derive(InputObject, Default)]
+pub struct EvaluatorWhereThreadVersionIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EvaluatorWhereArgs {
+    pub thread_version_id: Option<EvaluatorWhereThreadVersionIdArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EvaluatorArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub r#where: Option<EvaluatorWhereArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EvaluationWhereExecutionIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EvaluationWhereThreadVersionIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EvaluationWhereArgs {
+    pub execution_id: Option<EvaluationWhereExecutionIdArgs>,
+    pub thread_version_id: Option<EvaluationWhereThreadVersionIdArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EvaluationArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub r#where: Option<EvaluationWhereArgs>,
+}
@@ -0,0 +1,30 @@
+use async_graphql::InputObject;
+use dojo_macros::UpdateModel;
+use uuid::Uuid;
+
+use crate::domains::models::EvaluatorKind;
+
+#[derive(InputObject)]
+pub struct EvaluatorCreateInput {
+    pub thread_version_id: Uuid,
+    pub name: String,
+    pub kind: EvaluatorKind,
+    pub value: Option<String>,
+    pub threshold: Option<f64>,
+    #[graphql(default = 1.0)]
+    pub weight: f64,
+    pub judge_parameter_id: Option<Uuid>,
+    pub judge_api_key_id: Option<Uuid>,
+    pub judge_max_score: Option<f64>,
+}
+
+#[derive(InputObject, UpdateModel)]
+pub struct EvaluatorUpdateInput {
+    pub name: Option<String>,
+    pub value: Option<String>,
+    pub threshold: Option<f64>,
+    pub weight: Option<f64>,
+    pub judge_parameter_id: Option<Uuid>,
+    pub judge_api_key_id: Option<Uuid>,
+    pub judge_max_score: Option<f64>,
+}
@@ -0,0 +1,5 @@
+mod evaluation_args;
+mod evaluation_input;
+
+pub use evaluation_args::*;
+pub use evaluation_input::*;
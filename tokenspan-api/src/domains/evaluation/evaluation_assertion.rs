@@ -0,0 +1,310 @@
+use jsonschema::JSONSchema;
+use regex::Regex;
+
+use crate::domains::evaluation::evaluation_error::EvaluationError;
+use crate::domains::models::{
+    AssertionResult, Evaluator, EvaluatorKind, Execution, ExecutionStatus,
+};
+
+const DEFAULT_SIMILARITY: f64 = 0.8;
+const DEFAULT_JUDGE_THRESHOLD: f64 = 0.5;
+
+/// Checks that an evaluator has what its kind needs, so that a bad pattern or
+/// schema is reported when it is saved rather than on every execution.
+pub fn validate_evaluator(evaluator: &Evaluator) -> Result<(), EvaluationError> {
+    let invalid = |reason: String| EvaluationError::InvalidEvaluator {
+        kind: evaluator.kind.to_string(),
+        reason,
+    };
+
+    if evaluator.weight <= 0.0 {
+        return Err(invalid("weight must be positive".to_string()));
+    }
+    match evaluator.kind {
+        EvaluatorKind::Regex => {
+            let pattern = evaluator
+                .value
+                .as_ref()
+                .ok_or(invalid("a pattern is required".to_string()))?;
+            Regex::new(pattern).map_err(|e| invalid(e.to_string()))?;
+        }
+        EvaluatorKind::JsonSchema => {
+            let schema = evaluator
+                .value
+                .as_ref()
+                .ok_or(invalid("a schema is required".to_string()))?;
+            let schema = serde_json::from_str::<serde_json::Value>(schema)
+                .map_err(|e| invalid(e.to_string()))?;
+            JSONSchema::compile(&schema).map_err(|e| invalid(e.to_string()))?;
+        }
+        EvaluatorKind::MaxLength | EvaluatorKind::Latency | EvaluatorKind::Cost => {
+            if evaluator.threshold.is_none() {
+                return Err(invalid("a threshold is required".to_string()));
+            }
+        }
+        EvaluatorKind::Similarity | EvaluatorKind::Judge => {
+            if evaluator
+                .threshold
+                .is_some_and(|threshold| !(0.0..=1.0).contains(&threshold))
+            {
+                return Err(invalid("threshold must be between 0 and 1".to_string()));
+            }
+        }
+        EvaluatorKind::ExactMatch | EvaluatorKind::Contains | EvaluatorKind::JsonValid => {}
+    }
+    if evaluator.kind == EvaluatorKind::Judge {
+        if evaluator.judge_parameter_id.is_none() || evaluator.judge_api_key_id.is_none() {
+            return Err(invalid(
+                "a judge parameter and API key are required".to_string(),
+            ));
+        }
+        if evaluator
+            .judge_max_score
+            .is_some_and(|max_score| max_score <= 0.0)
+        {
+            return Err(invalid("max score must be positive".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// The text evaluators look at: the last message the execution produced.
+pub fn execution_output(execution: &Execution) -> &str {
+    execution
+        .output_messages
+        .last()
+        .map(|message| message.content.as_str())
+        .unwrap_or_default()
+}
+
+/// The execution's input messages as `role: content` lines, for a judge to read.
+pub fn execution_input(execution: &Execution) -> String {
+    execution
+        .input_messages
+        .iter()
+        .map(|message| format!("{}: {}", message.role, message.content))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn assert_output(
+    evaluator: &Evaluator,
+    execution: &Execution,
+    expected_output: Option<&str>,
+) -> AssertionResult {
+    let output = execution_output(execution);
+    let reference = evaluator.value.as_deref().or(expected_output);
+    let (score, reason) = match evaluator.kind {
+        EvaluatorKind::ExactMatch => match reference {
+            Some(reference) => check(
+                output.trim() == reference.trim(),
+                "output does not match".to_string(),
+            ),
+            None => missing_reference(),
+        },
+        EvaluatorKind::Contains => match reference {
+            Some(reference) => check(
+                output.contains(reference),
+                format!("output does not contain {:?}", reference),
+            ),
+            None => missing_reference(),
+        },
+        EvaluatorKind::Regex => match evaluator.value.as_deref().map(Regex::new) {
+            Some(Ok(regex)) => check(
+                regex.is_match(output),
+                format!("output does not match /{}/", regex),
+            ),
+            Some(Err(e)) => (0.0, Some(e.to_string())),
+            None => (0.0, Some("no pattern".to_string())),
+        },
+        EvaluatorKind::JsonValid => match serde_json::from_str::<serde_json::Value>(output) {
+            Ok(_) => (1.0, None),
+            Err(e) => (0.0, Some(format!("output is not JSON: {}", e))),
+        },
+        EvaluatorKind::JsonSchema => assert_json_schema(evaluator.value.as_deref(), output),
+        EvaluatorKind::MaxLength => {
+            let length = output.chars().count();
+            assert_threshold(evaluator.threshold, length as f64, "characters")
+        }
+        EvaluatorKind::Latency => {
+            assert_threshold(evaluator.threshold, execution.elapsed.api_call, "seconds")
+        }
+        EvaluatorKind::Cost => match &execution.cost {
+            Some(cost) => assert_threshold(evaluator.threshold, cost.total, &cost.currency),
+            None => (0.0, Some("execution has no cost".to_string())),
+        },
+        EvaluatorKind::Similarity => match reference {
+            Some(reference) => {
+                let similarity = strsim::normalized_levenshtein(output, reference);
+                let threshold = evaluator.threshold.unwrap_or(DEFAULT_SIMILARITY);
+                if similarity >= threshold {
+                    (similarity, None)
+                } else {
+                    (
+                        similarity,
+                        Some(format!(
+                            "similarity {:.3} is below {}",
+                            similarity, threshold
+                        )),
+                    )
+                }
+            }
+            None => missing_reference(),
+        },
+        EvaluatorKind::Judge => (0.0, Some("not judged".to_string())),
+    };
+
+    AssertionResult {
+        evaluator_id: evaluator.id,
+        name: evaluator.name.clone(),
+        kind: evaluator.kind,
+        passed: reason.is_none(),
+        score,
+        reason,
+        rationale: None,
+        execution_id: None,
+    }
+}
+
+pub fn failed_assertion(evaluator: &Evaluator, reason: String) -> AssertionResult {
+    AssertionResult {
+        evaluator_id: evaluator.id,
+        name: evaluator.name.clone(),
+        kind: evaluator.kind,
+        passed: false,
+        score: 0.0,
+        reason: Some(reason),
+        rationale: None,
+        execution_id: None,
+    }
+}
+
+/// Grades an execution from the reply of the judge's own execution.
+pub fn assert_judgement(evaluator: &Evaluator, judge_execution: &Execution) -> AssertionResult {
+    let failed = |reason: String| AssertionResult {
+        execution_id: Some(judge_execution.id),
+        ..failed_assertion(evaluator, reason)
+    };
+    if judge_execution.status != ExecutionStatus::Success {
+        let error = judge_execution
+            .error
+            .as_ref()
+            .map(|error| error.to_string())
+            .unwrap_or_default();
+        return failed(format!("judge execution failed: {}", error));
+    }
+
+    let reply = execution_output(judge_execution);
+    let Some((score, rationale)) = parse_judgement(reply) else {
+        return failed(format!("no score in judge reply {:?}", reply));
+    };
+    let max_score = evaluator.judge_max_score.unwrap_or(1.0);
+    let score = (score / max_score).clamp(0.0, 1.0);
+    let threshold = evaluator.threshold.unwrap_or(DEFAULT_JUDGE_THRESHOLD);
+    let reason = (score < threshold).then(|| format!("score {:.3} is below {}", score, threshold));
+
+    AssertionResult {
+        evaluator_id: evaluator.id,
+        name: evaluator.name.clone(),
+        kind: evaluator.kind,
+        passed: reason.is_none(),
+        score,
+        reason,
+        rationale,
+        execution_id: Some(judge_execution.id),
+    }
+}
+
+/// Reads a score and optional rationale from a judge's reply: a JSON object with
+/// `score` and `rationale` (possibly in a code fence), `Score:` and `Rationale:`
+/// lines, or a bare number.
+pub fn parse_judgement(reply: &str) -> Option<(f64, Option<String>)> {
+    let trimmed = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    if let Ok(serde_json::Value::Object(object)) = serde_json::from_str(trimmed) {
+        let score = match object.get("score") {
+            Some(serde_json::Value::Number(score)) => score.as_f64(),
+            Some(serde_json::Value::String(score)) => score.trim().parse().ok(),
+            _ => None,
+        };
+        let rationale = object
+            .get("rationale")
+            .or(object.get("reason"))
+            .and_then(|rationale| rationale.as_str())
+            .map(str::to_string);
+
+        return score.map(|score| (score, rationale));
+    }
+    if let Ok(score) = trimmed.parse::<f64>() {
+        return Some((score, None));
+    }
+
+    let score = Regex::new(r"(?i)score\s*[:=]\s*(-?\d+(?:\.\d+)?)")
+        .ok()?
+        .captures(reply)?
+        .get(1)?
+        .as_str()
+        .parse()
+        .ok()?;
+    let rationale = Regex::new(r"(?is)(?:rationale|reason)\s*[:=]\s*(.+)")
+        .ok()?
+        .captures(reply)
+        .and_then(|captures| captures.get(1))
+        .map(|rationale| rationale.as_str().trim().to_string());
+
+    Some((score, rationale))
+}
+
+fn check(passed: bool, reason: String) -> (f64, Option<String>) {
+    if passed {
+        (1.0, None)
+    } else {
+        (0.0, Some(reason))
+    }
+}
+
+fn missing_reference() -> (f64, Option<String>) {
+    (0.0, Some("no value or expected output".to_string()))
+}
+
+fn assert_threshold(threshold: Option<f64>, actual: f64, unit: &str) -> (f64, Option<String>) {
+    match threshold {
+        Some(threshold) => check(
+            actual <= threshold,
+            format!("{} {} exceeds {}", actual, unit, threshold),
+        ),
+        None => (0.0, Some("no threshold".to_string())),
+    }
+}
+
+fn assert_json_schema(schema: Option<&str>, output: &str) -> (f64, Option<String>) {
+    let schema = match schema.map(serde_json::from_str::<serde_json::Value>) {
+        Some(Ok(schema)) => schema,
+        Some(Err(e)) => return (0.0, Some(format!("invalid schema: {}", e))),
+        None => return (0.0, Some("no schema".to_string())),
+    };
+    let schema = match JSONSchema::compile(&schema) {
+        Ok(schema) => schema,
+        Err(e) => return (0.0, Some(format!("invalid schema: {}", e))),
+    };
+    let output = match serde_json::from_str::<serde_json::Value>(output) {
+        Ok(output) => output,
+        Err(e) => return (0.0, Some(format!("output is not JSON: {}", e))),
+    };
+
+    let result = schema.validate(&output).map_err(|errors| {
+        errors
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>()
+            .join("; ")
+    });
+    match result {
+        Ok(()) => (1.0, None),
+        Err(reason) => (0.0, Some(reason)),
+    }
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EvaluationError {
+    #[error("evaluator not found")]
+    EvaluatorNotFound,
+
+    #[error("invalid {kind} evaluator: {reason}")]
+    InvalidEvaluator { kind: String, reason: String },
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,114 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::NaiveDateTime;
+use dojo_macros::{EmbeddedModel, Model, Type};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+/// An assertion on the output of a thread version's executions.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[dojo(name = "evaluators", sort_keys = ["created_at", "id"])]
+pub struct Evaluator {
+    pub id: Uuid,
+    pub thread_version_id: Uuid,
+    pub name: String,
+    pub kind: EvaluatorKind,
+    /// The text, pattern or JSON Schema to check against, depending on `kind`.
+    pub value: Option<String>,
+    pub threshold: Option<f64>,
+    /// Share of this evaluator in the evaluation's score.
+    pub weight: f64,
+    /// The rubric thread version and parameter a `Judge` evaluator runs.
+    pub judge_parameter_id: Option<Uuid>,
+    pub judge_api_key_id: Option<Uuid>,
+    /// Top of the scale the rubric asks the judge to score on. 1 when unset.
+    pub judge_max_score: Option<f64>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(
+    Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Serialize, Deserialize, Type,
+)]
+#[dojo(name = "evaluator_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluatorKind {
+    /// The output equals `value`, or the expected output when unset, ignoring
+    /// surrounding whitespace.
+    #[strum(serialize = "exact_match")]
+    ExactMatch,
+    /// The output contains `value`, or the expected output when unset.
+    #[strum(serialize = "contains")]
+    Contains,
+    /// The output matches the regular expression in `value`.
+    #[strum(serialize = "regex")]
+    Regex,
+    #[strum(serialize = "json_valid")]
+    JsonValid,
+    /// The output is JSON conforming to the JSON Schema in `value`.
+    #[strum(serialize = "json_schema")]
+    JsonSchema,
+    /// The output is at most `threshold` characters long.
+    #[strum(serialize = "max_length")]
+    MaxLength,
+    /// The provider calls took at most `threshold` seconds.
+    #[strum(serialize = "latency")]
+    Latency,
+    /// The execution cost at most `threshold`, in the model's currency.
+    #[strum(serialize = "cost")]
+    Cost,
+    /// The normalized edit similarity to `value`, or the expected output when
+    /// unset, is at least `threshold` (0.8 by default). Scored by the similarity.
+    #[strum(serialize = "similarity")]
+    Similarity,
+    /// Another thread grades the execution. It is given the `input`, `output` and
+    /// `expected_output` variables and should reply with a score, either as JSON
+    /// `{"score": .., "rationale": ..}` or as `Score: ..` and `Rationale: ..`
+    /// lines. Passes when the score over `judge_max_score` is at least `threshold`
+    /// (0.5 by default).
+    #[strum(serialize = "judge")]
+    Judge,
+}
+
+/// The outcome of one evaluator on one execution.
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize, EmbeddedModel)]
+pub struct AssertionResult {
+    pub evaluator_id: Uuid,
+    pub name: String,
+    pub kind: EvaluatorKind,
+    pub passed: bool,
+    /// Between 0 and 1.
+    pub score: f64,
+    pub reason: Option<String>,
+    /// The judge's explanation of its score.
+    #[serde(default)]
+    pub rationale: Option<String>,
+    /// The judge's own execution.
+    #[serde(default)]
+    pub execution_id: Option<Uuid>,
+}
+
+/// All evaluators of a thread version applied to one execution.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[dojo(name = "evaluations", sort_keys = ["created_at", "id"])]
+pub struct Evaluation {
+    pub id: Uuid,
+    pub execution_id: Uuid,
+    pub thread_version_id: Uuid,
+    pub expected_output: Option<String>,
+    pub results: Vec<AssertionResult>,
+    /// Weighted mean of the assertion scores.
+    pub score: f64,
+    /// Whether every assertion passed.
+    pub passed: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Evaluations aggregated over a batch run.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct EvaluationSummary {
+    pub evaluated: i64,
+    pub passed: i64,
+    pub average_score: Option<f64>,
+}
@@ -0,0 +1,84 @@
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::evaluation::dto::{EvaluatorCreateInput, EvaluatorUpdateInput};
+use crate::domains::evaluation::evaluation_model::{Evaluation, Evaluator};
+use crate::domains::models::UserRole;
+use crate::domains::services::{EvaluationServiceDyn, ExecutionServiceDyn, ThreadServiceDyn};
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct EvaluationMutation;
+
+#[Object]
+impl EvaluationMutation {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_evaluator<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: EvaluatorCreateInput,
+    ) -> Result<Evaluator> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluator = evaluation_service.create_evaluator(input).await?;
+
+        Ok(evaluator)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn update_evaluator<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        input: EvaluatorUpdateInput,
+    ) -> Result<Evaluator> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluator = evaluation_service
+            .update_evaluator_by_id(&id, input)
+            .await?;
+
+        Ok(evaluator)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_evaluator<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Evaluator> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluator = evaluation_service.delete_evaluator_by_id(&id).await?;
+
+        Ok(evaluator)
+    }
+
+    /// Evaluates an existing execution again, for example after its thread
+    /// version's evaluators changed.
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn evaluate_execution<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        expected_output: Option<String>,
+    ) -> Result<Option<Evaluation>> {
+        let execution_service = ctx
+            .data::<ExecutionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+        let thread_service = ctx
+            .data::<ThreadServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let execution = execution_service
+            .find_by_id(&id)
+            .await?
+            .ok_or(AppError::NotFound("execution".to_string()))?;
+        let evaluation = thread_service.evaluate(&execution, expected_output).await?;
+
+        Ok(evaluation)
+    }
+}
@@ -0,0 +1,69 @@
+use async_graphql::connection::Connection;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::evaluation::dto::{EvaluationArgs, EvaluatorArgs};
+use crate::domains::evaluation::evaluation_model::{Evaluation, Evaluator};
+use crate::domains::models::UserRole;
+use crate::domains::services::EvaluationServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+
+#[derive(Default)]
+pub struct EvaluationQuery;
+
+#[Object]
+impl EvaluationQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn evaluators<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: EvaluatorArgs,
+    ) -> Result<Connection<Cursor, Evaluator, AdditionalFields>> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_evaluator = evaluation_service.paginate_evaluators(args).await?;
+
+        Ok(paginated_evaluator.into())
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn evaluator<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Option<Evaluator>> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluator = evaluation_service.find_evaluator_by_id(&id).await?;
+
+        Ok(evaluator)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn evaluations<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: EvaluationArgs,
+    ) -> Result<Connection<Cursor, Evaluation, AdditionalFields>> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_evaluation = evaluation_service.paginate(args).await?;
+
+        Ok(paginated_evaluation.into())
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn evaluation<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Option<Evaluation>> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluation = evaluation_service.find_by_id(&id).await?;
+
+        Ok(evaluation)
+    }
+}
@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::Utc;
+use dojo_orm::pagination::Pagination;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::dto::{
+    EvaluationArgs, EvaluatorArgs, EvaluatorCreateInput, EvaluatorUpdateInput,
+};
+use crate::domains::evaluation::evaluation_assertion::{
+    assert_output, failed_assertion, validate_evaluator,
+};
+use crate::domains::evaluation::evaluation_error::EvaluationError;
+use crate::domains::models::{
+    AssertionResult, Evaluation, EvaluationSummary, Evaluator, EvaluatorKind, Execution,
+};
+use crate::state::AppState;
+
+#[async_trait::async_trait]
+pub trait EvaluationServiceExt {
+    async fn paginate_evaluators(&self, args: EvaluatorArgs) -> Result<Pagination<Evaluator>>;
+    async fn find_evaluator_by_id(&self, id: &Uuid) -> Result<Option<Evaluator>>;
+    async fn find_evaluators_by_thread_version_id(
+        &self,
+        thread_version_id: &Uuid,
+    ) -> Result<Vec<Evaluator>>;
+    async fn create_evaluator(&self, input: EvaluatorCreateInput) -> Result<Evaluator>;
+    async fn duplicate_evaluators_by_thread_version_id(
+        &self,
+        current_thread_version_id: &Uuid,
+        new_thread_version_id: Uuid,
+    ) -> Result<Vec<Evaluator>>;
+    async fn update_evaluator_by_id(
+        &self,
+        id: &Uuid,
+        input: EvaluatorUpdateInput,
+    ) -> Result<Evaluator>;
+    async fn delete_evaluator_by_id(&self, id: &Uuid) -> Result<Evaluator>;
+    async fn paginate(&self, args: EvaluationArgs) -> Result<Pagination<Evaluation>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Evaluation>>;
+    async fn find_by_execution_id(&self, execution_id: &Uuid) -> Result<Option<Evaluation>>;
+    async fn find_by_execution_ids(&self, execution_ids: &[Uuid]) -> Result<Vec<Evaluation>>;
+    async fn evaluate(
+        &self,
+        execution: &Execution,
+        expected_output: Option<String>,
+        judgements: Vec<AssertionResult>,
+    ) -> Result<Option<Evaluation>>;
+    async fn summarize_run(&self, run_id: &Uuid) -> Result<EvaluationSummary>;
+}
+
+pub type EvaluationServiceDyn = Arc<dyn EvaluationServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for EvaluationServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.evaluation_service.clone()
+    }
+}
+
+#[derive(TypedBuilder)]
+pub struct EvaluationService {
+    db: Database,
+}
+
+#[async_trait::async_trait]
+impl EvaluationServiceExt for EvaluationService {
+    async fn paginate_evaluators(&self, args: EvaluatorArgs) -> Result<Pagination<Evaluator>> {
+        let mut predicates = vec![];
+        if let Some(r#where) = &args.r#where {
+            if let Some(thread_version_id_args) = &r#where.thread_version_id {
+                if let Some(id) = &thread_version_id_args.equals {
+                    predicates.push(equals("thread_version_id", id));
+                }
+            }
+        }
+
+        self.db
+            .bind::<Evaluator>()
+            .where_by(and(&predicates))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_evaluator_by_id(&self, id: &Uuid) -> Result<Option<Evaluator>> {
+        self.db
+            .bind::<Evaluator>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    async fn find_evaluators_by_thread_version_id(
+        &self,
+        thread_version_id: &Uuid,
+    ) -> Result<Vec<Evaluator>> {
+        self.db
+            .bind::<Evaluator>()
+            .where_by(equals("thread_version_id", thread_version_id))
+            .all()
+            .await
+    }
+
+    async fn create_evaluator(&self, input: EvaluatorCreateInput) -> Result<Evaluator> {
+        let input = Evaluator {
+            id: Uuid::new_v4(),
+            thread_version_id: input.thread_version_id,
+            name: input.name,
+            kind: input.kind,
+            value: input.value,
+            threshold: input.threshold,
+            weight: input.weight,
+            judge_parameter_id: input.judge_parameter_id,
+            judge_api_key_id: input.judge_api_key_id,
+            judge_max_score: input.judge_max_score,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+        validate_evaluator(&input)?;
+
+        self.db.insert(&input).exec().await
+    }
+
+    async fn duplicate_evaluators_by_thread_version_id(
+        &self,
+        current_thread_version_id: &Uuid,
+        new_thread_version_id: Uuid,
+    ) -> Result<Vec<Evaluator>> {
+        let mut evaluators = self
+            .find_evaluators_by_thread_version_id(current_thread_version_id)
+            .await?;
+
+        if evaluators.is_empty() {
+            return Ok(vec![]);
+        }
+
+        for evaluator in &mut evaluators {
+            evaluator.id = Uuid::new_v4();
+            evaluator.thread_version_id = new_thread_version_id;
+            evaluator.created_at = Utc::now().naive_utc();
+            evaluator.updated_at = Utc::now().naive_utc();
+        }
+
+        self.db.insert_many(&evaluators).exec().await
+    }
+
+    async fn update_evaluator_by_id(
+        &self,
+        id: &Uuid,
+        input: EvaluatorUpdateInput,
+    ) -> Result<Evaluator> {
+        let evaluator = self
+            .find_evaluator_by_id(id)
+            .await?
+            .ok_or(EvaluationError::EvaluatorNotFound)?;
+        validate_evaluator(&Evaluator {
+            value: input.value.clone().or(evaluator.value.clone()),
+            threshold: input.threshold.or(evaluator.threshold),
+            weight: input.weight.unwrap_or(evaluator.weight),
+            judge_parameter_id: input.judge_parameter_id.or(evaluator.judge_parameter_id),
+            judge_api_key_id: input.judge_api_key_id.or(evaluator.judge_api_key_id),
+            judge_max_score: input.judge_max_score.or(evaluator.judge_max_score),
+            ..evaluator
+        })?;
+
+        self.db
+            .update(&input)
+            .where_by(equals("id", id))
+            .exec()
+            .await
+    }
+
+    async fn delete_evaluator_by_id(&self, id: &Uuid) -> Result<Evaluator> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    async fn paginate(&self, args: EvaluationArgs) -> Result<Pagination<Evaluation>> {
+        let mut predicates = vec![];
+        if let Some(r#where) = &args.r#where {
+            if let Some(execution_id_args) = &r#where.execution_id {
+                if let Some(id) = &execution_id_args.equals {
+                    predicates.push(equals("execution_id", id));
+                }
+            }
+            if let Some(thread_version_id_args) = &r#where.thread_version_id {
+                if let Some(id) = &thread_version_id_args.equals {
+                    predicates.push(equals("thread_version_id", id));
+                }
+            }
+        }
+
+        self.db
+            .bind::<Evaluation>()
+            .where_by(and(&predicates))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Evaluation>> {
+        self.db
+            .bind::<Evaluation>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    /// The most recent evaluation of the execution.
+    async fn find_by_execution_id(&self, execution_id: &Uuid) -> Result<Option<Evaluation>> {
+        self.db
+            .bind::<Evaluation>()
+            .where_by(equals("execution_id", execution_id))
+            .order_by(desc("created_at"))
+            .first()
+            .await
+    }
+
+    async fn find_by_execution_ids(&self, execution_ids: &[Uuid]) -> Result<Vec<Evaluation>> {
+        self.db
+            .bind::<Evaluation>()
+            .where_by(in_list("execution_id", &execution_ids))
+            .all()
+            .await
+    }
+
+    /// Runs the evaluators of the execution's thread version on it and stores the
+    /// results. Judges need another execution, so their results are passed in as
+    /// `judgements`. Returns `None` when the thread version has no evaluators.
+    async fn evaluate(
+        &self,
+        execution: &Execution,
+        expected_output: Option<String>,
+        judgements: Vec<AssertionResult>,
+    ) -> Result<Option<Evaluation>> {
+        let evaluators = self
+            .find_evaluators_by_thread_version_id(&execution.thread_version_id)
+            .await?;
+        if evaluators.is_empty() {
+            return Ok(None);
+        }
+
+        let results = evaluators
+            .iter()
+            .map(|evaluator| match evaluator.kind {
+                EvaluatorKind::Judge => judgements
+                    .iter()
+                    .find(|judgement| judgement.evaluator_id == evaluator.id)
+                    .cloned()
+                    .unwrap_or_else(|| failed_assertion(evaluator, "not judged".to_string())),
+                _ => assert_output(evaluator, execution, expected_output.as_deref()),
+            })
+            .collect::<Vec<_>>();
+        let total_weight = evaluators
+            .iter()
+            .map(|evaluator| evaluator.weight)
+            .sum::<f64>();
+        let score = evaluators
+            .iter()
+            .zip(&results)
+            .map(|(evaluator, result)| evaluator.weight * result.score)
+            .sum::<f64>()
+            / total_weight;
+
+        let input = Evaluation {
+            id: Uuid::new_v4(),
+            execution_id: execution.id,
+            thread_version_id: execution.thread_version_id,
+            expected_output,
+            passed: results.iter().all(|result| result.passed),
+            results,
+            score,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await.map(Some)
+    }
+
+    async fn summarize_run(&self, run_id: &Uuid) -> Result<EvaluationSummary> {
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_one(
+                "SELECT COUNT(e.id), COUNT(e.id) FILTER (WHERE e.passed), AVG(e.score) \
+                 FROM run_items i \
+                 JOIN LATERAL (SELECT id, passed, score FROM evaluations \
+                 WHERE execution_id = i.execution_id ORDER BY created_at DESC LIMIT 1) e ON TRUE \
+                 WHERE i.run_id = $1",
+                &[run_id],
+            )
+            .await?;
+
+        Ok(EvaluationSummary {
+            evaluated: row.get(0),
+            passed: row.get(1),
+            average_score: row.get(2),
+        })
+    }
+}
+
+impl From<EvaluationService> for EvaluationServiceDyn {
+    fn from(value: EvaluationService) -> Self {
+        Arc::new(value) as Self
+    }
+}
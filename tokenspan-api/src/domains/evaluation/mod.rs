@@ -0,0 +1,10 @@
+pub use evaluation_mutation::*;
+pub use evaluation_query::*;
+
+pub mod dto;
+pub mod evaluation_assertion;
+pub mod evaluation_error;
+pub mod evaluation_model;
+mod evaluation_mutation;
+mod evaluation_query;
+pub mod evaluation_service;
@@ -2,11 +2,17 @@ use std::collections::HashMap;
 
 use uuid::Uuid;
 
-use crate::domains::models::{Elapsed, ExecutionStatus, Message, Parameter, Usage};
+use crate::domains::models::{
+    Cost, Elapsed, ExecutionAttempt, ExecutionStatus, Message, Parameter, Usage,
+};
 
 pub struct ExecutionCreateInput {
     pub thread_id: Uuid,
     pub thread_version_id: Uuid,
+    pub api_key_id: Uuid,
+    pub parent_execution_id: Option<Uuid>,
+    pub traffic_split_id: Option<Uuid>,
+    pub traffic_arm: Option<String>,
     pub parameter: Parameter,
     pub elapsed: Elapsed,
     pub input_messages: Vec<Message>,
@@ -14,6 +20,9 @@ pub struct ExecutionCreateInput {
     pub response: Option<serde_json::Value>,
     pub error: Option<serde_json::Value>,
     pub usage: Option<Usage>,
+    pub cost: Option<Cost>,
+    pub attempts: Vec<ExecutionAttempt>,
+    pub cache_hit: bool,
     pub status: ExecutionStatus,
     pub variables: HashMap<String, String>,
 }
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use dojo_orm::Database;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::adapters::ChatResponse;
+use crate::domains::cache::CacheExt;
+
+/// Provider responses keyed by `ChatRequest::cache_key`.
+pub type ResponseCacheDyn = Arc<dyn CacheExt<String, ChatResponse> + Send + Sync>;
+
+/// Keeps responses in process memory; entries are dropped once their TTL is up.
+#[derive(Clone)]
+pub struct MemoryResponseCache {
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<String, (Instant, ChatResponse)>>>,
+}
+
+impl MemoryResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheExt<String, ChatResponse> for MemoryResponseCache {
+    async fn set(&self, key: String, value: ChatResponse) {
+        let mut cache = self.cache.lock().await;
+        cache.retain(|_, (expires_at, _)| *expires_at > Instant::now());
+        cache.insert(key, (Instant::now() + self.ttl, value));
+    }
+
+    async fn get(&self, key: String) -> Option<ChatResponse> {
+        let cache = self.cache.lock().await;
+        cache
+            .get(&key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, value)| value.clone())
+    }
+}
+
+impl From<MemoryResponseCache> for ResponseCacheDyn {
+    fn from(value: MemoryResponseCache) -> Self {
+        Arc::new(value) as Self
+    }
+}
+
+/// Keeps responses in the `response_cache` table so they are shared between
+/// instances and survive restarts. Failures are logged and treated as misses.
+#[derive(Clone)]
+pub struct PostgresResponseCache {
+    db: Database,
+    ttl: Duration,
+}
+
+impl PostgresResponseCache {
+    pub fn new(db: Database, ttl: Duration) -> Self {
+        Self { db, ttl }
+    }
+
+    async fn try_set(&self, key: String, value: ChatResponse) -> anyhow::Result<()> {
+        let now = Utc::now().naive_utc();
+        let expires_at = now + chrono::Duration::from_std(self.ttl)?;
+        let response = serde_json::to_string(&value)?;
+
+        let conn = self.db.get().await?;
+        conn.execute(
+            "INSERT INTO response_cache (key, response, expires_at, created_at) \
+             VALUES ($1, $2::TEXT::jsonb, $3, $4) \
+             ON CONFLICT (key) DO UPDATE \
+             SET response = EXCLUDED.response, expires_at = EXCLUDED.expires_at",
+            &[&key, &response, &expires_at, &now],
+        )
+        .await?;
+        conn.execute("DELETE FROM response_cache WHERE expires_at <= $1", &[&now])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn try_get(&self, key: String) -> anyhow::Result<Option<ChatResponse>> {
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_opt(
+                "SELECT response::TEXT FROM response_cache WHERE key = $1 AND expires_at > $2",
+                &[&key, &Utc::now().naive_utc()],
+            )
+            .await?;
+
+        Ok(match row {
+            Some(row) => Some(serde_json::from_str(row.get::<_, &str>(0))?),
+            None => None,
+        })
+    }
+}
+
+#[async_trait]
+impl CacheExt<String, ChatResponse> for PostgresResponseCache {
+    async fn set(&self, key: String, value: ChatResponse) {
+        if let Err(e) = self.try_set(key, value).await {
+            warn!("failed to cache response: {:?}", e);
+        }
+    }
+
+    async fn get(&self, key: String) -> Option<ChatResponse> {
+        self.try_get(key).await.unwrap_or_else(|e| {
+            warn!("failed to read cached response: {:?}", e);
+            None
+        })
+    }
+}
+
+impl From<PostgresResponseCache> for ResponseCacheDyn {
+    fn from(value: PostgresResponseCache) -> Self {
+        Arc::new(value) as Self
+    }
+}
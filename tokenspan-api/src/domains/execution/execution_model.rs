@@ -1,11 +1,13 @@
-use async_graphql::{Enum, SimpleObject};
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject, Union};
 use chrono::NaiveDateTime;
 use dojo_macros::{EmbeddedModel, Model, Type};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 use uuid::Uuid;
 
-use crate::domains::models::{Message, Parameter};
+use crate::domains::models::{Evaluation, Message, Model, Parameter};
+use crate::domains::services::EvaluationServiceDyn;
+use crate::errors::AppError;
 
 #[derive(SimpleObject, Debug, Clone, Serialize, Deserialize, EmbeddedModel)]
 pub struct Usage {
@@ -14,6 +16,55 @@ pub struct Usage {
     pub total_tokens: i32,
 }
 
+impl std::ops::Add for Usage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            total_tokens: self.total_tokens + other.total_tokens,
+        }
+    }
+}
+
+/// What an execution cost, priced with the model's pricing at execution time.
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize, EmbeddedModel)]
+pub struct Cost {
+    pub input: f64,
+    pub output: f64,
+    pub total: f64,
+    pub currency: String,
+}
+
+impl Cost {
+    pub fn zero(currency: String) -> Self {
+        Self {
+            input: 0.0,
+            output: 0.0,
+            total: 0.0,
+            currency,
+        }
+    }
+
+    /// Returns `None` when the model's input and output prices are in different
+    /// currencies, since the total could not be added up.
+    pub fn new(usage: &Usage, model: &Model) -> Option<Self> {
+        if model.input_pricing.currency != model.output_pricing.currency {
+            return None;
+        }
+
+        let input = model.input_pricing.cost(usage.input_tokens);
+        let output = model.output_pricing.cost(usage.output_tokens);
+        Some(Self {
+            input,
+            output,
+            total: input + output,
+            currency: model.input_pricing.currency.clone(),
+        })
+    }
+}
+
 #[derive(SimpleObject, Default, Debug, Clone, Serialize, Deserialize, EmbeddedModel)]
 pub struct Elapsed {
     pub api_key: f64,
@@ -25,13 +76,38 @@ pub struct Elapsed {
     pub function: f64,
     pub api_call: f64,
     pub post: f64,
+    #[serde(default)]
+    pub time_to_first_token: Option<f64>,
+}
+
+/// One call to a provider made while answering an execution, kept so retries and
+/// fallbacks can be traced back to the model that actually answered.
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize, EmbeddedModel)]
+pub struct ExecutionAttempt {
+    pub parameter_id: Uuid,
+    pub model_id: Uuid,
+    pub provider_id: Uuid,
+    /// Attempt number against this model, starting at 1.
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub elapsed: f64,
+    pub created_at: NaiveDateTime,
 }
 
 #[derive(SimpleObject, Clone, Serialize, Debug, Model)]
+#[graphql(complex)]
 #[dojo(name = "executions", sort_keys = ["created_at", "id"])]
 pub struct Execution {
     pub id: Uuid,
+    pub thread_id: Uuid,
     pub thread_version_id: Uuid,
+    pub api_key_id: Option<Uuid>,
+    /// Set on a judge's execution to the execution it graded.
+    pub parent_execution_id: Option<Uuid>,
+    /// The traffic split and arm that served the execution, if any.
+    pub traffic_split_id: Option<Uuid>,
+    pub traffic_arm: Option<String>,
     pub executed_by_id: Uuid,
     pub parameter: Parameter,
     pub elapsed: Elapsed,
@@ -40,11 +116,28 @@ pub struct Execution {
     pub response: Option<serde_json::Value>,
     pub error: Option<serde_json::Value>,
     pub usage: Option<Usage>,
+    pub cost: Option<Cost>,
+    pub attempts: Vec<ExecutionAttempt>,
+    /// Answered entirely from the response cache, without calling the provider.
+    pub cache_hit: bool,
     pub status: ExecutionStatus,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+#[ComplexObject]
+impl Execution {
+    pub async fn evaluation<'a>(&self, ctx: &Context<'a>) -> Result<Option<Evaluation>> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluation = evaluation_service.find_by_execution_id(&self.id).await?;
+
+        Ok(evaluation)
+    }
+}
+
 #[derive(Type, Enum, Copy, Clone, Debug, Eq, PartialEq, EnumString, Display, Serialize)]
 #[dojo(name = "execution_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -53,4 +146,28 @@ pub enum ExecutionStatus {
     Success,
     #[strum(serialize = "failed")]
     Failed,
+    #[strum(serialize = "aborted")]
+    Aborted,
+}
+
+#[derive(SimpleObject, Debug, Clone, Serialize)]
+pub struct ExecutionDelta {
+    pub content: String,
+}
+
+#[derive(SimpleObject, Debug, Clone, Serialize)]
+pub struct ExecutionToolCallDelta {
+    pub index: i32,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
+/// Events emitted while a streamed execution is in flight. The stream always ends
+/// with `Completed` carrying the persisted execution, unless the consumer went away.
+#[derive(Union, Debug, Clone)]
+pub enum ExecutionEvent {
+    Delta(ExecutionDelta),
+    ToolCallDelta(ExecutionToolCallDelta),
+    Completed(Box<Execution>),
 }
@@ -57,7 +57,7 @@ impl ExecutionServiceExt for ExecutionService {
     async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Execution>> {
         self.db
             .bind::<Execution>()
-            .where_by(equals("id", &ids))
+            .where_by(in_list("id", &ids))
             .all()
             .await
     }
@@ -65,13 +65,21 @@ impl ExecutionServiceExt for ExecutionService {
     async fn create(&self, input: ExecutionCreateInput, executor_id: Uuid) -> Result<Execution> {
         let input = Execution {
             id: Uuid::new_v4(),
+            thread_id: input.thread_id,
             thread_version_id: input.thread_version_id,
+            api_key_id: Some(input.api_key_id),
+            parent_execution_id: input.parent_execution_id,
+            traffic_split_id: input.traffic_split_id,
+            traffic_arm: input.traffic_arm,
             executed_by_id: executor_id,
             parameter: input.parameter,
             input_messages: input.input_messages,
             output_messages: input.output_messages,
             elapsed: input.elapsed,
             usage: input.usage,
+            cost: input.cost,
+            attempts: input.attempts,
+            cache_hit: input.cache_hit,
             response: input.response,
             error: input.error,
             status: input.status,
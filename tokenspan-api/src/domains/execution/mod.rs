@@ -1,6 +1,7 @@
 pub use execution_query::ExecutionQuery;
 
 pub mod dto;
+pub mod execution_cache;
 mod execution_error;
 pub mod execution_loader;
 pub mod execution_model;
@@ -3,6 +3,8 @@ use dojo_macros::UpdateModel;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::prompts::ToolCall;
+
 #[derive(InputObject, Debug, Clone, Deserialize)]
 pub struct MessageCreateInput {
     pub raw: String,
@@ -10,6 +12,11 @@ pub struct MessageCreateInput {
     pub role: String,
     pub thread_version_id: Uuid,
     pub index: Option<i32>,
+    pub name: Option<String>,
+    pub tool_call_id: Option<String>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub tool_calls: Vec<ToolCall>,
 }
 
 #[derive(InputObject, UpdateModel)]
@@ -18,4 +25,7 @@ pub struct MessageUpdateInput {
     pub content: Option<String>,
     pub role: Option<String>,
     pub index: Option<i32>,
+    pub name: Option<String>,
+    pub tool_call_id: Option<String>,
+    pub tool_calls: Option<Vec<ToolCall>>,
 }
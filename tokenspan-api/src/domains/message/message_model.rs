@@ -1,9 +1,13 @@
+use std::str::FromStr;
+
 use async_graphql::SimpleObject;
 use chrono::NaiveDateTime;
 use dojo_macros::{EmbeddedModel, Model};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::prompts::{ChatMessage, PromptRole, ToolCall};
+
 #[derive(SimpleObject, Clone, Debug, Serialize, Deserialize, Model, EmbeddedModel)]
 #[dojo(name = "messages", sort_keys = ["created_at", "id"])]
 pub struct Message {
@@ -14,6 +18,29 @@ pub struct Message {
     pub content: String,
     pub role: String,
     pub index: i32,
+    /// Optional participant name, e.g. to tell few-shot speakers apart.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The call a `tool` message answers.
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+    /// Function calls requested by an `assistant` message.
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
+
+impl TryFrom<Message> for ChatMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        Ok(Self {
+            role: PromptRole::from_str(value.role.as_str())?,
+            content: value.content,
+            name: value.name,
+            tool_calls: value.tool_calls,
+            tool_call_id: value.tool_call_id,
+        })
+    }
+}
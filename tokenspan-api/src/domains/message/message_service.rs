@@ -11,6 +11,7 @@ use uuid::Uuid;
 
 use crate::domains::dto::{MessageArgs, MessageCreateInput, MessageUpdateInput};
 use crate::domains::models::Message;
+use crate::domains::services::ensure_editable;
 use crate::state::AppState;
 
 #[async_trait::async_trait]
@@ -76,6 +77,8 @@ impl MessageServiceExt for MessageService {
     }
 
     async fn create(&self, input: MessageCreateInput, owner_id: Uuid) -> Result<Message> {
+        ensure_editable(&self.db, &input.thread_version_id).await?;
+
         let index = if let Some(index) = input.index {
             index
         } else {
@@ -95,6 +98,9 @@ impl MessageServiceExt for MessageService {
             index,
             raw: input.raw,
             content: input.content,
+            name: input.name,
+            tool_call_id: input.tool_call_id,
+            tool_calls: input.tool_calls,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
         };
@@ -126,6 +132,12 @@ impl MessageServiceExt for MessageService {
     }
 
     async fn update_by_id(&self, id: &Uuid, input: MessageUpdateInput) -> Result<Message> {
+        let message = self
+            .find_by_id(id)
+            .await?
+            .ok_or(anyhow::anyhow!("message not found"))?;
+        ensure_editable(&self.db, &message.thread_version_id).await?;
+
         self.db
             .update(&input)
             .where_by(equals("id", id))
@@ -134,6 +146,12 @@ impl MessageServiceExt for MessageService {
     }
 
     async fn delete_by_id(&self, id: &Uuid) -> Result<Message> {
+        let message = self
+            .find_by_id(id)
+            .await?
+            .ok_or(anyhow::anyhow!("message not found"))?;
+        ensure_editable(&self.db, &message.thread_version_id).await?;
+
         self.db.delete().where_by(equals("id", id)).exec().await
     }
 }
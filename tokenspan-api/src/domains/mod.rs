@@ -5,7 +5,12 @@ use crate::state::AppState;
 
 mod api_key;
 mod auth;
+mod budget;
 mod cache;
+mod comparison;
+mod dataset;
+mod environment;
+mod evaluation;
 mod execution;
 mod function;
 mod health;
@@ -13,54 +18,99 @@ mod message;
 mod model;
 mod parameter;
 mod provider;
+mod run;
 mod thread;
 mod thread_version;
+mod traffic_split;
 mod user;
 
 pub mod services {
     pub use super::api_key::api_key_service::*;
     pub use super::auth::auth_service::*;
+    pub use super::budget::budget_service::*;
+    pub use super::comparison::comparison_report::*;
+    pub use super::comparison::comparison_service::*;
+    pub use super::dataset::dataset_format::*;
+    pub use super::dataset::dataset_service::*;
+    pub use super::environment::environment_service::*;
+    pub use super::evaluation::evaluation_assertion::*;
+    pub use super::evaluation::evaluation_service::*;
     pub use super::execution::execution_service::*;
     pub use super::function::function_service::*;
     pub use super::message::message_service::*;
     pub use super::model::model_service::*;
     pub use super::parameter::parameter_service::*;
     pub use super::provider::provider_service::*;
+    pub use super::run::run_service::*;
     pub use super::thread::thread_service::*;
+    pub use super::thread_version::thread_version_diff::*;
+    pub use super::thread_version::thread_version_lock::*;
+    pub use super::thread_version::thread_version_semver::*;
     pub use super::thread_version::thread_version_service::*;
+    pub use super::thread_version::thread_version_variable::*;
+    pub use super::traffic_split::traffic_split_service::*;
     pub use super::user::user_service::*;
 }
 
 pub mod models {
     pub use super::api_key::api_key_model::*;
     pub use super::auth::auth_model::*;
+    pub use super::budget::budget_model::*;
+    pub use super::comparison::comparison_model::*;
+    pub use super::dataset::dataset_model::*;
+    pub use super::environment::environment_model::*;
+    pub use super::evaluation::evaluation_model::*;
     pub use super::execution::execution_model::*;
     pub use super::function::function_model::*;
     pub use super::message::message_model::*;
     pub use super::model::model_model::*;
     pub use super::parameter::parameter_model::*;
     pub use super::provider::provider_model::*;
+    pub use super::run::run_model::*;
     pub use super::thread::thread_model::*;
     pub use super::thread_version::thread_version_model::*;
+    pub use super::traffic_split::traffic_split_model::*;
     pub use super::user::user_model::*;
 }
 
 pub mod dto {
     pub use super::api_key::dto::*;
     pub use super::auth::dto::*;
+    pub use super::budget::dto::*;
+    pub use super::comparison::dto::*;
+    pub use super::dataset::dto::*;
+    pub use super::environment::dto::*;
+    pub use super::evaluation::dto::*;
     pub use super::execution::dto::*;
     pub use super::function::dto::*;
     pub use super::message::dto::*;
     pub use super::model::dto::*;
     pub use super::parameter::dto::*;
     pub use super::provider::dto::*;
+    pub use super::run::dto::*;
     pub use super::thread::dto::*;
     pub use super::thread_version::dto::*;
+    pub use super::traffic_split::dto::*;
     pub use super::user::dto::*;
 }
 
+pub mod errors {
+    pub use super::budget::budget_error::*;
+    pub use super::comparison::comparison_error::*;
+    pub use super::dataset::dataset_error::*;
+    pub use super::environment::environment_error::*;
+    pub use super::evaluation::evaluation_error::*;
+    pub use super::parameter::parameter_error::*;
+    pub use super::run::run_error::*;
+    pub use super::thread::thread_error::*;
+    pub use super::thread_version::thread_version_error::*;
+    pub use super::traffic_split::traffic_split_error::*;
+}
+
 pub mod caches {
     pub use super::api_key::api_key_cache;
+    pub use super::cache::CacheExt;
+    pub use super::execution::execution_cache;
     pub use super::model::model_cache;
 }
 
@@ -87,6 +137,13 @@ pub struct QueryRoot(
     pub parameter::ParameterQuery,
     pub message::MessageQuery,
     pub function::FunctionQuery,
+    pub budget::BudgetQuery,
+    pub dataset::DatasetQuery,
+    pub run::RunQuery,
+    pub evaluation::EvaluationQuery,
+    pub comparison::ComparisonQuery,
+    pub environment::EnvironmentQuery,
+    pub traffic_split::TrafficSplitQuery,
 );
 
 #[derive(MergedObject, Default)]
@@ -101,6 +158,13 @@ pub struct MutationRoot(
     pub parameter::ParameterMutation,
     pub message::MessageMutation,
     pub function::FunctionMutation,
+    pub budget::BudgetMutation,
+    pub dataset::DatasetMutation,
+    pub run::RunMutation,
+    pub evaluation::EvaluationMutation,
+    pub comparison::ComparisonMutation,
+    pub environment::EnvironmentMutation,
+    pub traffic_split::TrafficSplitMutation,
 );
 
 #[derive(MergedSubscription, Default)]
@@ -11,6 +11,17 @@ pub struct Pricing {
     pub currency: String,
 }
 
+impl Pricing {
+    /// Prices `tokens` at this rate, which is `price` per `self.tokens` tokens.
+    pub fn cost(&self, tokens: i32) -> f64 {
+        if self.tokens == 0 {
+            return 0.0;
+        }
+
+        self.price * tokens as f64 / self.tokens as f64
+    }
+}
+
 #[derive(SimpleObject, Clone, Model, Debug, Deserialize)]
 #[dojo(name = "models", sort_keys = ["created_at", "id"])]
 pub struct Model {
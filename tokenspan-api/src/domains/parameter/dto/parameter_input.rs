@@ -46,5 +46,9 @@ pub struct ParameterUpdateInput {
     pub presence_penalty: Option<f32>,
     #[builder(setter(strip_option))]
     pub extra: Option<serde_json::Value>,
+    /// Makes this the parameter a version runs with by default, taking over
+    /// from the version's current default.
+    #[builder(setter(strip_option))]
+    pub is_default: Option<bool>,
     pub model_id: Option<Uuid>,
 }
@@ -2,7 +2,7 @@ pub use parameter_mutation::ParameterMutation;
 pub use parameter_query::ParameterQuery;
 
 pub mod dto;
-mod parameter_error;
+pub mod parameter_error;
 pub mod parameter_model;
 mod parameter_mutation;
 mod parameter_query;
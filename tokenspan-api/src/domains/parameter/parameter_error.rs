@@ -2,6 +2,9 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ParameterError {
+    #[error("parameter not found")]
+    NotFound,
+
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
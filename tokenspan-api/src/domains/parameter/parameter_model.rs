@@ -23,4 +23,7 @@ pub struct Parameter {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub is_default: bool,
+    /// The parameter this one was copied from on publish, traced back to the
+    /// version it was first created in.
+    pub origin_id: Option<Uuid>,
 }
@@ -9,13 +9,15 @@ use typed_builder::TypedBuilder;
 use uuid::Uuid;
 
 use crate::domains::dto::{ParameterArgs, ParameterCreateInput, ParameterUpdateInput};
+use crate::domains::errors::ParameterError;
 use crate::domains::models::Parameter;
+use crate::domains::services::ensure_editable;
 
 #[async_trait::async_trait]
 pub trait ParameterServiceExt {
     async fn paginate(&self, args: ParameterArgs) -> Result<Pagination<Parameter>>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Parameter>>;
-    async fn find_default(&self, id: &Uuid) -> Result<Option<Parameter>>;
+    async fn find_default(&self, thread_version_id: &Uuid) -> Result<Option<Parameter>>;
     async fn find_by_thread_version_id(&self, thread_version_id: &Uuid) -> Result<Vec<Parameter>>;
     async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Parameter>>;
     async fn create(&self, inputs: ParameterCreateInput) -> Result<Parameter>;
@@ -35,6 +37,22 @@ pub struct ParameterService {
     db: Database,
 }
 
+impl ParameterService {
+    /// A version has at most one default parameter, so making one the default
+    /// clears the flag on the others.
+    async fn clear_default(&self, thread_version_id: &Uuid, except_id: &Uuid) -> Result<()> {
+        let conn = self.db.get().await?;
+        conn.execute(
+            "UPDATE parameters SET is_default = FALSE \
+             WHERE thread_version_id = $1 AND id <> $2 AND is_default",
+            &[thread_version_id, except_id],
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
 #[async_trait::async_trait]
 impl ParameterServiceExt for ParameterService {
     async fn paginate(&self, args: ParameterArgs) -> Result<Pagination<Parameter>> {
@@ -52,10 +70,13 @@ impl ParameterServiceExt for ParameterService {
             .await
     }
 
-    async fn find_default(&self, id: &Uuid) -> Result<Option<Parameter>> {
+    async fn find_default(&self, thread_version_id: &Uuid) -> Result<Option<Parameter>> {
         self.db
             .bind::<Parameter>()
-            .where_by(and(&[equals("id", id), equals("is_default", &true)]))
+            .where_by(and(&[
+                equals("thread_version_id", thread_version_id),
+                equals("is_default", &true),
+            ]))
             .first()
             .await
     }
@@ -77,8 +98,15 @@ impl ParameterServiceExt for ParameterService {
     }
 
     async fn create(&self, input: ParameterCreateInput) -> Result<Parameter> {
+        ensure_editable(&self.db, &input.thread_version_id).await?;
+
+        let id = Uuid::new_v4();
+        if input.is_default {
+            self.clear_default(&input.thread_version_id, &id).await?;
+        }
+
         let input = Parameter {
-            id: Uuid::new_v4(),
+            id,
             name: input.name,
             stop_sequences: input.stop_sequences,
             model_id: input.model_id,
@@ -90,6 +118,7 @@ impl ParameterServiceExt for ParameterService {
             extra: input.extra,
             thread_version_id: input.thread_version_id,
             is_default: input.is_default,
+            origin_id: None,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
         };
@@ -106,6 +135,7 @@ impl ParameterServiceExt for ParameterService {
             .find_by_thread_version_id(current_thread_version_id)
             .await?;
         for parameter in &mut parameters {
+            parameter.origin_id = Some(parameter.origin_id.unwrap_or(parameter.id));
             parameter.id = Uuid::new_v4();
             parameter.thread_version_id = new_thread_version_id;
             parameter.created_at = Utc::now().naive_utc();
@@ -116,6 +146,12 @@ impl ParameterServiceExt for ParameterService {
     }
 
     async fn update_by_id(&self, id: &Uuid, input: ParameterUpdateInput) -> Result<Parameter> {
+        let parameter = self.find_by_id(id).await?.ok_or(ParameterError::NotFound)?;
+        ensure_editable(&self.db, &parameter.thread_version_id).await?;
+        if input.is_default == Some(true) {
+            self.clear_default(&parameter.thread_version_id, id).await?;
+        }
+
         self.db
             .update(&input)
             .where_by(equals("id", id))
@@ -124,6 +160,9 @@ impl ParameterServiceExt for ParameterService {
     }
 
     async fn delete_by_id(&self, id: &Uuid) -> Result<Parameter> {
+        let parameter = self.find_by_id(id).await?.ok_or(ParameterError::NotFound)?;
+        ensure_editable(&self.db, &parameter.thread_version_id).await?;
+
         self.db.delete().where_by(equals("id", id)).exec().await
     }
 }
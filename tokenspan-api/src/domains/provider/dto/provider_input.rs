@@ -1,11 +1,23 @@
 use async_graphql::InputObject;
 use dojo_macros::UpdateModel;
 
-#[derive(InputObject)]
+use crate::domains::models::{AuthScheme, ProviderKind, ProviderParam};
+
+#[derive(InputObject, Default)]
 pub struct ProviderCreateInput {
     pub name: String,
     pub slug: String,
     pub base_url: String,
+    #[graphql(default)]
+    pub kind: ProviderKind,
+    #[graphql(default)]
+    pub auth_scheme: AuthScheme,
+    pub auth_name: Option<String>,
+    #[graphql(default)]
+    pub headers: Vec<ProviderParam>,
+    #[graphql(default)]
+    pub query_params: Vec<ProviderParam>,
+    pub url_template: Option<String>,
 }
 
 #[derive(InputObject, UpdateModel)]
@@ -13,4 +25,10 @@ pub struct ProviderUpdateInput {
     pub name: Option<String>,
     pub slug: Option<String>,
     pub base_url: Option<String>,
+    pub kind: Option<ProviderKind>,
+    pub auth_scheme: Option<AuthScheme>,
+    pub auth_name: Option<String>,
+    pub headers: Option<Vec<ProviderParam>>,
+    pub query_params: Option<Vec<ProviderParam>>,
+    pub url_template: Option<String>,
 }
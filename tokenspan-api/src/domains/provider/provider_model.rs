@@ -1,7 +1,8 @@
-use async_graphql::SimpleObject;
+use async_graphql::{Enum, InputObject, SimpleObject};
 use chrono::NaiveDateTime;
-use dojo_macros::Model;
-use serde::Deserialize;
+use dojo_macros::{EmbeddedModel, Model, Type};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
 use uuid::Uuid;
 
 #[derive(SimpleObject, Debug, Clone, Model, Deserialize)]
@@ -11,6 +12,72 @@ pub struct Provider {
     pub name: String,
     pub slug: String,
     pub base_url: String,
+    #[serde(default)]
+    pub kind: ProviderKind,
+    #[serde(default)]
+    pub auth_scheme: AuthScheme,
+    /// Header or query parameter name for the `header` and `query` auth schemes.
+    #[serde(default)]
+    pub auth_name: Option<String>,
+    #[serde(default)]
+    pub headers: Vec<ProviderParam>,
+    #[serde(default)]
+    pub query_params: Vec<ProviderParam>,
+    /// Overrides the request URL, e.g. `{base_url}/openai/deployments/{model}/chat/completions`.
+    #[serde(default)]
+    pub url_template: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
+
+/// Selects the adapter used to talk to the provider's API.
+#[derive(
+    Enum, Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Display, EnumString, Deserialize, Type,
+)]
+#[dojo(name = "provider_kind", rename_all = "lowercase")]
+pub enum ProviderKind {
+    #[default]
+    #[strum(serialize = "openai")]
+    #[serde(rename = "openai")]
+    OpenAI,
+    #[strum(serialize = "anthropic")]
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    #[strum(serialize = "gemini")]
+    #[serde(rename = "gemini")]
+    Gemini,
+}
+
+/// How the decrypted API key is attached to outgoing requests. `Native` keeps
+/// whatever the provider kind uses by default.
+#[derive(
+    Enum, Copy, Clone, Debug, Default, Eq, PartialEq, Display, EnumString, Deserialize, Type,
+)]
+#[dojo(name = "provider_auth_scheme", rename_all = "lowercase")]
+pub enum AuthScheme {
+    #[default]
+    #[strum(serialize = "native")]
+    #[serde(rename = "native")]
+    Native,
+    #[strum(serialize = "bearer")]
+    #[serde(rename = "bearer")]
+    Bearer,
+    #[strum(serialize = "header")]
+    #[serde(rename = "header")]
+    Header,
+    #[strum(serialize = "query")]
+    #[serde(rename = "query")]
+    Query,
+    #[strum(serialize = "none")]
+    #[serde(rename = "none")]
+    None,
+}
+
+/// A static header or query parameter. `{api_key}` in the value is replaced with
+/// the decrypted API key at execution time.
+#[derive(SimpleObject, InputObject, Debug, Clone, Serialize, Deserialize, EmbeddedModel)]
+#[graphql(input_name = "ProviderParamInput")]
+pub struct ProviderParam {
+    pub name: String,
+    pub value: String,
+}
@@ -68,6 +68,12 @@ impl ProviderServiceExt for ProviderService {
             name: input.name,
             slug: input.slug,
             base_url: input.base_url,
+            kind: input.kind,
+            auth_scheme: input.auth_scheme,
+            auth_name: input.auth_name,
+            headers: input.headers,
+            query_params: input.query_params,
+            url_template: input.url_template,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
         };
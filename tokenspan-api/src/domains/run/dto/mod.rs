@@ -0,0 +1,5 @@
+mod run_args;
+mod run_input;
+
+pub use run_args::*;
+pub use run_input::*;
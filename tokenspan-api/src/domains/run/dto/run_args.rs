@@ -0,0 +1,36 @@
+use async_graphql::InputObject;
+use dojo_orm::pagination::Cursor;
+use uuid::Uuid;
+
+#[derive(InputObject, Default)]
+pub struct RunWhereOwnerIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct RunWhereThreadVersionIdArgs {
+    pub equals: Option<Uuid>,
+}
+
+#[derive(InputObject, Default)]
+pub struct RunWhereArgs {
+    pub owner_id: Option<RunWhereOwnerIdArgs>,
+    pub thread_version_id: Option<RunWhereThreadVersionIdArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct RunArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub r#where: Option<RunWhereArgs>,
+}
+
+#[derive(InputObject, Default)]
+pub struct RunItemArgs {
+    pub first: Option<i64>,
+    pub last: Option<i64>,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+}
@@ -0,0 +1,27 @@
+use async_graphql::InputObject;
+use uuid::Uuid;
+
+use crate::adapters::RetryPolicy;
+use crate::domains::dto::{FallbackInput, ToolInput};
+
+#[derive(InputObject)]
+pub struct RunCreateInput {
+    pub thread_version_id: Uuid,
+    pub parameter_id: Uuid,
+    pub api_key_id: Uuid,
+    /// Run over every row of this dataset.
+    pub dataset_id: Option<Uuid>,
+    /// Or over these rows, given as JSONL in the same format datasets import.
+    pub rows: Option<String>,
+    /// How many rows are executed at the same time.
+    #[graphql(default = 4)]
+    pub concurrency: i32,
+    #[graphql(default)]
+    pub tools: Vec<ToolInput>,
+    pub max_tool_iterations: Option<i32>,
+    pub retry: Option<RetryPolicy>,
+    #[graphql(default)]
+    pub fallbacks: Vec<FallbackInput>,
+    #[graphql(default)]
+    pub bypass_cache: bool,
+}
@@ -0,0 +1,9 @@
+pub use run_mutation::*;
+pub use run_query::*;
+
+pub mod dto;
+pub mod run_error;
+pub mod run_model;
+mod run_mutation;
+mod run_query;
+pub mod run_service;
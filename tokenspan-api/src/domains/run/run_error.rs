@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RunError {
+    #[error("run not found")]
+    NotFound,
+
+    #[error("a run takes either a dataset or rows, not both")]
+    AmbiguousInput,
+
+    #[error("a run needs a dataset or rows")]
+    MissingInput,
+
+    #[error("a run needs at least one row")]
+    Empty,
+
+    #[error("invalid retry policy: {0}")]
+    InvalidRetry(String),
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,170 @@
+use async_graphql::connection::Connection;
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use chrono::NaiveDateTime;
+use dojo_macros::{Model, Type};
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domains::dto::RunItemArgs;
+use crate::domains::models::{Evaluation, EvaluationSummary, Execution};
+use crate::domains::services::{EvaluationServiceDyn, ExecutionServiceDyn, RunServiceDyn};
+use crate::errors::AppError;
+
+/// One thread version and parameter executed over a list of rows.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "runs", sort_keys = ["created_at", "id"])]
+pub struct Run {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub thread_version_id: Uuid,
+    pub parameter_id: Uuid,
+    pub api_key_id: Uuid,
+    pub dataset_id: Option<Uuid>,
+    pub concurrency: i32,
+    pub status: RunStatus,
+    /// Number of rows; the run is done once `succeeded + failed` reaches it, or
+    /// earlier when cancelled.
+    pub total: i32,
+    pub succeeded: i32,
+    pub failed: i32,
+    pub finished_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl Run {
+    pub async fn items<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: RunItemArgs,
+    ) -> Result<Connection<Cursor, RunItem, AdditionalFields>> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_item = run_service.paginate_items(&self.id, args).await?;
+
+        Ok(paginated_item.into())
+    }
+
+    pub async fn usage<'a>(&self, ctx: &Context<'a>) -> Result<RunUsage> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let usage = run_service.usage(&self.id).await?;
+
+        Ok(usage)
+    }
+
+    pub async fn evaluation<'a>(&self, ctx: &Context<'a>) -> Result<EvaluationSummary> {
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let summary = evaluation_service.summarize_run(&self.id).await?;
+
+        Ok(summary)
+    }
+}
+
+/// Usage and cost summed over the run's executions so far, including the
+/// executions of judge evaluators grading them.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct RunUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub total_tokens: i64,
+    /// Unset when the executions were priced in more than one currency.
+    pub cost: Option<f64>,
+    pub currency: Option<String>,
+}
+
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "run_items", sort_keys = ["index", "id"])]
+pub struct RunItem {
+    pub id: Uuid,
+    pub run_id: Uuid,
+    pub index: i32,
+    pub dataset_row_id: Option<Uuid>,
+    /// A JSON object of string values, keyed by variable name.
+    pub variables: serde_json::Value,
+    pub expected_output: Option<String>,
+    pub status: RunItemStatus,
+    pub execution_id: Option<Uuid>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[ComplexObject]
+impl RunItem {
+    pub async fn execution<'a>(&self, ctx: &Context<'a>) -> Result<Option<Execution>> {
+        let Some(execution_id) = &self.execution_id else {
+            return Ok(None);
+        };
+
+        let execution_service = ctx
+            .data::<ExecutionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let execution = execution_service.find_by_id(execution_id).await?;
+
+        Ok(execution)
+    }
+
+    pub async fn evaluation<'a>(&self, ctx: &Context<'a>) -> Result<Option<Evaluation>> {
+        let Some(execution_id) = &self.execution_id else {
+            return Ok(None);
+        };
+
+        let evaluation_service = ctx
+            .data::<EvaluationServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let evaluation = evaluation_service
+            .find_by_execution_id(execution_id)
+            .await?;
+
+        Ok(evaluation)
+    }
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Deserialize, Type)]
+#[dojo(name = "run_status", rename_all = "lowercase")]
+pub enum RunStatus {
+    #[strum(serialize = "running")]
+    #[serde(rename = "running")]
+    Running,
+    #[strum(serialize = "completed")]
+    #[serde(rename = "completed")]
+    Completed,
+    #[strum(serialize = "cancelled")]
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Deserialize, Type)]
+#[dojo(name = "run_item_status", rename_all = "lowercase")]
+pub enum RunItemStatus {
+    #[strum(serialize = "pending")]
+    #[serde(rename = "pending")]
+    Pending,
+    #[strum(serialize = "running")]
+    #[serde(rename = "running")]
+    Running,
+    #[strum(serialize = "succeeded")]
+    #[serde(rename = "succeeded")]
+    Succeeded,
+    #[strum(serialize = "failed")]
+    #[serde(rename = "failed")]
+    Failed,
+    #[strum(serialize = "cancelled")]
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
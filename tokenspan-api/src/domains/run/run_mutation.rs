@@ -0,0 +1,56 @@
+use async_graphql::{Context, ErrorExtensions, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::models::{ParsedToken, UserRole};
+use crate::domains::run::dto::RunCreateInput;
+use crate::domains::run::run_model::Run;
+use crate::domains::services::RunServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct RunMutation;
+
+#[Object]
+impl RunMutation {
+    /// Starts executing the rows in the background and returns the run right away;
+    /// poll it for progress.
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_run<'a>(&self, ctx: &Context<'a>, input: RunCreateInput) -> Result<Run> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let run = run_service.create(input, parsed_token.user_id).await?;
+
+        Ok(run)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn cancel_run<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Run> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let run = run_service.cancel_by_id(&id).await?;
+
+        Ok(run)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_run<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Run> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let run = run_service.delete_by_id(&id).await?;
+
+        Ok(run)
+    }
+}
@@ -0,0 +1,43 @@
+use async_graphql::connection::Connection;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::models::UserRole;
+use crate::domains::run::dto::RunArgs;
+use crate::domains::run::run_model::Run;
+use crate::domains::services::RunServiceDyn;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+use dojo_orm::pagination::{AdditionalFields, Cursor};
+
+#[derive(Default)]
+pub struct RunQuery;
+
+#[Object]
+impl RunQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn runs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default)] args: RunArgs,
+    ) -> Result<Connection<Cursor, Run, AdditionalFields>> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let paginated_run = run_service.paginate(args).await?;
+
+        Ok(paginated_run.into())
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn run<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<Option<Run>> {
+        let run_service = ctx
+            .data::<RunServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let run = run_service.find_by_id(&id).await?;
+
+        Ok(run)
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::Utc;
+use dojo_macros::UpdateModel;
+use dojo_orm::pagination::Pagination;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use futures::StreamExt;
+use tracing::error;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::dataset::dataset_error::DatasetError;
+use crate::domains::dto::{RunArgs, RunCreateInput, RunItemArgs, ThreadExecuteInput};
+use crate::domains::models::{
+    DatasetFormat, ExecutionStatus, Run, RunItem, RunItemStatus, RunStatus, RunUsage,
+};
+use crate::domains::run::run_error::RunError;
+use crate::domains::services::{
+    parse_rows, to_variables, DatasetServiceDyn, ThreadServiceDyn, MAX_DATASET_ROWS,
+};
+use crate::state::AppState;
+
+/// Upper bound on the rows of one run executed at the same time.
+pub const MAX_RUN_CONCURRENCY: i32 = 16;
+
+#[async_trait::async_trait]
+pub trait RunServiceExt {
+    async fn paginate(&self, args: RunArgs) -> Result<Pagination<Run>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Run>>;
+    async fn create(&self, input: RunCreateInput, owner_id: Uuid) -> Result<Run>;
+    async fn cancel_by_id(&self, id: &Uuid) -> Result<Run>;
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Run>;
+    async fn paginate_items(&self, run_id: &Uuid, args: RunItemArgs)
+        -> Result<Pagination<RunItem>>;
+    async fn find_items(&self, run_id: &Uuid) -> Result<Vec<RunItem>>;
+    async fn usage(&self, run_id: &Uuid) -> Result<RunUsage>;
+}
+
+pub type RunServiceDyn = Arc<dyn RunServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for RunServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.run_service.clone()
+    }
+}
+
+#[derive(UpdateModel)]
+struct RunItemChanges {
+    status: Option<RunItemStatus>,
+    execution_id: Option<Uuid>,
+    error: Option<String>,
+}
+
+#[derive(TypedBuilder)]
+pub struct RunService {
+    db: Database,
+    thread_service: ThreadServiceDyn,
+    dataset_service: DatasetServiceDyn,
+}
+
+impl RunService {
+    fn worker(&self) -> RunWorker {
+        RunWorker {
+            db: self.db.clone(),
+            thread_service: self.thread_service.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RunServiceExt for RunService {
+    async fn paginate(&self, args: RunArgs) -> Result<Pagination<Run>> {
+        let mut predicates = vec![];
+        if let Some(r#where) = &args.r#where {
+            if let Some(owner_id_args) = &r#where.owner_id {
+                if let Some(id) = &owner_id_args.equals {
+                    predicates.push(equals("owner_id", id));
+                }
+            }
+            if let Some(thread_version_id_args) = &r#where.thread_version_id {
+                if let Some(id) = &thread_version_id_args.equals {
+                    predicates.push(equals("thread_version_id", id));
+                }
+            }
+        }
+
+        self.db
+            .bind::<Run>()
+            .where_by(and(&predicates))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Run>> {
+        self.db
+            .bind::<Run>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    /// Stores the run and its items, then executes the items in the background.
+    /// A run that was still going when the server stopped is left `Running`.
+    async fn create(&self, input: RunCreateInput, owner_id: Uuid) -> Result<Run> {
+        if let Some(retry) = &input.retry {
+            retry.validate().map_err(RunError::InvalidRetry)?;
+        }
+
+        let now = Utc::now().naive_utc();
+        let run_id = Uuid::new_v4();
+        let new_item = |index: usize,
+                        dataset_row_id: Option<Uuid>,
+                        variables: serde_json::Value,
+                        expected_output: Option<String>| RunItem {
+            id: Uuid::new_v4(),
+            run_id,
+            index: index as i32,
+            dataset_row_id,
+            variables,
+            expected_output,
+            status: RunItemStatus::Pending,
+            execution_id: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let items = match (&input.dataset_id, &input.rows) {
+            (Some(_), Some(_)) => return Err(RunError::AmbiguousInput.into()),
+            (None, None) => return Err(RunError::MissingInput.into()),
+            (Some(dataset_id), None) => {
+                self.dataset_service
+                    .find_by_id(dataset_id)
+                    .await?
+                    .ok_or(DatasetError::NotFound)?;
+
+                self.dataset_service
+                    .find_rows(dataset_id)
+                    .await?
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, row)| {
+                        new_item(index, Some(row.id), row.variables, row.expected_output)
+                    })
+                    .collect::<Vec<_>>()
+            }
+            (None, Some(rows)) => {
+                let rows = parse_rows(DatasetFormat::Jsonl, rows)?;
+                if rows.len() > MAX_DATASET_ROWS {
+                    return Err(DatasetError::TooManyRows(MAX_DATASET_ROWS).into());
+                }
+
+                rows.into_iter()
+                    .enumerate()
+                    .map(|(index, row)| {
+                        new_item(
+                            index,
+                            None,
+                            serde_json::Value::Object(row.variables),
+                            row.expected_output,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        if items.is_empty() {
+            return Err(RunError::Empty.into());
+        }
+
+        let template = ThreadExecuteInput {
+            thread_version_id: input.thread_version_id,
+            parameter_id: input.parameter_id,
+            api_key_id: input.api_key_id,
+            tools: input.tools,
+            variables: HashMap::new(),
+            max_tool_iterations: input.max_tool_iterations,
+            retry: input.retry,
+            fallbacks: input.fallbacks,
+            bypass_cache: input.bypass_cache,
+            expected_output: None,
+            parent_execution_id: None,
+            traffic_split_id: None,
+            traffic_arm: None,
+            stream: false,
+        };
+        let run = Run {
+            id: run_id,
+            owner_id,
+            thread_version_id: input.thread_version_id,
+            parameter_id: input.parameter_id,
+            api_key_id: input.api_key_id,
+            dataset_id: input.dataset_id,
+            concurrency: input.concurrency.clamp(1, MAX_RUN_CONCURRENCY),
+            status: RunStatus::Running,
+            total: items.len() as i32,
+            succeeded: 0,
+            failed: 0,
+            finished_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let run = self.db.insert(&run).exec().await?;
+        let mut created = vec![];
+        for chunk in items.chunks(1000) {
+            created.extend(self.db.insert_many(chunk).exec().await?);
+        }
+
+        let worker = self.worker();
+        let spawned = run.clone();
+        tokio::spawn(async move { worker.process(spawned, created, template).await });
+
+        Ok(run)
+    }
+
+    /// Stops the run from starting more items. Items already executing finish
+    /// and are still counted.
+    async fn cancel_by_id(&self, id: &Uuid) -> Result<Run> {
+        let conn = self.db.get().await?;
+        conn.execute(
+            "UPDATE runs SET status = 'cancelled', finished_at = $2, updated_at = $2 \
+             WHERE id = $1 AND status = 'running'",
+            &[id, &Utc::now().naive_utc()],
+        )
+        .await?;
+
+        let run = self.find_by_id(id).await?.ok_or(RunError::NotFound)?;
+
+        Ok(run)
+    }
+
+    async fn delete_by_id(&self, id: &Uuid) -> Result<Run> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    async fn paginate_items(
+        &self,
+        run_id: &Uuid,
+        args: RunItemArgs,
+    ) -> Result<Pagination<RunItem>> {
+        self.db
+            .bind::<RunItem>()
+            .where_by(equals("run_id", run_id))
+            .cursor(args.first, args.after, args.last, args.before)
+            .await
+    }
+
+    async fn find_items(&self, run_id: &Uuid) -> Result<Vec<RunItem>> {
+        self.db
+            .bind::<RunItem>()
+            .where_by(equals("run_id", run_id))
+            .order_by(asc("index"))
+            .limit(MAX_DATASET_ROWS as i64)
+            .await
+    }
+
+    async fn usage(&self, run_id: &Uuid) -> Result<RunUsage> {
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_one(
+                "SELECT COALESCE(SUM((e.usage ->> 'input_tokens')::BIGINT), 0)::BIGINT, \
+                 COALESCE(SUM((e.usage ->> 'output_tokens')::BIGINT), 0)::BIGINT, \
+                 COALESCE(SUM((e.usage ->> 'total_tokens')::BIGINT), 0)::BIGINT, \
+                 SUM((e.cost ->> 'total')::DOUBLE PRECISION), \
+                 MIN(e.cost ->> 'currency'), \
+                 COUNT(DISTINCT e.cost ->> 'currency') \
+                 FROM run_items i JOIN executions e \
+                 ON e.id = i.execution_id OR e.parent_execution_id = i.execution_id \
+                 WHERE i.run_id = $1",
+                &[run_id],
+            )
+            .await?;
+        let currencies: i64 = row.get(5);
+        let (cost, currency) = if currencies == 1 {
+            (row.get(3), row.get(4))
+        } else {
+            (None, None)
+        };
+
+        Ok(RunUsage {
+            input_tokens: row.get(0),
+            output_tokens: row.get(1),
+            total_tokens: row.get(2),
+            cost,
+            currency,
+        })
+    }
+}
+
+/// Executes a run's items outside of the request that created it.
+struct RunWorker {
+    db: Database,
+    thread_service: ThreadServiceDyn,
+}
+
+impl RunWorker {
+    async fn process(&self, run: Run, items: Vec<RunItem>, template: ThreadExecuteInput) {
+        futures::stream::iter(items)
+            .map(|item| self.process_item(&run, item, &template))
+            .buffer_unordered(run.concurrency as usize)
+            .collect::<Vec<_>>()
+            .await;
+
+        if let Err(e) = self.finish(&run.id).await {
+            error!("failed to finish run {}: {}", run.id, e);
+        }
+    }
+
+    async fn process_item(&self, run: &Run, item: RunItem, template: &ThreadExecuteInput) {
+        let result = match self.is_cancelled(&run.id).await {
+            Ok(true) => {
+                self.update_item(&item.id, RunItemStatus::Cancelled, None, None)
+                    .await
+            }
+            Ok(false) => self.execute_item(run, &item, template).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!(
+                "failed to process item {} of run {}: {}",
+                item.id, run.id, e
+            );
+        }
+    }
+
+    /// Every item that is not cancelled ends up succeeded or failed and is
+    /// counted, so the run's counts always add up to its total.
+    async fn execute_item(
+        &self,
+        run: &Run,
+        item: &RunItem,
+        template: &ThreadExecuteInput,
+    ) -> Result<()> {
+        let (status, execution_id, error) = match self.run_item(run, item, template).await {
+            Ok(outcome) => outcome,
+            Err(e) => (RunItemStatus::Failed, None, Some(e.to_string())),
+        };
+
+        let saved = self
+            .update_item(&item.id, status, execution_id, error)
+            .await;
+        let status = if saved.is_ok() {
+            status
+        } else {
+            RunItemStatus::Failed
+        };
+        let counted = self.count(&run.id, status).await;
+
+        saved.and(counted)
+    }
+
+    async fn run_item(
+        &self,
+        run: &Run,
+        item: &RunItem,
+        template: &ThreadExecuteInput,
+    ) -> Result<(RunItemStatus, Option<Uuid>, Option<String>)> {
+        let variables = to_variables(&item.variables)
+            .ok_or(anyhow::anyhow!("variables must be a JSON object"))?;
+        let input = ThreadExecuteInput {
+            variables,
+            expected_output: item.expected_output.clone(),
+            ..template.clone()
+        };
+        self.update_item(&item.id, RunItemStatus::Running, None, None)
+            .await?;
+
+        let outcome = match self.thread_service.execute(input, run.owner_id).await {
+            Ok(execution) if execution.status == ExecutionStatus::Success => {
+                (RunItemStatus::Succeeded, Some(execution.id), None)
+            }
+            Ok(execution) => (
+                RunItemStatus::Failed,
+                Some(execution.id),
+                execution.error.map(|error| error.to_string()),
+            ),
+            Err(e) => (RunItemStatus::Failed, None, Some(e.to_string())),
+        };
+
+        Ok(outcome)
+    }
+
+    async fn update_item(
+        &self,
+        id: &Uuid,
+        status: RunItemStatus,
+        execution_id: Option<Uuid>,
+        error: Option<String>,
+    ) -> Result<()> {
+        let input = RunItemChanges {
+            status: Some(status),
+            execution_id,
+            error,
+        };
+        self.db
+            .update::<RunItem, _>(&input)
+            .where_by(equals("id", id))
+            .exec()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn is_cancelled(&self, run_id: &Uuid) -> Result<bool> {
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_one("SELECT status::TEXT FROM runs WHERE id = $1", &[run_id])
+            .await?;
+        let status: String = row.get(0);
+
+        Ok(status == RunStatus::Cancelled.to_string())
+    }
+
+    async fn count(&self, run_id: &Uuid, status: RunItemStatus) -> Result<()> {
+        let column = match status {
+            RunItemStatus::Succeeded => "succeeded",
+            _ => "failed",
+        };
+        let conn = self.db.get().await?;
+        conn.execute(
+            &format!("UPDATE runs SET {column} = {column} + 1, updated_at = $2 WHERE id = $1"),
+            &[run_id, &Utc::now().naive_utc()],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn finish(&self, run_id: &Uuid) -> Result<()> {
+        let conn = self.db.get().await?;
+        conn.execute(
+            "UPDATE runs SET status = 'completed', finished_at = $2, updated_at = $2 \
+             WHERE id = $1 AND status = 'running'",
+            &[run_id, &Utc::now().naive_utc()],
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl From<RunService> for RunServiceDyn {
+    fn from(value: RunService) -> Self {
+        Arc::new(value) as Self
+    }
+}
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 
-use async_graphql::InputObject;
+use async_graphql::{Enum, InputObject};
 use dojo_macros::UpdateModel;
 use serde::Deserialize;
 use strum_macros::{Display, EnumString};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::adapters::RetryPolicy;
+
 #[derive(InputObject)]
 pub struct ThreadCreateInput {
     pub name: String,
@@ -19,26 +21,120 @@ pub struct ThreadUpdateInput {
     pub slug: Option<String>,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq, EnumString, Display)]
+#[derive(Deserialize, Enum, Copy, Clone, Debug, PartialEq, Eq, EnumString, Display)]
 pub enum ToolType {
     #[strum(serialize = "function")]
     #[serde(rename = "function")]
     Function,
 }
 
-#[derive(Deserialize, Validate, Clone, Debug)]
+#[derive(Deserialize, InputObject, Validate, Clone, Debug)]
 pub struct ToolInput {
     #[serde(rename = "type")]
+    #[graphql(name = "type")]
     pub ty: ToolType,
     pub id: Uuid,
 }
 
-#[derive(Deserialize, Validate, Clone, Debug)]
+#[derive(Deserialize, Enum, Copy, Clone, Debug, PartialEq, Eq, EnumString, Display)]
+pub enum FallbackType {
+    #[strum(serialize = "parameter")]
+    #[serde(rename = "parameter")]
+    Parameter,
+    /// Reuses the execution's parameter with another model.
+    #[strum(serialize = "model")]
+    #[serde(rename = "model")]
+    Model,
+}
+
+#[derive(Deserialize, InputObject, Validate, Clone, Debug)]
+pub struct FallbackInput {
+    #[serde(rename = "type")]
+    #[graphql(name = "type")]
+    pub ty: FallbackType,
+    pub id: Uuid,
+}
+
+#[derive(Deserialize, InputObject, Validate, Clone, Debug)]
 pub struct ThreadExecuteInput {
     pub thread_version_id: Uuid,
     pub parameter_id: Uuid,
     pub api_key_id: Uuid,
     #[serde(default)]
+    #[graphql(default)]
+    pub tools: Vec<ToolInput>,
+    pub variables: HashMap<String, String>,
+    /// Answer the model's tool calls and send the results back until it replies
+    /// without any, for at most this many round trips. Off when unset; streamed
+    /// executions reject it.
+    #[serde(default)]
+    pub max_tool_iterations: Option<i32>,
+    /// Overrides the server's retry policy for this execution. Streamed
+    /// executions are not retried and reject it.
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Parameters or models tried in order once the primary one has used up its
+    /// attempts. Streamed executions reject them like `retry`.
+    #[serde(default)]
+    #[graphql(default)]
+    pub fallbacks: Vec<FallbackInput>,
+    /// Skip the response cache lookup. The fresh response still replaces the
+    /// cached one. Streamed executions never use the cache.
+    #[serde(default)]
+    #[graphql(default)]
+    pub bypass_cache: bool,
+    /// What the thread version's evaluators compare the output with when they
+    /// have no value of their own.
+    #[serde(default)]
+    pub expected_output: Option<String>,
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub parent_execution_id: Option<Uuid>,
+    /// Set when an environment's traffic split picked the version.
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub traffic_split_id: Option<Uuid>,
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub traffic_arm: Option<String>,
+    #[serde(default)]
+    #[graphql(skip)]
+    pub stream: bool,
+}
+
+/// Executes a thread by slug. The version's default parameter and the caller's
+/// default key for the model's provider are used.
+#[derive(Deserialize, Validate, Clone, Debug)]
+pub struct ThreadRunInput {
+    /// The semver of a published version, or a range like `^1.2` for the
+    /// highest one in it. At most one of `semver`, `label` and `environment`
+    /// may be given; without any the latest published version runs.
+    #[serde(default)]
+    pub semver: Option<String>,
+    /// A label set on a published version, or `latest`.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// An environment of the thread; runs the version promoted to it, or the
+    /// one its active traffic split picks.
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// Keeps a caller, such as an end user, on the same arm of a traffic split.
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
     pub tools: Vec<ToolInput>,
+    #[serde(default)]
     pub variables: HashMap<String, String>,
+    #[serde(default)]
+    pub max_tool_iterations: Option<i32>,
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    #[serde(default)]
+    pub fallbacks: Vec<FallbackInput>,
+    #[serde(default)]
+    pub bypass_cache: bool,
+    #[serde(default)]
+    pub expected_output: Option<String>,
+    #[serde(default)]
+    pub stream: bool,
 }
@@ -1,6 +1,11 @@
+use std::convert::Infallible;
+
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::Sse;
 use axum::Json;
+use futures::{Stream, StreamExt};
 
-use crate::domains::models::{Execution, ParsedToken};
+use crate::domains::models::{Execution, ExecutionEvent, ParsedToken};
 use crate::domains::services::ThreadServiceDyn;
 use crate::domains::thread::dto::ThreadExecuteInput;
 use crate::domains::thread::thread_error::ThreadError;
@@ -16,7 +21,37 @@ pub async fn execute_thread_v1(
     let execution = thread_service
         .execute(input, parsed_token.user_id)
         .await
-        .map_err(|e| ThreadError::Unknown(anyhow::anyhow!(e)))?;
+        .map_err(ThreadError::from_execution)?;
 
     Ok(Json(execution))
 }
+
+pub async fn execute_thread_stream_v1(
+    thread_service: ThreadServiceDyn,
+    input: ThreadExecuteInput,
+    token: Option<ParsedToken>,
+) -> anyhow::Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ThreadError> {
+    let parsed_token = token.ok_or(ThreadError::Unknown(
+        anyhow::anyhow!("no token".to_string()),
+    ))?;
+    let events = thread_service
+        .execute_stream(input, parsed_token.user_id)
+        .await
+        .map_err(ThreadError::from_execution)?;
+
+    let events = events.map(|event| {
+        let event = match event {
+            ExecutionEvent::Delta(delta) => Event::default().event("delta").json_data(delta),
+            ExecutionEvent::ToolCallDelta(delta) => {
+                Event::default().event("tool_call_delta").json_data(delta)
+            }
+            ExecutionEvent::Completed(execution) => {
+                Event::default().event("completed").json_data(execution)
+            }
+        };
+
+        Ok(event.unwrap_or_else(|e| Event::default().event("error").data(e.to_string())))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
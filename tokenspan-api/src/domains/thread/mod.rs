@@ -5,7 +5,7 @@ pub use thread_subscription::ThreadSubscription;
 
 pub mod dto;
 mod handler;
-mod thread_error;
+pub mod thread_error;
 mod thread_handler;
 pub mod thread_loader;
 pub mod thread_model;
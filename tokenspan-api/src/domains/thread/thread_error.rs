@@ -4,15 +4,54 @@ use axum::Json;
 use serde_json::json;
 use thiserror::Error;
 
+use crate::domains::errors::{BudgetError, VariableViolation};
+use crate::prompts::template::TemplateError;
+
 #[derive(Debug, Error)]
 pub enum ThreadError {
+    #[error(transparent)]
+    BudgetExceeded(BudgetError),
+
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("invalid variables: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    InvalidVariables(Vec<VariableViolation>),
+
+    #[error("message {index}: {source}")]
+    Template { index: i32, source: TemplateError },
+
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
 
+impl ThreadError {
+    /// Keeps budget, variable and template errors distinct so callers can tell
+    /// a blocked or malformed execution from a failed one.
+    pub fn from_execution(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<ThreadError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        match e.downcast::<BudgetError>() {
+            Ok(e @ BudgetError::Exceeded { .. }) => ThreadError::BudgetExceeded(e),
+            Ok(e) => ThreadError::Unknown(e.into()),
+            Err(e) => ThreadError::Unknown(e),
+        }
+    }
+}
+
 impl IntoResponse for ThreadError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
+            ThreadError::BudgetExceeded(e) => (StatusCode::TOO_MANY_REQUESTS, e.to_string()),
+            e @ ThreadError::NotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+            e @ (ThreadError::InvalidInput(_)
+            | ThreadError::InvalidVariables(_)
+            | ThreadError::Template { .. }) => (StatusCode::BAD_REQUEST, e.to_string()),
             ThreadError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
         };
 
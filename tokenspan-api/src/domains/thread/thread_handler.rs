@@ -1,12 +1,16 @@
-use axum::extract::State;
+use std::collections::HashMap;
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
 use axum::routing::post;
-use axum::{Extension, Json, Router};
+use axum::{Extension, Router};
 use tracing::info;
 
-use crate::domains::models::{Execution, ParsedToken};
+use crate::domains::models::ParsedToken;
 use crate::domains::services::ThreadServiceDyn;
-use crate::domains::thread::dto::ThreadExecuteInput;
-use crate::domains::thread::handler::execute_thread_v1;
+use crate::domains::thread::dto::{ThreadExecuteInput, ThreadRunInput};
+use crate::domains::thread::handler::{execute_thread_stream_v1, execute_thread_v1};
 use crate::domains::thread::thread_error::ThreadError;
 use crate::extractors::valid_json::ValidJson;
 use crate::extractors::versioning::Version;
@@ -16,18 +20,68 @@ async fn execute_thread(
     version: Version,
     State(thread_service): State<ThreadServiceDyn>,
     Extension(token): Extension<Option<ParsedToken>>,
+    headers: HeaderMap,
     ValidJson(input): ValidJson<ThreadExecuteInput>,
-) -> anyhow::Result<Json<Execution>, ThreadError> {
+) -> anyhow::Result<Response, ThreadError> {
     info!("{:?}", input);
-    match version {
-        Version::V1 => execute_thread_v1(thread_service, input, token).await,
-    }
+
+    respond(version, thread_service, token, &headers, input).await
+}
+
+async fn run_thread(
+    version: Version,
+    State(thread_service): State<ThreadServiceDyn>,
+    Extension(token): Extension<Option<ParsedToken>>,
+    Path(params): Path<HashMap<String, String>>,
+    headers: HeaderMap,
+    ValidJson(input): ValidJson<ThreadRunInput>,
+) -> anyhow::Result<Response, ThreadError> {
+    info!("{:?}", input);
+    let slug = params
+        .get("slug")
+        .cloned()
+        .ok_or(ThreadError::NotFound("thread".to_string()))?;
+    let parsed_token = token.as_ref().ok_or(ThreadError::Unknown(anyhow::anyhow!(
+        "no token".to_string()
+    )))?;
+    let input = thread_service
+        .resolve_run(slug, input, parsed_token.user_id)
+        .await
+        .map_err(ThreadError::from_execution)?;
+
+    respond(version, thread_service, token, &headers, input).await
+}
+
+async fn respond(
+    version: Version,
+    thread_service: ThreadServiceDyn,
+    token: Option<ParsedToken>,
+    headers: &HeaderMap,
+    mut input: ThreadExecuteInput,
+) -> anyhow::Result<Response, ThreadError> {
+    input.stream |= headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+
+    let response = match version {
+        Version::V1 if input.stream => execute_thread_stream_v1(thread_service, input, token)
+            .await?
+            .into_response(),
+        Version::V1 => execute_thread_v1(thread_service, input, token)
+            .await?
+            .into_response(),
+    };
+
+    Ok(response)
 }
 
 pub struct ThreadRouter;
 
 impl ThreadRouter {
     pub fn new() -> Router<AppState> {
-        Router::new().route("/execute", post(execute_thread))
+        Router::new()
+            .route("/execute", post(execute_thread))
+            .route("/:slug/run", post(run_thread))
     }
 }
@@ -1,41 +1,50 @@
-use std::str::FromStr;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use async_openai::config::OpenAIConfig;
-use async_openai::types::{
-    ChatCompletionRequestMessage, ChatCompletionToolArgs, ChatCompletionToolType,
-    CreateChatCompletionRequestArgs, CreateChatCompletionResponse, FunctionObjectArgs,
-};
-use async_openai::Client;
 use axum::extract::FromRef;
 use chrono::Utc;
 use dojo_orm::pagination::Pagination;
 use dojo_orm::prelude::equals;
 use dojo_orm::prelude::*;
 use dojo_orm::Database;
-use regex::Regex;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use serde_json::json;
-use tracing::info;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, info, warn};
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
 
+use crate::adapters::{
+    ChatRequest, ChatResponse, ChatStream, ChatStreamEvent, ProviderAdapterRegistry, ProviderError,
+    RetryPolicy, ToolHandlerRegistry, RETRY_WINDOW,
+};
 use crate::domains::api_key::api_key_error::ApiKeyError;
+use crate::domains::caches::execution_cache::ResponseCacheDyn;
 use crate::domains::dto::{
-    ExecutionCreateInput, ParameterCreateInput, ThreadExecuteInput, ThreadVersionCreateInput,
-    ToolType,
+    ExecutionCreateInput, FallbackType, ParameterCreateInput, ThreadExecuteInput, ThreadRunInput,
+    ThreadVersionCreateInput, ToolType,
 };
 use crate::domains::models::{
-    Elapsed, Execution, ExecutionStatus, Function, Model, Parameter, Thread, Usage,
+    ApiKey, AssertionResult, BudgetScope, Cost, Elapsed, Evaluation, Evaluator, EvaluatorKind,
+    Execution, ExecutionAttempt, ExecutionDelta, ExecutionEvent, ExecutionStatus,
+    ExecutionToolCallDelta, Function, Message, Model, Parameter, Provider, Thread, ThreadVersion,
+    ThreadVersionStatus, Usage,
 };
 use crate::domains::services::{
-    ApiKeyServiceDyn, ExecutionServiceDyn, FunctionServiceDyn, MessageServiceDyn, ModelServiceDyn,
-    ParameterServiceDyn, ProviderServiceDyn, ThreadVersionServiceDyn,
+    assert_judgement, execution_input, execution_output, failed_assertion, resolve_variables,
+    ApiKeyServiceDyn, BudgetServiceDyn, EnvironmentServiceDyn, EvaluationServiceDyn,
+    ExecutionServiceDyn, FunctionServiceDyn, MessageServiceDyn, ModelServiceDyn,
+    ParameterServiceDyn, ProviderServiceDyn, SemverSelector, ThreadVersionServiceDyn,
+    TrafficSplitServiceDyn, LATEST_LABEL,
 };
 use crate::domains::thread::dto::{ThreadArgs, ThreadCreateInput, ThreadUpdateInput};
 use crate::domains::thread::thread_error::ThreadError;
-use crate::prompts::{ChatMessage, PromptRole};
+use crate::prompts::template;
+use crate::prompts::{ChatMessage, ToolCall};
 use crate::state::AppState;
 
 #[async_trait::async_trait]
@@ -50,8 +59,26 @@ pub trait ThreadServiceExt {
     async fn update_by_id(&self, id: &Uuid, input: ThreadUpdateInput) -> Result<Thread>;
     async fn delete_by_id(&self, id: &Uuid) -> Result<Thread>;
     async fn execute(&self, input: ThreadExecuteInput, execute_by_id: Uuid) -> Result<Execution>;
+    async fn execute_stream(
+        &self,
+        input: ThreadExecuteInput,
+        execute_by_id: Uuid,
+    ) -> Result<ExecutionEventStream>;
+    async fn evaluate(
+        &self,
+        execution: &Execution,
+        expected_output: Option<String>,
+    ) -> Result<Option<Evaluation>>;
+    async fn resolve_run(
+        &self,
+        slug: String,
+        input: ThreadRunInput,
+        owner_id: Uuid,
+    ) -> Result<ThreadExecuteInput>;
 }
 
+pub type ExecutionEventStream = BoxStream<'static, ExecutionEvent>;
+
 pub type ThreadServiceDyn = Arc<dyn ThreadServiceExt + Send + Sync>;
 
 impl FromRef<AppState> for ThreadServiceDyn {
@@ -60,7 +87,7 @@ impl FromRef<AppState> for ThreadServiceDyn {
     }
 }
 
-#[derive(TypedBuilder)]
+#[derive(TypedBuilder, Clone)]
 pub struct ThreadService {
     db: Database,
     api_key_service: ApiKeyServiceDyn,
@@ -71,71 +98,443 @@ pub struct ThreadService {
     thread_version_service: ThreadVersionServiceDyn,
     message_service: MessageServiceDyn,
     function_service: FunctionServiceDyn,
+    provider_adapters: ProviderAdapterRegistry,
+    tool_handlers: ToolHandlerRegistry,
+    budget_service: BudgetServiceDyn,
+    evaluation_service: EvaluationServiceDyn,
+    environment_service: EnvironmentServiceDyn,
+    traffic_split_service: TrafficSplitServiceDyn,
+    retry_policy: RetryPolicy,
+    response_cache: Option<ResponseCacheDyn>,
 }
 
 impl ThreadService {
-    pub async fn chat_completion(
+    async fn prepare(&self, input: &ThreadExecuteInput) -> Result<PreparedExecution> {
+        let start = Instant::now();
+        let api_key = self
+            .api_key_service
+            .find_by_id(&input.api_key_id)
+            .await?
+            .ok_or(ApiKeyError::Unknown(anyhow::anyhow!("API key not found")))?;
+        let decrypted_key = self.api_key_service.decrypt(&api_key.key)?;
+        let api_key_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let thread_version = self
+            .thread_version_service
+            .find_by_id(&input.thread_version_id)
+            .await?
+            .ok_or(ThreadError::Unknown(anyhow::anyhow!(
+                "Thread version not found"
+            )))?;
+        let thread_version_elapsed = start.elapsed();
+        let variables = resolve_variables(&thread_version.variables, &input.variables)
+            .map_err(ThreadError::InvalidVariables)?;
+
+        let start = Instant::now();
+        let input_messages = self
+            .message_service
+            .find_by_thread_version_id(&input.thread_version_id)
+            .await?;
+
+        let chat_messages: Vec<ChatMessage> = input_messages
+            .clone()
+            .into_iter()
+            .map(|message| {
+                let content = template::render(&message.content, &variables).map_err(|source| {
+                    ThreadError::Template {
+                        index: message.index,
+                        source,
+                    }
+                })?;
+
+                Ok(ChatMessage {
+                    content,
+                    ..ChatMessage::try_from(message)?
+                })
+            })
+            .collect::<Result<Vec<ChatMessage>>>()?;
+        let messages_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let parameter = self
+            .parameter_service
+            .find_by_id(&input.parameter_id)
+            .await?
+            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Parameter not found")))?;
+        let parameter_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let model = self
+            .model_service
+            .find_by_id(&parameter.model_id)
+            .await?
+            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Model not found")))?;
+        let model_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let provider = self
+            .provider_service
+            .find_by_id(&model.provider_id)
+            .await?
+            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Provider not found")))?;
+        let provider_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let function_ids = input
+            .tools
+            .iter()
+            .filter_map(|tool| {
+                if tool.ty == ToolType::Function {
+                    Some(tool.id)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let functions = self.function_service.find_by_ids(&function_ids).await?;
+        let function_elapsed = start.elapsed();
+
+        let mut targets = vec![ExecutionTarget {
+            parameter: parameter.clone(),
+            model: model.clone(),
+            provider,
+            api_key_id: api_key.id,
+            decrypted_key: decrypted_key.clone(),
+        }];
+        for fallback in &input.fallbacks {
+            let parameter = match fallback.ty {
+                FallbackType::Parameter => self
+                    .parameter_service
+                    .find_by_id(&fallback.id)
+                    .await?
+                    .ok_or(ThreadError::Unknown(anyhow::anyhow!(
+                    "Fallback parameter not found"
+                )))?,
+                FallbackType::Model => Parameter {
+                    model_id: fallback.id,
+                    ..parameter.clone()
+                },
+            };
+            targets.push(
+                self.resolve_target(parameter, &api_key, &decrypted_key)
+                    .await?,
+            );
+        }
+
+        Ok(PreparedExecution {
+            thread_version,
+            input_messages,
+            variables,
+            request: ChatRequest {
+                model,
+                messages: chat_messages,
+                parameter,
+                functions,
+            },
+            targets,
+            target: 0,
+            elapsed: Elapsed {
+                api_key: api_key_elapsed.as_secs_f64(),
+                thread_version: thread_version_elapsed.as_secs_f64(),
+                messages: messages_elapsed.as_secs_f64(),
+                parameter: parameter_elapsed.as_secs_f64(),
+                model: model_elapsed.as_secs_f64(),
+                provider: provider_elapsed.as_secs_f64(),
+                function: function_elapsed.as_secs_f64(),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Resolves the model, provider and API key a fallback parameter runs against.
+    /// The execution's key is reused when it belongs to the model's provider,
+    /// otherwise the key owner's key for that provider is used.
+    async fn resolve_target(
         &self,
-        base_url: &String,
-        chat_messages: &[ChatMessage],
-        api_key: &String,
         parameter: Parameter,
-        model: Model,
+        api_key: &ApiKey,
+        decrypted_key: &str,
+    ) -> Result<ExecutionTarget> {
+        let model = self
+            .model_service
+            .find_by_id(&parameter.model_id)
+            .await?
+            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Model not found")))?;
+        let provider = self
+            .provider_service
+            .find_by_id(&model.provider_id)
+            .await?
+            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Provider not found")))?;
+
+        let (api_key_id, decrypted_key) = if provider.id == api_key.provider_id {
+            (api_key.id, decrypted_key.to_string())
+        } else {
+            let api_key = self
+                .api_key_service
+                .find_by_provider(&api_key.owner_id, &provider.id)
+                .await?
+                .ok_or(ApiKeyError::Unknown(anyhow::anyhow!(
+                    "no API key for provider {}",
+                    provider.slug
+                )))?;
+            (api_key.id, self.api_key_service.decrypt(&api_key.key)?)
+        };
+
+        Ok(ExecutionTarget {
+            parameter,
+            model,
+            provider,
+            api_key_id,
+            decrypted_key,
+        })
+    }
+
+    /// Sends `request` to the targets starting at `first`, retrying each one as
+    /// the policy allows before falling back to the next. No wait may run past
+    /// `deadline`. Every call is recorded in `attempts`; returns the index of the
+    /// target that answered, or of the last one tried.
+    async fn complete(
+        &self,
+        prepared: &PreparedExecution,
+        first: usize,
+        request: &ChatRequest,
+        policy: &RetryPolicy,
+        deadline: Instant,
+        attempts: &mut Vec<ExecutionAttempt>,
+    ) -> (usize, Result<ChatResponse>) {
+        let mut last = (first, Err(anyhow::anyhow!("no model to execute")));
+        for (index, target) in prepared.targets.iter().enumerate().skip(first) {
+            let adapter = match self.provider_adapters.get(target.provider.kind) {
+                Ok(adapter) => adapter,
+                Err(e) => {
+                    last = (index, Err(e));
+                    continue;
+                }
+            };
+            let request = prepared.target_request(index, request);
+
+            for attempt in 1..=policy.max_attempts() {
+                let start = Instant::now();
+                let result = adapter
+                    .chat_completion(&target.provider, &target.decrypted_key, &request)
+                    .await;
+                attempts.push(target.attempt(attempt, start.elapsed(), result.as_ref().err()));
+
+                let e = match result {
+                    Ok(response) => return (index, Ok(response)),
+                    Err(e) => e,
+                };
+                let backoff = policy
+                    .backoff(attempt, &e)
+                    .filter(|backoff| Instant::now() + *backoff < deadline);
+                warn!(
+                    "attempt {} on model {} failed: {}",
+                    attempt, target.model.slug, e
+                );
+                last = (index, Err(e));
+                match backoff {
+                    Some(backoff) => tokio::time::sleep(backoff).await,
+                    None => break,
+                }
+            }
+        }
+
+        last
+    }
+
+    async fn cached_response(&self, request: &ChatRequest, bypass: bool) -> Option<ChatResponse> {
+        match &self.response_cache {
+            Some(cache) if !bypass => cache.get(request.cache_key()).await,
+            _ => None,
+        }
+    }
+
+    async fn cache_response(&self, request: &ChatRequest, response: &ChatResponse) {
+        if let Some(cache) = &self.response_cache {
+            cache.set(request.cache_key(), response.clone()).await;
+        }
+    }
+
+    /// Checks the budgets of the user, the thread and every key a target may be
+    /// charged to; a fallback can run on another key of its own provider.
+    async fn check_budgets(&self, prepared: &PreparedExecution, execute_by_id: Uuid) -> Result<()> {
+        let mut scopes = vec![
+            (BudgetScope::User, execute_by_id),
+            (BudgetScope::Thread, prepared.thread_version.thread_id),
+        ];
+        for target in &prepared.targets {
+            let scope = (BudgetScope::ApiKey, target.api_key_id);
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+
+        self.budget_service.check(&scopes).await
+    }
+
+    /// Answers each tool call with the handler configured for its function, falling
+    /// back to the function's stored `response` as a mock.
+    async fn call_tools(
+        &self,
         functions: &[Function],
-    ) -> Result<(
-        CreateChatCompletionResponse,
-        Vec<ChatCompletionRequestMessage>,
-    )> {
-        let mut messages: Vec<ChatCompletionRequestMessage> = vec![];
-        for message in chat_messages.iter().cloned() {
-            messages.push(message.try_into()?);
+        tool_calls: &[ToolCall],
+    ) -> Result<Vec<ChatMessage>> {
+        let mut messages = vec![];
+        for tool_call in tool_calls {
+            let function = functions
+                .iter()
+                .find(|function| function.name == tool_call.name)
+                .ok_or(ThreadError::Unknown(anyhow::anyhow!(
+                    "model called unknown function {}",
+                    tool_call.name
+                )))?;
+
+            let result = match self.tool_handlers.get(&function.name) {
+                Some(handler) => {
+                    let arguments = serde_json::from_str(&tool_call.arguments)
+                        .unwrap_or(serde_json::Value::String(tool_call.arguments.clone()));
+                    handler.call(function, arguments).await?
+                }
+                None => function
+                    .response
+                    .clone()
+                    .ok_or(ThreadError::Unknown(anyhow::anyhow!(
+                        "no tool handler or mock response for function {}",
+                        function.name
+                    )))?,
+            };
+            let content = match result {
+                serde_json::Value::String(result) => result,
+                result => result.to_string(),
+            };
+            messages.push(ChatMessage::tool(tool_call.id.clone(), content));
         }
 
-        let mut request = CreateChatCompletionRequestArgs::default()
-            .model(model.name.clone())
-            .max_tokens(parameter.max_tokens as u16)
-            .temperature(parameter.temperature)
-            .top_p(parameter.top_p)
-            .frequency_penalty(parameter.frequency_penalty)
-            .presence_penalty(parameter.presence_penalty)
-            .stop(parameter.stop_sequences)
-            .messages(messages.clone())
-            .build()
-            .map_err(|e| anyhow::anyhow!(e))?;
-
-        let mut tools = vec![];
-        if !functions.is_empty() {
-            for function in functions.iter().cloned() {
-                let tool_args = ChatCompletionToolArgs::default()
-                    .r#type(ChatCompletionToolType::Function)
-                    .function(
-                        FunctionObjectArgs::default()
-                            .name(function.name)
-                            .description(function.description)
-                            .parameters(function.parameters)
-                            .build()
-                            .map_err(|e| anyhow::anyhow!(e.to_string()))?,
-                    )
-                    .build()
-                    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
-
-                tools.push(tool_args);
+        Ok(messages)
+    }
+
+    /// Evaluation after an execution is best effort: a failure is logged and
+    /// never fails the execution. Judge executions are not evaluated themselves.
+    async fn evaluate_quietly(&self, execution: &Execution, expected_output: Option<String>) {
+        if execution.parent_execution_id.is_some() {
+            return;
+        }
+        if let Err(e) = self.evaluate(execution, expected_output).await {
+            error!("failed to evaluate execution {}: {:?}", execution.id, e);
+        }
+    }
+
+    /// Runs the judge's rubric thread on the execution, charged to the same user.
+    async fn judge(
+        &self,
+        evaluator: &Evaluator,
+        execution: &Execution,
+        expected_output: Option<&str>,
+    ) -> AssertionResult {
+        let (Some(parameter_id), Some(api_key_id)) =
+            (evaluator.judge_parameter_id, evaluator.judge_api_key_id)
+        else {
+            return failed_assertion(evaluator, "no judge parameter or API key".to_string());
+        };
+        let parameter = match self.parameter_service.find_by_id(&parameter_id).await {
+            Ok(Some(parameter)) => parameter,
+            Ok(None) => {
+                return failed_assertion(evaluator, "judge parameter not found".to_string())
             }
+            Err(e) => return failed_assertion(evaluator, e.to_string()),
+        };
 
-            request.tools = Some(tools);
+        let variables = HashMap::from([
+            ("input".to_string(), execution_input(execution)),
+            (
+                "output".to_string(),
+                execution_output(execution).to_string(),
+            ),
+            (
+                "expected_output".to_string(),
+                expected_output.unwrap_or_default().to_string(),
+            ),
+        ]);
+        let input = ThreadExecuteInput {
+            thread_version_id: parameter.thread_version_id,
+            parameter_id,
+            api_key_id,
+            tools: vec![],
+            variables,
+            max_tool_iterations: None,
+            retry: None,
+            fallbacks: vec![],
+            bypass_cache: false,
+            expected_output: None,
+            parent_execution_id: Some(execution.id),
+            traffic_split_id: None,
+            traffic_arm: None,
+            stream: false,
+        };
+        match self.execute(input, execution.executed_by_id).await {
+            Ok(judge_execution) => assert_judgement(evaluator, &judge_execution),
+            Err(e) => failed_assertion(evaluator, format!("judge execution failed: {}", e)),
         }
+    }
 
-        let config = OpenAIConfig::new()
-            .with_api_key(api_key)
-            .with_api_base(base_url);
-        let client = Client::with_config(config);
-        let response = client
-            .chat()
-            .create(request)
-            .await
-            .map_err(|e| anyhow::anyhow!(e))?;
+    async fn save_execution(
+        &self,
+        input: ThreadExecuteInput,
+        prepared: PreparedExecution,
+        execute_by_id: Uuid,
+        outcome: CompletionOutcome,
+    ) -> Result<Execution> {
+        let start = Instant::now();
+        let cache_hit = outcome.result.is_ok() && outcome.attempts.is_empty();
+        let (status, response, error) = match outcome.result {
+            Err(e) => (ExecutionStatus::Failed, None, Some(json!(e.to_string()))),
+            Ok(response) => (ExecutionStatus::Success, Some(response.raw), None),
+        };
+        info!(?response);
 
-        Ok((response, messages))
+        let output_messages = prepared.output_messages(outcome.output_messages, execute_by_id);
+        let post_elapsed = start.elapsed();
+
+        let cost = if cache_hit {
+            Some(Cost::zero(
+                prepared.target().model.input_pricing.currency.clone(),
+            ))
+        } else {
+            prepared.cost(outcome.usage.as_ref())
+        };
+        let elapsed = Elapsed {
+            api_call: outcome.api_call_elapsed.as_secs_f64(),
+            post: post_elapsed.as_secs_f64(),
+            ..prepared.elapsed
+        };
+        let target = prepared.target();
+
+        self.execution_service
+            .create(
+                ExecutionCreateInput {
+                    thread_id: prepared.thread_version.thread_id,
+                    thread_version_id: input.thread_version_id,
+                    api_key_id: target.api_key_id,
+                    parent_execution_id: input.parent_execution_id,
+                    traffic_split_id: input.traffic_split_id,
+                    traffic_arm: input.traffic_arm,
+                    parameter: target.parameter.clone(),
+                    input_messages: prepared.input_messages,
+                    variables: prepared.variables,
+                    output_messages,
+                    elapsed,
+                    status,
+                    response,
+                    error,
+                    cost,
+                    attempts: outcome.attempts,
+                    cache_hit,
+                    usage: outcome.usage,
+                },
+                execute_by_id,
+            )
+            .await
     }
 }
 
@@ -227,6 +626,7 @@ impl ThreadServiceExt for ThreadService {
                 ParameterCreateInput::builder()
                     .model_id(model.id)
                     .thread_version_id(created_thread_version.id)
+                    .is_default(true)
                     .build(),
             )
             .await?;
@@ -247,164 +647,591 @@ impl ThreadServiceExt for ThreadService {
     }
 
     async fn execute(&self, input: ThreadExecuteInput, execute_by_id: Uuid) -> Result<Execution> {
-        let start = Instant::now();
-        let api_key = self
-            .api_key_service
-            .find_by_id(&input.api_key_id)
-            .await?
-            .ok_or(ApiKeyError::Unknown(anyhow::anyhow!("API key not found")))?;
-        let decrypted_key = self.api_key_service.decrypt(&api_key.key)?;
-        let api_key_elapsed = start.elapsed();
+        let mut prepared = self.prepare(&input).await?;
+        self.check_budgets(&prepared, execute_by_id).await?;
+        let max_tool_iterations = input.max_tool_iterations.unwrap_or(0).max(0) as usize;
+        let policy = input.retry.clone().unwrap_or(self.retry_policy.clone());
+        policy.validate().map_err(ThreadError::InvalidInput)?;
+        let deadline = Instant::now() + RETRY_WINDOW;
 
-        let start = Instant::now();
-        let thread_version = self
-            .thread_version_service
-            .find_by_id(&input.thread_version_id)
-            .await?
-            .ok_or(ThreadError::Unknown(anyhow::anyhow!(
-                "Thread version not found"
-            )))?;
-        let thread_version_elapsed = start.elapsed();
+        let mut request = prepared.request.clone();
+        let mut output_messages = vec![];
+        let mut usage: Option<Usage> = None;
+        let mut attempts = vec![];
+        let mut api_call_elapsed = Duration::ZERO;
+        let mut iteration = 0;
+        let result = loop {
+            // Cached responses are free, so only provider calls add to the usage.
+            // They are keyed on the model that answered, not the one asked for.
+            let target_request = prepared.target_request(prepared.target, &request);
+            let response = match self
+                .cached_response(&target_request, input.bypass_cache)
+                .await
+            {
+                Some(response) => response,
+                None => {
+                    let start = Instant::now();
+                    let (target, response) = self
+                        .complete(
+                            &prepared,
+                            prepared.target,
+                            &request,
+                            &policy,
+                            deadline,
+                            &mut attempts,
+                        )
+                        .await;
+                    api_call_elapsed += start.elapsed();
+                    // Later tool round trips stay on the model that answered.
+                    prepared.target = target;
 
-        let start = Instant::now();
-        let input_messages = self
-            .message_service
-            .find_by_thread_version_id(&input.thread_version_id)
+                    let response = match response {
+                        Ok(response) => response,
+                        Err(e) => break Err(e),
+                    };
+                    self.cache_response(&prepared.target_request(target, &request), &response)
+                        .await;
+                    if let Some(response_usage) = &response.usage {
+                        usage = Some(match usage {
+                            Some(usage) => usage + response_usage.clone(),
+                            None => response_usage.clone(),
+                        });
+                    }
+                    response
+                }
+            };
+            if response.tool_calls.is_empty() {
+                output_messages.extend(response.messages.clone());
+                break Ok(response);
+            }
+
+            let content = response
+                .messages
+                .iter()
+                .map(|message| message.content.as_str())
+                .collect::<String>();
+            let mut messages = vec![ChatMessage {
+                tool_calls: response.tool_calls.clone(),
+                ..ChatMessage::assistant(content)
+            }];
+            // Without a tool loop the calls are the answer, handed back to the caller.
+            if input.max_tool_iterations.is_none() {
+                output_messages.extend(messages);
+                break Ok(response);
+            }
+            if iteration == max_tool_iterations {
+                output_messages.extend(messages);
+                break Err(anyhow::anyhow!(
+                    "model still requested tool calls after {} iterations",
+                    max_tool_iterations
+                ));
+            }
+            match self
+                .call_tools(&request.functions, &response.tool_calls)
+                .await
+            {
+                Ok(results) => messages.extend(results),
+                Err(e) => {
+                    output_messages.extend(messages);
+                    break Err(e);
+                }
+            }
+
+            output_messages.extend(messages.clone());
+            request.messages.extend(messages);
+            iteration += 1;
+        };
+
+        let outcome = CompletionOutcome {
+            result,
+            output_messages,
+            usage,
+            attempts,
+            api_call_elapsed,
+        };
+        let expected_output = input.expected_output.clone();
+        let execution = self
+            .save_execution(input, prepared, execute_by_id, outcome)
             .await?;
+        self.evaluate_quietly(&execution, expected_output).await;
 
-        let re = Regex::new(r#"\$\{([a-zA-Z]+)}"#).unwrap();
-        let chat_messages: Vec<ChatMessage> = input_messages
-            .clone()
-            .into_iter()
-            .map(|message| {
-                let mut content = message.content.clone();
-                for cap in re.captures_iter(&message.content) {
-                    let variable = input
-                        .variables
-                        .get(&cap[1])
-                        .ok_or(ThreadError::Unknown(anyhow::anyhow!("Variable not found")))?;
-                    content = content.replace(&cap[0], variable);
+        Ok(execution)
+    }
+
+    async fn execute_stream(
+        &self,
+        input: ThreadExecuteInput,
+        execute_by_id: Uuid,
+    ) -> Result<ExecutionEventStream> {
+        if input.retry.is_some() || !input.fallbacks.is_empty() {
+            return Err(ThreadError::InvalidInput(
+                "retry and fallbacks are not supported on streamed executions".to_string(),
+            )
+            .into());
+        }
+        if input.max_tool_iterations.is_some() {
+            return Err(ThreadError::InvalidInput(
+                "max_tool_iterations is not supported on streamed executions".to_string(),
+            )
+            .into());
+        }
+
+        let prepared = self.prepare(&input).await?;
+        self.check_budgets(&prepared, execute_by_id).await?;
+        let adapter = self
+            .provider_adapters
+            .get(prepared.target().provider.kind)?;
+
+        // The provider stream is drained on its own task so that the execution is
+        // persisted even if the client disconnects halfway through.
+        let (tx, rx) = mpsc::channel(32);
+        let execution_service = self.execution_service.clone();
+        let service = self.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let target = prepared.target();
+            let outcome = match adapter
+                .chat_completion_stream(&target.provider, &target.decrypted_key, &prepared.request)
+                .await
+            {
+                Ok(stream) => forward_stream(stream, &tx, start).await,
+                Err(e) => StreamOutcome::failed(e),
+            };
+            let api_call_elapsed = start.elapsed();
+
+            let start = Instant::now();
+            let status = if outcome.aborted {
+                ExecutionStatus::Aborted
+            } else if outcome.error.is_some() {
+                ExecutionStatus::Failed
+            } else {
+                ExecutionStatus::Success
+            };
+            let output_messages = outcome.output_message().into_iter().collect();
+            let output_messages = prepared.output_messages(output_messages, execute_by_id);
+            let cost = prepared.cost(outcome.usage.as_ref());
+            let expected_output = input.expected_output.clone();
+            let attempt = ExecutionAttempt {
+                status_code: outcome.status_code,
+                error: outcome.error.clone(),
+                ..target.attempt(1, api_call_elapsed, None)
+            };
+            let elapsed = Elapsed {
+                api_call: api_call_elapsed.as_secs_f64(),
+                post: start.elapsed().as_secs_f64(),
+                time_to_first_token: outcome.time_to_first_token,
+                ..prepared.elapsed
+            };
+
+            let execution = execution_service
+                .create(
+                    ExecutionCreateInput {
+                        thread_id: prepared.thread_version.thread_id,
+                        thread_version_id: input.thread_version_id,
+                        api_key_id: target.api_key_id,
+                        parent_execution_id: input.parent_execution_id,
+                        traffic_split_id: input.traffic_split_id,
+                        traffic_arm: input.traffic_arm,
+                        parameter: target.parameter.clone(),
+                        input_messages: prepared.input_messages,
+                        variables: prepared.variables,
+                        output_messages,
+                        elapsed,
+                        status,
+                        response: Some(outcome.response()),
+                        error: outcome.error.map(|e| json!(e)),
+                        cost,
+                        attempts: vec![attempt],
+                        cache_hit: false,
+                        usage: outcome.usage,
+                    },
+                    execute_by_id,
+                )
+                .await;
+
+            match execution {
+                Ok(execution) => {
+                    service.evaluate_quietly(&execution, expected_output).await;
+                    let _ = tx
+                        .send(ExecutionEvent::Completed(Box::new(execution)))
+                        .await;
                 }
+                Err(e) => error!("failed to save streamed execution: {:?}", e),
+            }
+        });
 
-                Ok(ChatMessage {
-                    content,
-                    role: PromptRole::from_str(message.role.as_str())?,
-                })
-            })
-            .collect::<Result<Vec<ChatMessage>>>()?;
-        let messages_elapsed = start.elapsed();
+        Ok(ReceiverStream::new(rx).boxed())
+    }
+
+    /// Turns a run by slug into an execution of the selected published
+    /// version with its default parameter and the caller's key.
+    async fn resolve_run(
+        &self,
+        slug: String,
+        input: ThreadRunInput,
+        owner_id: Uuid,
+    ) -> Result<ThreadExecuteInput> {
+        let thread = self
+            .find_by_slug(&slug)
+            .await?
+            .ok_or_else(|| ThreadError::NotFound(format!("thread `{}`", slug)))?;
+
+        let selectors = [&input.semver, &input.label, &input.environment];
+        if selectors
+            .iter()
+            .filter(|selector| selector.is_some())
+            .count()
+            > 1
+        {
+            return Err(ThreadError::InvalidInput(
+                "give at most one of a semver, a label and an environment".to_string(),
+            )
+            .into());
+        }
+
+        if let Some(semver) = &input.semver {
+            SemverSelector::parse(semver).map_err(|e| ThreadError::InvalidInput(e.to_string()))?;
+        }
+
+        let mut traffic = None;
+        let thread_version = match (&input.semver, &input.label, &input.environment) {
+            (Some(semver), _, _) => self
+                .thread_version_service
+                .find_by_semver(&thread.id, semver)
+                .await?
+                .filter(|thread_version| thread_version.status == ThreadVersionStatus::Published)
+                .ok_or_else(|| {
+                    ThreadError::NotFound(format!("published version `{}` of `{}`", semver, slug))
+                })?,
+            (_, Some(label), _) if label != LATEST_LABEL => self
+                .thread_version_service
+                .find_by_label(&thread.id, label)
+                .await?
+                .ok_or_else(|| ThreadError::NotFound(format!("label `{}` of `{}`", label, slug)))?,
+            (_, _, Some(environment)) => {
+                let not_found = || {
+                    ThreadError::NotFound(format!(
+                        "a version promoted to `{}` of `{}`",
+                        environment, slug
+                    ))
+                };
+                let environment = self
+                    .environment_service
+                    .find_by_name(&thread.id, environment)
+                    .await?
+                    .ok_or_else(not_found)?;
+                let traffic_split = self
+                    .traffic_split_service
+                    .find_active_by_environment_id(&environment.id)
+                    .await?;
+                let arm = traffic_split
+                    .as_ref()
+                    .and_then(|traffic_split| traffic_split.assign(input.subject.as_deref()));
+                let thread_version_id = match (&traffic_split, arm) {
+                    (Some(traffic_split), Some(arm)) => {
+                        traffic = Some((traffic_split.id, arm.name.clone()));
+                        arm.thread_version_id
+                    }
+                    _ => environment.thread_version_id.ok_or_else(not_found)?,
+                };
+                self.thread_version_service
+                    .find_by_id(&thread_version_id)
+                    .await?
+                    .ok_or_else(not_found)?
+            }
+            _ => self
+                .thread_version_service
+                .find_latest_published(&thread.id)
+                .await?
+                .ok_or_else(|| {
+                    ThreadError::NotFound(format!("a published version of `{}`", slug))
+                })?,
+        };
 
-        let start = Instant::now();
         let parameter = self
             .parameter_service
-            .find_by_id(&input.parameter_id)
+            .find_default(&thread_version.id)
             .await?
-            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Parameter not found")))?;
-        let parameter_elapsed = start.elapsed();
-
-        let start = Instant::now();
+            .ok_or_else(|| {
+                ThreadError::NotFound(format!(
+                    "a default parameter for version `{}` of `{}`",
+                    thread_version.semver, slug
+                ))
+            })?;
         let model = self
             .model_service
             .find_by_id(&parameter.model_id)
             .await?
             .ok_or(ThreadError::Unknown(anyhow::anyhow!("Model not found")))?;
-        let model_elapsed = start.elapsed();
-
-        let start = Instant::now();
-        let provider = self
-            .provider_service
-            .find_by_id(&model.provider_id)
+        let api_key = self
+            .api_key_service
+            .find_by_provider(&owner_id, &model.provider_id)
             .await?
-            .ok_or(ThreadError::Unknown(anyhow::anyhow!("Provider not found")))?;
-        let provider_elapsed = start.elapsed();
+            .ok_or_else(|| {
+                ThreadError::NotFound(format!("an API key for the provider of `{}`", model.name))
+            })?;
 
-        let start = Instant::now();
-        let function_ids = input
-            .tools
+        let (traffic_split_id, traffic_arm) = traffic.unzip();
+        Ok(ThreadExecuteInput {
+            thread_version_id: thread_version.id,
+            parameter_id: parameter.id,
+            api_key_id: api_key.id,
+            tools: input.tools,
+            variables: input.variables,
+            max_tool_iterations: input.max_tool_iterations,
+            retry: input.retry,
+            fallbacks: input.fallbacks,
+            bypass_cache: input.bypass_cache,
+            expected_output: input.expected_output,
+            parent_execution_id: None,
+            traffic_split_id,
+            traffic_arm,
+            stream: input.stream,
+        })
+    }
+
+    async fn evaluate(
+        &self,
+        execution: &Execution,
+        expected_output: Option<String>,
+    ) -> Result<Option<Evaluation>> {
+        let evaluators = self
+            .evaluation_service
+            .find_evaluators_by_thread_version_id(&execution.thread_version_id)
+            .await?;
+        let mut judgements = vec![];
+        for evaluator in evaluators
             .iter()
-            .filter_map(|tool| {
-                if tool.ty == ToolType::Function {
-                    Some(tool.id)
-                } else {
-                    None
-                }
+            .filter(|evaluator| evaluator.kind == EvaluatorKind::Judge)
+        {
+            judgements.push(
+                self.judge(evaluator, execution, expected_output.as_deref())
+                    .await,
+            );
+        }
+
+        self.evaluation_service
+            .evaluate(execution, expected_output, judgements)
+            .await
+    }
+}
+
+/// A parameter resolved down to the model, provider and key it runs against.
+struct ExecutionTarget {
+    parameter: Parameter,
+    model: Model,
+    provider: Provider,
+    api_key_id: Uuid,
+    decrypted_key: String,
+}
+
+impl ExecutionTarget {
+    fn attempt(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+        error: Option<&anyhow::Error>,
+    ) -> ExecutionAttempt {
+        ExecutionAttempt {
+            parameter_id: self.parameter.id,
+            model_id: self.model.id,
+            provider_id: self.provider.id,
+            attempt: attempt as i32,
+            status_code: error
+                .and_then(|e| e.downcast_ref::<ProviderError>())
+                .map(|e| e.status() as i32),
+            error: error.map(|e| e.to_string()),
+            elapsed: elapsed.as_secs_f64(),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+/// Everything an execution needs before the provider is called.
+struct PreparedExecution {
+    thread_version: ThreadVersion,
+    input_messages: Vec<Message>,
+    /// The execution's variables with the schema's defaults filled in.
+    variables: HashMap<String, String>,
+    request: ChatRequest,
+    /// The primary parameter followed by the fallbacks, in order.
+    targets: Vec<ExecutionTarget>,
+    /// Index of the target currently in use.
+    target: usize,
+    elapsed: Elapsed,
+}
+
+impl PreparedExecution {
+    fn target(&self) -> &ExecutionTarget {
+        &self.targets[self.target]
+    }
+
+    /// `request` as sent to the target at `index`.
+    fn target_request(&self, index: usize, request: &ChatRequest) -> ChatRequest {
+        let target = &self.targets[index];
+        ChatRequest {
+            model: target.model.clone(),
+            parameter: target.parameter.clone(),
+            ..request.clone()
+        }
+    }
+
+    fn cost(&self, usage: Option<&Usage>) -> Option<Cost> {
+        usage.and_then(|usage| Cost::new(usage, &self.target().model))
+    }
+
+    fn output_messages(&self, messages: Vec<ChatMessage>, owner_id: Uuid) -> Vec<Message> {
+        let offset = self.input_messages.len() as i32;
+        messages
+            .into_iter()
+            .enumerate()
+            .map(|(index, message)| Message {
+                id: Uuid::new_v4(),
+                thread_version_id: self.thread_version.id,
+                owner_id,
+                raw: message.content.clone(),
+                content: message.content,
+                role: message.role.to_string(),
+                index: offset + index as i32,
+                name: message.name,
+                tool_call_id: message.tool_call_id,
+                tool_calls: message.tool_calls,
+                created_at: Utc::now().naive_utc(),
+                updated_at: Utc::now().naive_utc(),
             })
-            .collect::<Vec<_>>();
-        let functions = self.function_service.find_by_ids(&function_ids).await?;
-        let function_elapsed = start.elapsed();
+            .collect()
+    }
+}
 
-        let start = Instant::now();
-        let response = self
-            .chat_completion(
-                &provider.base_url,
-                &chat_messages,
-                &decrypted_key,
-                parameter.clone(),
-                model,
-                &functions,
-            )
-            .await;
-        let api_call_elapsed = start.elapsed();
+/// What came back from the provider over all round trips of an execution.
+struct CompletionOutcome {
+    result: Result<ChatResponse>,
+    output_messages: Vec<ChatMessage>,
+    usage: Option<Usage>,
+    attempts: Vec<ExecutionAttempt>,
+    api_call_elapsed: Duration,
+}
 
-        let start = Instant::now();
-        let (status, response, usage, error) = match response {
-            Err(e) => (
-                ExecutionStatus::Failed,
-                None,
-                None,
-                Some(json!(e.to_string())),
-            ),
-            Ok(response) => (
-                ExecutionStatus::Success,
-                Some(json!(response.0)),
-                response.0.usage,
-                None,
-            ),
-        };
-        info!(?response);
+#[derive(Default)]
+struct StreamOutcome {
+    content: String,
+    tool_calls: Vec<ExecutionToolCallDelta>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    time_to_first_token: Option<f64>,
+    error: Option<String>,
+    status_code: Option<i32>,
+    aborted: bool,
+}
 
-        let usage = usage.map(|usage| Usage {
-            input_tokens: usage.prompt_tokens as i32,
-            output_tokens: usage.completion_tokens as i32,
-            total_tokens: usage.total_tokens as i32,
-        });
+impl StreamOutcome {
+    fn failed(e: anyhow::Error) -> Self {
+        let mut outcome = Self::default();
+        outcome.fail(e);
+        outcome
+    }
 
-        let post_elapsed = start.elapsed();
+    fn fail(&mut self, e: anyhow::Error) {
+        self.error = Some(e.to_string());
+        self.status_code = e.downcast_ref::<ProviderError>().map(|e| e.status() as i32);
+    }
 
-        let elapsed = Elapsed {
-            api_key: api_key_elapsed.as_secs_f64(),
-            thread_version: thread_version_elapsed.as_secs_f64(),
-            messages: messages_elapsed.as_secs_f64(),
-            parameter: parameter_elapsed.as_secs_f64(),
-            model: model_elapsed.as_secs_f64(),
-            provider: provider_elapsed.as_secs_f64(),
-            function: function_elapsed.as_secs_f64(),
-            api_call: api_call_elapsed.as_secs_f64(),
-            post: post_elapsed.as_secs_f64(),
-        };
+    /// The assistant message the chunks add up to, if they carried anything.
+    fn output_message(&self) -> Option<ChatMessage> {
+        if self.content.is_empty() && self.tool_calls.is_empty() {
+            return None;
+        }
 
-        let execution = self
-            .execution_service
-            .create(
-                ExecutionCreateInput {
-                    thread_id: thread_version.thread_id,
-                    thread_version_id: input.thread_version_id,
-                    variables: input.variables,
-                    parameter,
-                    input_messages,
-                    output_messages: vec![],
-                    elapsed,
-                    status,
-                    response,
-                    error,
-                    usage,
-                },
-                execute_by_id,
-            )
-            .await?;
+        let tool_calls = self
+            .tool_calls
+            .iter()
+            .map(|tool_call| ToolCall {
+                id: tool_call.id.clone().unwrap_or_default(),
+                name: tool_call.name.clone().unwrap_or_default(),
+                arguments: tool_call.arguments.clone().unwrap_or_default(),
+            })
+            .collect();
+        Some(ChatMessage {
+            tool_calls,
+            ..ChatMessage::assistant(self.content.clone())
+        })
+    }
 
-        Ok(execution)
+    /// Reassembles the streamed chunks into a single provider-agnostic response.
+    fn response(&self) -> serde_json::Value {
+        json!({
+            "content": self.content,
+            "tool_calls": self.tool_calls,
+            "finish_reason": self.finish_reason,
+            "usage": self.usage,
+        })
     }
+
+    fn accumulate_tool_call(&mut self, delta: &ExecutionToolCallDelta) {
+        match self.tool_calls.iter_mut().find(|t| t.index == delta.index) {
+            Some(tool_call) => {
+                if delta.id.is_some() {
+                    tool_call.id = delta.id.clone();
+                }
+                if let Some(name) = &delta.name {
+                    tool_call
+                        .name
+                        .get_or_insert_with(String::new)
+                        .push_str(name);
+                }
+                if let Some(arguments) = &delta.arguments {
+                    tool_call
+                        .arguments
+                        .get_or_insert_with(String::new)
+                        .push_str(arguments);
+                }
+            }
+            None => self.tool_calls.push(delta.clone()),
+        }
+    }
+}
+
+async fn forward_stream(
+    mut stream: ChatStream,
+    tx: &mpsc::Sender<ExecutionEvent>,
+    start: Instant,
+) -> StreamOutcome {
+    let mut outcome = StreamOutcome::default();
+
+    while let Some(event) = stream.next().await {
+        let event = match event {
+            Ok(ChatStreamEvent::Content(content)) => {
+                outcome.content.push_str(&content);
+                ExecutionEvent::Delta(ExecutionDelta { content })
+            }
+            Ok(ChatStreamEvent::ToolCall(delta)) => {
+                outcome.accumulate_tool_call(&delta);
+                ExecutionEvent::ToolCallDelta(delta)
+            }
+            Ok(ChatStreamEvent::Usage(usage)) => {
+                outcome.usage = Some(usage);
+                continue;
+            }
+            Ok(ChatStreamEvent::Finish(finish_reason)) => {
+                outcome.finish_reason = Some(finish_reason);
+                continue;
+            }
+            Err(e) => {
+                outcome.fail(e);
+                break;
+            }
+        };
+
+        if outcome.time_to_first_token.is_none() {
+            outcome.time_to_first_token = Some(start.elapsed().as_secs_f64());
+        }
+        if tx.send(event).await.is_err() {
+            outcome.aborted = true;
+            break;
+        }
+    }
+
+    outcome
 }
 
 impl From<ThreadService> for ThreadServiceDyn {
@@ -1,20 +1,42 @@
-use async_graphql::Subscription;
+use async_graphql::{Context, ErrorExtensions, Result, Subscription};
 use futures::Stream;
-use futures_util::StreamExt;
-use std::time::Duration;
+
+use crate::domains::errors::BudgetError;
+use crate::domains::models::{ExecutionEvent, ParsedToken, UserRole};
+use crate::domains::services::ThreadServiceDyn;
+use crate::domains::thread::dto::ThreadExecuteInput;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
 
 #[derive(Default)]
 pub struct ThreadSubscription;
 
 #[Subscription]
 impl ThreadSubscription {
-    async fn execute(&self, #[graphql(default = 1)] condition: i32) -> impl Stream<Item = i32> {
-        let mut value = 0;
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    async fn execute<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: ThreadExecuteInput,
+    ) -> Result<impl Stream<Item = ExecutionEvent>> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let thread_service = ctx
+            .data::<ThreadServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let events = thread_service
+            .execute_stream(input, parsed_token.user_id)
+            .await
+            .map_err(|e| match e.downcast::<BudgetError>() {
+                Ok(e) => e.extend(),
+                Err(e) => e.into(),
+            })?;
 
-        tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(Duration::from_secs(1)))
-            .map(move |_| {
-                value += condition;
-                value
-            })
+        Ok(events)
     }
 }
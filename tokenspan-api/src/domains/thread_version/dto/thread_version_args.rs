@@ -15,6 +15,7 @@ pub struct ThreadVersionArgs {
 #[derive(InputObject)]
 pub struct ThreadVersionBySemver {
     pub thread_id: Uuid,
+    /// An exact semver, a range like `^1.2` or `latest`.
     pub semver: String,
 }
 
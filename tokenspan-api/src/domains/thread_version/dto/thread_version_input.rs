@@ -1,4 +1,4 @@
-use crate::domains::models::ThreadVersionStatus;
+use crate::domains::models::{SemverBump, ThreadVersionStatus, VariableDefinition};
 use async_graphql::InputObject;
 use dojo_macros::UpdateModel;
 use typed_builder::TypedBuilder;
@@ -17,11 +17,20 @@ pub struct ThreadVersionCreateInput {
     pub description: Option<String>,
     #[builder(default)]
     pub document: Option<String>,
+    #[builder(default)]
+    #[graphql(default)]
+    pub variables: Vec<VariableDefinition>,
 }
 
+/// Publishes the draft and opens the next one. Give either the next draft's
+/// `semver` or how to `bump` the published one's; it must come after every
+/// semver the thread has.
 #[derive(InputObject, TypedBuilder)]
 pub struct ThreadVersionPublishInput {
-    pub semver: String,
+    #[builder(default)]
+    pub semver: Option<String>,
+    #[builder(default)]
+    pub bump: Option<SemverBump>,
     pub release_note: String,
 }
 
@@ -31,6 +40,9 @@ pub struct ThreadVersionUpdateInput {
     pub description: Option<String>,
     #[builder(default = None)]
     pub document: Option<String>,
+    /// Replaces the whole variable schema.
+    #[builder(default = None)]
+    pub variables: Option<Vec<VariableDefinition>>,
     #[builder(default = None)]
     #[graphql(skip, default)]
     pub release_note: Option<String>,
@@ -38,3 +50,13 @@ pub struct ThreadVersionUpdateInput {
     #[graphql(skip, default)]
     pub status: Option<ThreadVersionStatus>,
 }
+
+/// Lifts the read-only lock of a published version for `minutes`, recording
+/// why.
+#[derive(InputObject, TypedBuilder)]
+pub struct ThreadVersionUnlockInput {
+    pub reason: String,
+    #[builder(default = 60)]
+    #[graphql(default = 60)]
+    pub minutes: i64,
+}
@@ -2,9 +2,13 @@ pub use thread_version_mutation::ThreadVersionMutation;
 pub use thread_version_query::ThreadVersionQuery;
 
 pub mod dto;
-mod thread_version_error;
+pub mod thread_version_diff;
+pub mod thread_version_error;
 pub mod thread_version_loader;
+pub mod thread_version_lock;
 pub mod thread_version_model;
 mod thread_version_mutation;
 mod thread_version_query;
+pub mod thread_version_semver;
 pub mod thread_version_service;
+pub mod thread_version_variable;
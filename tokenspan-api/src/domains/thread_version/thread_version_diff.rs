@@ -0,0 +1,293 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use similar::{capture_diff_slices, Algorithm, ChangeTag, TextDiff};
+use uuid::Uuid;
+
+use crate::domains::models::{
+    DiffChangeKind, DiffLine, DiffTag, FieldChange, Message, MessageChange, Parameter,
+    ParameterChange, TextFieldChange, ThreadVersion,
+};
+
+/// Diffs two texts line by line.
+pub fn diff_text(old: &str, new: &str) -> Vec<DiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            tag: match change.tag() {
+                ChangeTag::Equal => DiffTag::Equal,
+                ChangeTag::Insert => DiffTag::Insert,
+                ChangeTag::Delete => DiffTag::Delete,
+            },
+            old_line: change.old_index().map(|index| index as i32 + 1),
+            new_line: change.new_index().map(|index| index as i32 + 1),
+            content: change.value().trim_end_matches('\n').to_string(),
+        })
+        .collect()
+}
+
+/// Compares the text fields of two versions, leaving out the unchanged ones.
+pub fn diff_fields(base: &ThreadVersion, target: &ThreadVersion) -> Vec<TextFieldChange> {
+    [
+        ("description", &base.description, &target.description),
+        ("document", &base.document, &target.document),
+        ("release_note", &base.release_note, &target.release_note),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| TextFieldChange {
+        field: field.to_string(),
+        old: old.clone(),
+        new: new.clone(),
+        diff: diff_text(
+            old.as_deref().unwrap_or_default(),
+            new.as_deref().unwrap_or_default(),
+        ),
+    })
+    .collect()
+}
+
+/// Aligns the messages of two versions by `index`. Messages kept in the same
+/// relative order are unchanged even if their index shifted. Identical ones out
+/// of order are moved, and the rest are paired up where they replaced each
+/// other as modified, or else added or removed.
+pub fn diff_messages(old: &[Message], new: &[Message]) -> Vec<MessageChange> {
+    let mut old: Vec<&Message> = old.iter().collect();
+    let mut new: Vec<&Message> = new.iter().collect();
+    old.sort_by_key(|message| message.index);
+    new.sort_by_key(|message| message.index);
+
+    let old_keys: Vec<String> = old.iter().map(|message| message_key(message)).collect();
+    let new_keys: Vec<String> = new.iter().map(|message| message_key(message)).collect();
+
+    // Unmatched messages, each with the diff hunk it came from.
+    let mut removed = vec![];
+    let mut added = vec![];
+    for (hunk, op) in capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys)
+        .iter()
+        .enumerate()
+    {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == similar::DiffTag::Equal {
+            continue;
+        }
+        removed.extend(old_range.map(|i| (hunk, i)));
+        added.extend(new_range.map(|j| (hunk, j)));
+    }
+
+    let mut changes = vec![];
+    removed.retain(|&(_, i)| {
+        let moved = added.iter().position(|&(_, j)| old_keys[i] == new_keys[j]);
+        match moved {
+            Some(position) => {
+                let (_, j) = added.remove(position);
+                changes.push(MessageChange {
+                    kind: DiffChangeKind::Moved,
+                    old_index: Some(old[i].index),
+                    new_index: Some(new[j].index),
+                    role: new[j].role.clone(),
+                    content_diff: vec![],
+                    fields: vec![],
+                });
+                false
+            }
+            None => true,
+        }
+    });
+
+    let mut added_by_hunk: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (hunk, j) in added {
+        added_by_hunk.entry(hunk).or_default().push(j);
+    }
+    for (hunk, i) in removed {
+        let replacement = added_by_hunk
+            .get_mut(&hunk)
+            .filter(|added| !added.is_empty())
+            .map(|added| added.remove(0));
+        let change = match replacement {
+            Some(j) => MessageChange {
+                kind: DiffChangeKind::Modified,
+                old_index: Some(old[i].index),
+                new_index: Some(new[j].index),
+                role: new[j].role.clone(),
+                content_diff: if old[i].content == new[j].content {
+                    vec![]
+                } else {
+                    diff_text(&old[i].content, &new[j].content)
+                },
+                fields: [
+                    field_change("role", &old[i].role, &new[j].role),
+                    field_change("name", &old[i].name, &new[j].name),
+                    field_change("tool_call_id", &old[i].tool_call_id, &new[j].tool_call_id),
+                    field_change("tool_calls", &old[i].tool_calls, &new[j].tool_calls),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            },
+            None => MessageChange {
+                kind: DiffChangeKind::Removed,
+                old_index: Some(old[i].index),
+                new_index: None,
+                role: old[i].role.clone(),
+                content_diff: diff_text(&old[i].content, ""),
+                fields: vec![],
+            },
+        };
+        changes.push(change);
+    }
+    for j in added_by_hunk.into_values().flatten() {
+        changes.push(MessageChange {
+            kind: DiffChangeKind::Added,
+            old_index: None,
+            new_index: Some(new[j].index),
+            role: new[j].role.clone(),
+            content_diff: diff_text("", &new[j].content),
+            fields: vec![],
+        });
+    }
+
+    changes.sort_by_key(|change| (change.new_index.or(change.old_index), change.old_index));
+    changes
+}
+
+/// Matches the parameters of two versions by the parameter they were copied
+/// from, then the rest by name and, among those sharing a name, by creation
+/// order, and compares their settings.
+pub fn diff_parameters(old: &[Parameter], new: &[Parameter]) -> Vec<ParameterChange> {
+    let mut old: Vec<&Parameter> = old.iter().collect();
+    let mut new: Vec<&Parameter> = new.iter().collect();
+    old.sort_by_key(|parameter| (parameter.created_at, parameter.id));
+    new.sort_by_key(|parameter| (parameter.created_at, parameter.id));
+
+    let mut duplicate_names = HashSet::new();
+    for parameters in [&old, &new] {
+        let mut names = HashSet::new();
+        for &parameter in parameters {
+            if !names.insert(parameter.name.as_str()) {
+                duplicate_names.insert(parameter.name.as_str());
+            }
+        }
+    }
+
+    let mut unmatched_new: Vec<Option<&Parameter>> = new.into_iter().map(Some).collect();
+    let mut take_new = |matches: &dyn Fn(&Parameter) -> bool| {
+        unmatched_new
+            .iter_mut()
+            .find(|parameter| parameter.is_some_and(matches))
+            .and_then(Option::take)
+    };
+    let mut pairs = vec![];
+    let mut unmatched_old = vec![];
+    for old_parameter in old {
+        match take_new(&|parameter| parameter_origin(parameter) == parameter_origin(old_parameter))
+        {
+            Some(new_parameter) => pairs.push((Some(old_parameter), Some(new_parameter))),
+            None => unmatched_old.push(old_parameter),
+        }
+    }
+    for old_parameter in unmatched_old {
+        let new_parameter = take_new(&|parameter| parameter.name == old_parameter.name);
+        pairs.push((Some(old_parameter), new_parameter));
+    }
+    pairs.extend(
+        unmatched_new
+            .into_iter()
+            .flatten()
+            .map(|new_parameter| (None, Some(new_parameter))),
+    );
+
+    let mut changes = vec![];
+    for (old_parameter, new_parameter) in pairs {
+        let (kind, name, fields) = match (old_parameter, new_parameter) {
+            (Some(old_parameter), Some(new_parameter)) => {
+                let fields = parameter_fields(old_parameter, new_parameter);
+                if fields.is_empty() {
+                    continue;
+                }
+                (DiffChangeKind::Modified, &new_parameter.name, fields)
+            }
+            (Some(old_parameter), None) => (DiffChangeKind::Removed, &old_parameter.name, vec![]),
+            (None, Some(new_parameter)) => (DiffChangeKind::Added, &new_parameter.name, vec![]),
+            (None, None) => continue,
+        };
+        changes.push(ParameterChange {
+            kind,
+            name: name.clone(),
+            old_id: old_parameter.map(|parameter| parameter.id),
+            new_id: new_parameter.map(|parameter| parameter.id),
+            duplicate_name: duplicate_names.contains(name.as_str()),
+            fields,
+        });
+    }
+
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+/// The first version's parameter that this one is a copy of, or itself.
+fn parameter_origin(parameter: &Parameter) -> Uuid {
+    parameter.origin_id.unwrap_or(parameter.id)
+}
+
+fn parameter_fields(old: &Parameter, new: &Parameter) -> Vec<FieldChange> {
+    [
+        field_change("name", &old.name, &new.name),
+        field_change("model_id", &old.model_id, &new.model_id),
+        field_change("temperature", &old.temperature, &new.temperature),
+        field_change("max_tokens", &old.max_tokens, &new.max_tokens),
+        field_change("stop_sequences", &old.stop_sequences, &new.stop_sequences),
+        field_change("top_p", &old.top_p, &new.top_p),
+        field_change(
+            "frequency_penalty",
+            &old.frequency_penalty,
+            &new.frequency_penalty,
+        ),
+        field_change(
+            "presence_penalty",
+            &old.presence_penalty,
+            &new.presence_penalty,
+        ),
+        field_change("extra", &old.extra, &new.extra),
+        field_change("is_default", &old.is_default, &new.is_default),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// What makes two messages the same, whatever their index.
+fn message_key(message: &Message) -> String {
+    json!({
+        "role": message.role,
+        "name": message.name,
+        "content": message.content,
+        "tool_call_id": message.tool_call_id,
+        "tool_calls": message.tool_calls,
+    })
+    .to_string()
+}
+
+fn field_change<T: Serialize + PartialEq>(field: &str, old: &T, new: &T) -> Option<FieldChange> {
+    if old == new {
+        return None;
+    }
+
+    Some(FieldChange {
+        field: field.to_string(),
+        old: field_value(old),
+        new: field_value(new),
+    })
+}
+
+fn field_value<T: Serialize>(value: &T) -> Option<String> {
+    // Serialized directly rather than through `Value`, which would widen
+    // `f32`s and print 0.7 as 0.699999988079071.
+    let json = serde_json::to_string(value).ok()?;
+    match serde_json::from_str(&json).ok()? {
+        Value::Null => None,
+        Value::String(value) => Some(value),
+        _ => Some(json),
+    }
+}
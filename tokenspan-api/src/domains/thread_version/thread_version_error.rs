@@ -2,6 +2,35 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ThreadVersionError {
+    #[error("invalid variable `{name}`: {reason}")]
+    InvalidVariable { name: String, reason: String },
+
+    #[error("invalid label `{name}`: {reason}")]
+    InvalidLabel { name: String, reason: String },
+
+    #[error("invalid semver `{semver}`: {reason}")]
+    InvalidSemver { semver: String, reason: String },
+
+    #[error("thread version `{semver}` is published and read-only; {hint}")]
+    ReadOnly { semver: String, hint: String },
+
+    #[error("invalid unlock: {0}")]
+    InvalidUnlock(String),
+
+    #[error("thread version is in use: {0}")]
+    InUse(String),
+
+    #[error("thread version not found")]
+    NotFound,
+
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
+
+/// A way in which an execution's variables break the thread version's schema.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("`{name}` {reason}")]
+pub struct VariableViolation {
+    pub name: String,
+    pub reason: String,
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use chrono::Utc;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use uuid::Uuid;
+
+use crate::domains::errors::ThreadVersionError;
+use crate::domains::models::{ThreadVersion, ThreadVersionStatus, ThreadVersionUnlock};
+
+/// How long an admin can unlock a published version for at once.
+pub const MAX_UNLOCK_MINUTES: i64 = 24 * 60;
+
+/// Fails unless the version is a draft or an admin has unlocked it. Takes the
+/// database rather than a service so the message and parameter services,
+/// which the thread version service is built on, can check it too.
+pub async fn ensure_editable(db: &Database, thread_version_id: &Uuid) -> Result<()> {
+    let thread_version = db
+        .bind::<ThreadVersion>()
+        .where_by(equals("id", thread_version_id))
+        .first()
+        .await?
+        .ok_or(ThreadVersionError::NotFound)?;
+    if thread_version.status == ThreadVersionStatus::Draft
+        || find_active_unlock(db, thread_version_id).await?.is_some()
+    {
+        return Ok(());
+    }
+
+    let draft = db
+        .bind::<ThreadVersion>()
+        .where_by(and(&[
+            equals("thread_id", &thread_version.thread_id),
+            equals("status", &ThreadVersionStatus::Draft),
+        ]))
+        .order_by(desc("version"))
+        .first()
+        .await?;
+    let hint = match draft {
+        Some(draft) => format!(
+            "edit the current draft `{}` ({}) instead",
+            draft.semver, draft.id
+        ),
+        None => "edit the thread's current draft instead".to_string(),
+    };
+
+    Err(ThreadVersionError::ReadOnly {
+        semver: thread_version.semver,
+        hint,
+    }
+    .into())
+}
+
+/// The unlock of the version still in effect, if any.
+pub async fn find_active_unlock(
+    db: &Database,
+    thread_version_id: &Uuid,
+) -> Result<Option<ThreadVersionUnlock>> {
+    let unlock = db
+        .bind::<ThreadVersionUnlock>()
+        .where_by(equals("thread_version_id", thread_version_id))
+        .order_by(desc("created_at"))
+        .first()
+        .await?;
+
+    Ok(unlock.filter(|unlock| unlock.is_active(Utc::now().naive_utc())))
+}
@@ -1,13 +1,17 @@
-use async_graphql::{ComplexObject, Enum, SimpleObject};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Enum, InputObject, SimpleObject};
 use async_graphql::{Context, Result};
 use chrono::NaiveDateTime;
-use dojo_macros::{Model, Type};
-use serde::Deserialize;
+use dojo_macros::{EmbeddedModel, Model, Type};
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 use uuid::Uuid;
 
-use crate::domains::models::{Message, Parameter, Thread};
-use crate::domains::services::{MessageServiceDyn, ParameterServiceDyn, ThreadServiceDyn};
+use crate::domains::loaders::UserLoader;
+use crate::domains::models::{Message, Parameter, Thread, User};
+use crate::domains::services::{
+    MessageServiceDyn, ParameterServiceDyn, ThreadServiceDyn, ThreadVersionServiceDyn,
+};
 use crate::errors::AppError;
 
 #[derive(SimpleObject, Clone, Debug, Deserialize, Model)]
@@ -24,6 +28,10 @@ pub struct ThreadVersion {
     pub thread_id: Uuid,
     pub owner_id: Uuid,
     pub published_at: Option<NaiveDateTime>,
+    /// The input variables the messages expect. Executions are checked
+    /// against them when any are declared.
+    #[serde(default)]
+    pub variables: Vec<VariableDefinition>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -71,6 +79,111 @@ impl ThreadVersion {
 
         Ok(messages)
     }
+
+    pub async fn labels<'a>(&self, ctx: &Context<'a>) -> Result<Vec<ThreadVersionLabel>> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let labels = thread_version_service
+            .find_labels_by_thread_version_id(&self.id)
+            .await?;
+
+        Ok(labels)
+    }
+
+    /// Every time an admin unlocked this version, latest first.
+    pub async fn unlocks<'a>(&self, ctx: &Context<'a>) -> Result<Vec<ThreadVersionUnlock>> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let unlocks = thread_version_service
+            .find_unlocks_by_thread_version_id(&self.id)
+            .await?;
+
+        Ok(unlocks)
+    }
+}
+
+/// A name for one published version of a thread, unique within the thread.
+#[derive(SimpleObject, Clone, Debug, Deserialize, Model)]
+#[dojo(name = "thread_version_labels", sort_keys = ["created_at", "id"])]
+pub struct ThreadVersionLabel {
+    pub id: Uuid,
+    pub name: String,
+    pub thread_id: Uuid,
+    pub thread_version_id: Uuid,
+    pub owner_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// An admin lifting the read-only lock of a published version until
+/// `expires_at`, or until someone locks it again. Kept after the version is
+/// deleted, which is recorded in `deleted_at`.
+#[derive(SimpleObject, Clone, Debug, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "thread_version_unlocks", sort_keys = ["created_at", "id"])]
+pub struct ThreadVersionUnlock {
+    pub id: Uuid,
+    pub thread_version_id: Uuid,
+    pub reason: String,
+    pub unlocked_by_id: Uuid,
+    pub expires_at: NaiveDateTime,
+    pub locked_at: Option<NaiveDateTime>,
+    pub locked_by_id: Option<Uuid>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub deleted_by_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl ThreadVersionUnlock {
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        self.locked_at.is_none() && self.deleted_at.is_none() && self.expires_at > now
+    }
+}
+
+#[ComplexObject]
+impl ThreadVersionUnlock {
+    pub async fn unlocked_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let user_loader = ctx
+            .data::<DataLoader<UserLoader>>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let user = user_loader.load_one(self.unlocked_by_id).await?;
+
+        Ok(user)
+    }
+
+    pub async fn locked_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let Some(locked_by_id) = self.locked_by_id else {
+            return Ok(None);
+        };
+
+        let user_loader = ctx
+            .data::<DataLoader<UserLoader>>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let user = user_loader.load_one(locked_by_id).await?;
+
+        Ok(user)
+    }
+
+    pub async fn deleted_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let Some(deleted_by_id) = self.deleted_by_id else {
+            return Ok(None);
+        };
+
+        let user_loader = ctx
+            .data::<DataLoader<UserLoader>>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let user = user_loader.load_one(deleted_by_id).await?;
+
+        Ok(user)
+    }
 }
 
 #[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, EnumString, Deserialize, Type)]
@@ -83,3 +196,130 @@ pub enum ThreadVersionStatus {
     #[serde(rename = "published")]
     Published,
 }
+
+/// Which part of the current version's semver publishing increments.
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SemverBump {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// An input variable of a thread version.
+#[derive(
+    SimpleObject, InputObject, Debug, Clone, PartialEq, Serialize, Deserialize, EmbeddedModel,
+)]
+#[graphql(input_name = "VariableDefinitionInput")]
+pub struct VariableDefinition {
+    pub name: String,
+    #[graphql(default)]
+    #[serde(rename = "type", default)]
+    pub r#type: VariableType,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Used when the variable is not given.
+    #[serde(default)]
+    pub default: Option<String>,
+    #[graphql(default)]
+    #[serde(default)]
+    pub required: bool,
+    /// The allowed values of an `ENUM` variable.
+    #[graphql(default)]
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// Variables are always passed as strings; the type says how to read them.
+#[derive(Enum, Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableType {
+    #[default]
+    String,
+    Number,
+    /// `true` or `false`.
+    Boolean,
+    Enum,
+    Json,
+}
+
+/// What changed from one thread version to another.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ThreadVersionDiff {
+    pub base_id: Uuid,
+    pub target_id: Uuid,
+    /// Changes to the description, document and release note.
+    pub fields: Vec<TextFieldChange>,
+    /// In the order of the target's messages, with removed ones where they
+    /// used to be.
+    pub messages: Vec<MessageChange>,
+    /// Parameters are matched by the parameter they were copied from, or else
+    /// by name and, among those sharing it, by creation order.
+    pub parameters: Vec<ParameterChange>,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct TextFieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub diff: Vec<DiffLine>,
+}
+
+/// A line of a text diff. Line numbers start at 1.
+#[derive(SimpleObject, Clone, Debug, PartialEq)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub old_line: Option<i32>,
+    pub new_line: Option<i32>,
+    pub content: String,
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DiffTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct MessageChange {
+    pub kind: DiffChangeKind,
+    pub old_index: Option<i32>,
+    pub new_index: Option<i32>,
+    pub role: String,
+    /// The content line by line; all inserted for added messages and all
+    /// deleted for removed ones. Empty when the content is unchanged.
+    pub content_diff: Vec<DiffLine>,
+    /// Changes to the role, name and tool calls of a modified message.
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ParameterChange {
+    pub kind: DiffChangeKind,
+    pub name: String,
+    pub old_id: Option<Uuid>,
+    pub new_id: Option<Uuid>,
+    /// Another parameter of either version has the same name, so only the ids
+    /// tell them apart.
+    pub duplicate_name: bool,
+    pub fields: Vec<FieldChange>,
+}
+
+/// Strings are given as they are and other values as JSON. Unset values are
+/// null.
+#[derive(SimpleObject, Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DiffChangeKind {
+    Added,
+    Removed,
+    Modified,
+    /// Unchanged but at another place among the messages.
+    Moved,
+}
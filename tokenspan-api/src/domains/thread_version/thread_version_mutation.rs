@@ -1,8 +1,10 @@
 use async_graphql::{Context, ErrorExtensions, Object, Result};
 use uuid::Uuid;
 
-use crate::domains::dto::ThreadVersionPublishInput;
-use crate::domains::models::{ParsedToken, ThreadVersion, UserRole};
+use crate::domains::dto::{ThreadVersionPublishInput, ThreadVersionUnlockInput};
+use crate::domains::models::{
+    ParsedToken, ThreadVersion, ThreadVersionLabel, ThreadVersionUnlock, UserRole,
+};
 use crate::domains::services::ThreadVersionServiceDyn;
 use crate::domains::thread_version::dto::ThreadVersionUpdateInput;
 use crate::errors::AppError;
@@ -63,8 +65,109 @@ impl ThreadVersionMutation {
             .data::<ThreadVersionServiceDyn>()
             .map_err(|_| AppError::ContextExtractionError)?;
 
-        let thread_version = thread_version_service.delete_by_id(&id).await?;
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let thread_version = thread_version_service
+            .delete_by_id(&id, parsed_token.user_id)
+            .await?;
 
         Ok(thread_version)
     }
+
+    /// Points a label at a published version, taking it from any other
+    /// version of the thread.
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn set_thread_version_label<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        name: String,
+    ) -> Result<ThreadVersionLabel> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let label = thread_version_service
+            .set_label(&id, name, parsed_token.user_id)
+            .await?;
+
+        Ok(label)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_thread_version_label<'a>(
+        &self,
+        ctx: &Context<'a>,
+        thread_id: Uuid,
+        name: String,
+    ) -> Result<ThreadVersionLabel> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let label = thread_version_service
+            .delete_label(&thread_id, &name)
+            .await?;
+
+        Ok(label)
+    }
+
+    /// Lets a published version be edited for a while. Every unlock is kept
+    /// with its reason on the version's `unlocks`.
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn unlock_thread_version<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+        input: ThreadVersionUnlockInput,
+    ) -> Result<ThreadVersionUnlock> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let unlock = thread_version_service
+            .unlock(&id, input, parsed_token.user_id)
+            .await?;
+
+        Ok(unlock)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::Admin)")]
+    pub async fn lock_thread_version<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+    ) -> Result<ThreadVersionUnlock> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let unlock = thread_version_service
+            .lock(&id, parsed_token.user_id)
+            .await?;
+
+        Ok(unlock)
+    }
 }
@@ -1,9 +1,10 @@
 use async_graphql::connection::Connection;
 use async_graphql::{Context, Object, Result};
 use dojo_orm::pagination::{AdditionalFields, Cursor};
+use uuid::Uuid;
 
 use crate::domains::dto::{ThreadVersionArgs, ThreadVersionBy, ThreadVersionBySemver};
-use crate::domains::models::ThreadVersion;
+use crate::domains::models::{ThreadVersion, ThreadVersionDiff};
 use crate::domains::services::ThreadVersionServiceDyn;
 use crate::errors::AppError;
 
@@ -53,4 +54,20 @@ impl ThreadVersionQuery {
 
         Ok(thread_version)
     }
+
+    /// What changed from the base version to the target.
+    pub async fn thread_version_diff<'a>(
+        &self,
+        ctx: &Context<'a>,
+        base_id: Uuid,
+        target_id: Uuid,
+    ) -> Result<ThreadVersionDiff> {
+        let thread_version_service = ctx
+            .data::<ThreadVersionServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let diff = thread_version_service.diff(&base_id, &target_id).await?;
+
+        Ok(diff)
+    }
 }
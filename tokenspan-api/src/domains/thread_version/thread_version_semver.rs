@@ -0,0 +1,92 @@
+use semver::{Version, VersionReq};
+
+use crate::domains::models::{SemverBump, ThreadVersion, ThreadVersionStatus};
+use crate::domains::thread_version::thread_version_error::ThreadVersionError;
+
+/// How a version is picked by its semver.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemverSelector {
+    Exact(Version),
+    /// A range such as `^1.2` or `~1.2.3`, resolved to the highest published
+    /// version in it.
+    Range(VersionReq),
+}
+
+impl SemverSelector {
+    /// A full version like `1.2.3` is exact; anything else is read as a range.
+    pub fn parse(selector: &str) -> Result<Self, ThreadVersionError> {
+        if let Ok(version) = Version::parse(selector) {
+            return Ok(Self::Exact(version));
+        }
+
+        VersionReq::parse(selector).map(Self::Range).map_err(|e| {
+            ThreadVersionError::InvalidSemver {
+                semver: selector.to_string(),
+                reason: e.to_string(),
+            }
+        })
+    }
+
+    /// Picks from a thread's versions. Versions whose semver does not parse
+    /// never match.
+    pub fn select<'a>(&self, thread_versions: &'a [ThreadVersion]) -> Option<&'a ThreadVersion> {
+        let parsed = thread_versions.iter().filter_map(|thread_version| {
+            Version::parse(&thread_version.semver)
+                .ok()
+                .map(|version| (version, thread_version))
+        });
+
+        match self {
+            Self::Exact(exact) => parsed
+                .filter(|(version, _)| version == exact)
+                .map(|(_, thread_version)| thread_version)
+                .next(),
+            Self::Range(req) => parsed
+                .filter(|(version, thread_version)| {
+                    thread_version.status == ThreadVersionStatus::Published && req.matches(version)
+                })
+                .max_by(|(a, _), (b, _)| a.cmp_precedence(b))
+                .map(|(_, thread_version)| thread_version),
+        }
+    }
+}
+
+pub fn parse_semver(semver: &str) -> Result<Version, ThreadVersionError> {
+    Version::parse(semver).map_err(|e| ThreadVersionError::InvalidSemver {
+        semver: semver.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Increments one part of a semver, resetting the ones after it and dropping
+/// any pre-release and build metadata.
+pub fn bump_semver(version: &Version, bump: SemverBump) -> Version {
+    match bump {
+        SemverBump::Major => Version::new(version.major + 1, 0, 0),
+        SemverBump::Minor => Version::new(version.major, version.minor + 1, 0),
+        SemverBump::Patch => Version::new(version.major, version.minor, version.patch + 1),
+    }
+}
+
+/// Checks the next semver of a thread comes after every one it already has.
+pub fn check_next_semver(
+    thread_versions: &[ThreadVersion],
+    next: &Version,
+) -> Result<(), ThreadVersionError> {
+    for thread_version in thread_versions {
+        let taken = thread_version.semver == next.to_string()
+            || Version::parse(&thread_version.semver)
+                .is_ok_and(|version| version.cmp_precedence(next).is_ge());
+        if taken {
+            return Err(ThreadVersionError::InvalidSemver {
+                semver: next.to_string(),
+                reason: format!(
+                    "must come after `{}`, which the thread already has",
+                    thread_version.semver
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
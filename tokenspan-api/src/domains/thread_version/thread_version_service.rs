@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{Duration, NaiveDateTime, Utc};
+use dojo_macros::UpdateModel;
 use dojo_orm::pagination::Pagination;
 use dojo_orm::prelude::*;
 use dojo_orm::Database;
@@ -9,9 +10,16 @@ use tracing::info;
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
 
-use crate::domains::dto::ThreadVersionPublishInput;
-use crate::domains::models::{ThreadVersion, ThreadVersionStatus};
-use crate::domains::services::{MessageServiceDyn, ParameterServiceDyn};
+use crate::domains::dto::{ThreadVersionPublishInput, ThreadVersionUnlockInput};
+use crate::domains::errors::ThreadVersionError;
+use crate::domains::models::{
+    ThreadVersion, ThreadVersionDiff, ThreadVersionLabel, ThreadVersionStatus, ThreadVersionUnlock,
+};
+use crate::domains::services::{
+    bump_semver, check_next_semver, diff_fields, diff_messages, diff_parameters, ensure_editable,
+    find_active_unlock, parse_semver, validate_definitions, EvaluationServiceDyn,
+    MessageServiceDyn, ParameterServiceDyn, SemverSelector, MAX_UNLOCK_MINUTES,
+};
 use crate::domains::thread_version::dto::{
     ThreadVersionArgs, ThreadVersionCreateInput, ThreadVersionUpdateInput,
 };
@@ -26,6 +34,8 @@ pub trait ThreadVersionServiceExt {
         semver: &String,
     ) -> Result<Option<ThreadVersion>>;
     async fn find_latest(&self, thread_id: &Uuid) -> Result<Option<ThreadVersion>>;
+    async fn find_latest_published(&self, thread_id: &Uuid) -> Result<Option<ThreadVersion>>;
+    async fn find_by_label(&self, thread_id: &Uuid, name: &str) -> Result<Option<ThreadVersion>>;
     async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<ThreadVersion>>;
     async fn create(
         &self,
@@ -43,16 +53,93 @@ pub trait ThreadVersionServiceExt {
         id: &Uuid,
         input: ThreadVersionUpdateInput,
     ) -> Result<ThreadVersion>;
-    async fn delete_by_id(&self, id: &Uuid) -> Result<ThreadVersion>;
+    async fn delete_by_id(&self, id: &Uuid, deleted_by_id: Uuid) -> Result<ThreadVersion>;
+    async fn find_labels_by_thread_version_id(
+        &self,
+        thread_version_id: &Uuid,
+    ) -> Result<Vec<ThreadVersionLabel>>;
+    async fn set_label(
+        &self,
+        id: &Uuid,
+        name: String,
+        owner_id: Uuid,
+    ) -> Result<ThreadVersionLabel>;
+    async fn delete_label(&self, thread_id: &Uuid, name: &str) -> Result<ThreadVersionLabel>;
+    async fn diff(&self, base_id: &Uuid, target_id: &Uuid) -> Result<ThreadVersionDiff>;
+    async fn find_unlocks_by_thread_version_id(
+        &self,
+        thread_version_id: &Uuid,
+    ) -> Result<Vec<ThreadVersionUnlock>>;
+    async fn unlock(
+        &self,
+        id: &Uuid,
+        input: ThreadVersionUnlockInput,
+        unlocked_by_id: Uuid,
+    ) -> Result<ThreadVersionUnlock>;
+    async fn lock(&self, id: &Uuid, locked_by_id: Uuid) -> Result<ThreadVersionUnlock>;
 }
 
 pub type ThreadVersionServiceDyn = Arc<dyn ThreadVersionServiceExt + Send + Sync>;
 
+/// Selects the latest published version wherever a version is picked by name.
+pub const LATEST_LABEL: &str = "latest";
+
+#[derive(UpdateModel)]
+struct ThreadVersionUnlockChanges {
+    locked_at: Option<NaiveDateTime>,
+    locked_by_id: Option<Uuid>,
+    deleted_at: Option<NaiveDateTime>,
+    deleted_by_id: Option<Uuid>,
+    updated_at: Option<NaiveDateTime>,
+}
+
 #[derive(TypedBuilder)]
 pub struct ThreadVersionService {
     db: Database,
     parameter_service: ParameterServiceDyn,
     message_service: MessageServiceDyn,
+    evaluation_service: EvaluationServiceDyn,
+}
+
+impl ThreadVersionService {
+    async fn find_all_by_thread_id(&self, thread_id: &Uuid) -> Result<Vec<ThreadVersion>> {
+        self.db
+            .bind::<ThreadVersion>()
+            .where_by(equals("thread_id", thread_id))
+            .order_by(desc("version"))
+            .all()
+            .await
+    }
+
+    /// Fails when something still serves the version: an environment, its
+    /// promotion history, a label or an arm of an active traffic split.
+    async fn ensure_unused(&self, id: &Uuid) -> Result<()> {
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_opt(
+                "SELECT 'environment `' || name || '` serves it' \
+                 FROM environments WHERE thread_version_id = $1 \
+                 UNION ALL \
+                 SELECT 'it was promoted in environment `' || e.name || '`' \
+                 FROM environment_promotions p JOIN environments e ON e.id = p.environment_id \
+                 WHERE p.thread_version_id = $1 OR p.previous_thread_version_id = $1 \
+                 UNION ALL \
+                 SELECT 'label `' || name || '` points at it' \
+                 FROM thread_version_labels WHERE thread_version_id = $1 \
+                 UNION ALL \
+                 SELECT 'traffic split `' || s.name || '` serves it' \
+                 FROM traffic_splits s, unnest(s.arms) arm \
+                 WHERE s.status = 'active' AND (arm ->> 'thread_version_id')::uuid = $1 \
+                 LIMIT 1",
+                &[id],
+            )
+            .await?;
+
+        match row {
+            Some(row) => Err(ThreadVersionError::InUse(row.get(0)).into()),
+            None => Ok(()),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -72,30 +159,69 @@ impl ThreadVersionServiceExt for ThreadVersionService {
             .await
     }
 
+    /// Takes an exact semver or a range like `^1.2`, which picks the highest
+    /// published version in it.
     async fn find_by_semver(
         &self,
         thread_id: &Uuid,
         semver: &String,
     ) -> Result<Option<ThreadVersion>> {
+        let selector = SemverSelector::parse(semver)?;
+        let thread_versions = match &selector {
+            SemverSelector::Exact(version) => {
+                self.db
+                    .bind::<ThreadVersion>()
+                    .where_by(and(&[
+                        equals("thread_id", thread_id),
+                        equals("semver", &version.to_string()),
+                    ]))
+                    .all()
+                    .await?
+            }
+            SemverSelector::Range(_) => self.find_all_by_thread_id(thread_id).await?,
+        };
+
+        Ok(selector.select(&thread_versions).cloned())
+    }
+
+    async fn find_latest(&self, thread_id: &Uuid) -> Result<Option<ThreadVersion>> {
         self.db
             .bind::<ThreadVersion>()
-            .where_by(and(&[
-                equals("thread_id", thread_id),
-                equals("semver", semver),
-            ]))
+            .where_by(equals("thread_id", thread_id))
+            .order_by(desc("version"))
             .first()
             .await
     }
 
-    async fn find_latest(&self, thread_id: &Uuid) -> Result<Option<ThreadVersion>> {
+    async fn find_latest_published(&self, thread_id: &Uuid) -> Result<Option<ThreadVersion>> {
         self.db
             .bind::<ThreadVersion>()
-            .where_by(equals("thread_id", thread_id))
+            .where_by(and(&[
+                equals("thread_id", thread_id),
+                equals("status", &ThreadVersionStatus::Published),
+            ]))
             .order_by(desc("version"))
             .first()
             .await
     }
 
+    async fn find_by_label(&self, thread_id: &Uuid, name: &str) -> Result<Option<ThreadVersion>> {
+        let label = self
+            .db
+            .bind::<ThreadVersionLabel>()
+            .where_by(and(&[
+                equals("thread_id", thread_id),
+                equals("name", &name),
+            ]))
+            .first()
+            .await?;
+
+        match label {
+            Some(label) => self.find_by_id(&label.thread_version_id).await,
+            None => Ok(None),
+        }
+    }
+
     async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<ThreadVersion>> {
         self.db
             .bind::<ThreadVersion>()
@@ -109,6 +235,11 @@ impl ThreadVersionServiceExt for ThreadVersionService {
         input: ThreadVersionCreateInput,
         owner_id: Uuid,
     ) -> Result<ThreadVersion> {
+        validate_definitions(&input.variables)?;
+        let semver = parse_semver(&input.semver)?;
+        let thread_versions = self.find_all_by_thread_id(&input.thread_id).await?;
+        check_next_semver(&thread_versions, &semver)?;
+
         let input = ThreadVersion {
             id: Uuid::new_v4(),
             version: input.version,
@@ -120,6 +251,7 @@ impl ThreadVersionServiceExt for ThreadVersionService {
             thread_id: input.thread_id,
             owner_id,
             published_at: None,
+            variables: input.variables,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
         };
@@ -142,6 +274,22 @@ impl ThreadVersionServiceExt for ThreadVersionService {
             return Err(anyhow::anyhow!("thread version already published"));
         }
 
+        let semver = match (input.semver, input.bump) {
+            (Some(semver), None) => parse_semver(&semver)?,
+            (None, Some(bump)) => bump_semver(&parse_semver(&thread_version.semver)?, bump),
+            _ => {
+                return Err(ThreadVersionError::InvalidSemver {
+                    semver: thread_version.semver,
+                    reason: "give either the next semver or a bump, not both".to_string(),
+                }
+                .into());
+            }
+        };
+        let thread_versions = self
+            .find_all_by_thread_id(&thread_version.thread_id)
+            .await?;
+        check_next_semver(&thread_versions, &semver)?;
+
         let update_input = ThreadVersionUpdateInput {
             release_note: Some(input.release_note),
             status: Some(ThreadVersionStatus::Published),
@@ -156,10 +304,11 @@ impl ThreadVersionServiceExt for ThreadVersionService {
             description: current_thread_version.description,
             document: current_thread_version.document,
             release_note: current_thread_version.release_note,
-            semver: input.semver,
+            semver: semver.to_string(),
             version: new_version,
             thread_id: current_thread_version.thread_id,
             published_at: None,
+            variables: current_thread_version.variables,
             status: ThreadVersionStatus::Draft,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
@@ -172,6 +321,12 @@ impl ThreadVersionServiceExt for ThreadVersionService {
         self.message_service
             .duplicate_by_thread_version_id(&current_thread_version.id, new_thread_version.id)
             .await?;
+        self.evaluation_service
+            .duplicate_evaluators_by_thread_version_id(
+                &current_thread_version.id,
+                new_thread_version.id,
+            )
+            .await?;
 
         Ok(new_thread_version)
     }
@@ -182,6 +337,11 @@ impl ThreadVersionServiceExt for ThreadVersionService {
         input: ThreadVersionUpdateInput,
     ) -> Result<ThreadVersion> {
         info!("update thread_version: id: {}, input: {:?}", id, input);
+        ensure_editable(&self.db, id).await?;
+        if let Some(variables) = &input.variables {
+            validate_definitions(variables)?;
+        }
+
         self.db
             .update(&input)
             .where_by(equals("id", id))
@@ -189,7 +349,9 @@ impl ThreadVersionServiceExt for ThreadVersionService {
             .await
     }
 
-    async fn delete_by_id(&self, id: &Uuid) -> Result<ThreadVersion> {
+    /// A published version can only be deleted while unlocked and nothing
+    /// serves it, and the deletion is recorded on the unlock.
+    async fn delete_by_id(&self, id: &Uuid, deleted_by_id: Uuid) -> Result<ThreadVersion> {
         let thread_version = self
             .find_by_id(id)
             .await?
@@ -198,9 +360,272 @@ impl ThreadVersionServiceExt for ThreadVersionService {
         if thread_version.status == ThreadVersionStatus::Draft {
             return Err(anyhow::anyhow!("thread version is draft"));
         }
+        let unlock = if thread_version.status == ThreadVersionStatus::Published {
+            ensure_editable(&self.db, id).await?;
+            find_active_unlock(&self.db, id).await?
+        } else {
+            None
+        };
+        self.ensure_unused(id).await?;
+
+        let thread_version: ThreadVersion =
+            self.db.delete().where_by(equals("id", id)).exec().await?;
+        if let Some(unlock) = unlock {
+            let now = Utc::now().naive_utc();
+            let changes = ThreadVersionUnlockChanges {
+                locked_at: None,
+                locked_by_id: None,
+                deleted_at: Some(now),
+                deleted_by_id: Some(deleted_by_id),
+                updated_at: Some(now),
+            };
+            info!(
+                "delete unlocked thread_version: id: {}, by: {}, unlock: {}",
+                id, deleted_by_id, unlock.id
+            );
+            let _: ThreadVersionUnlock = self
+                .db
+                .update(&changes)
+                .where_by(equals("id", &unlock.id))
+                .exec()
+                .await?;
+        }
 
-        self.db.delete().where_by(equals("id", id)).exec().await
+        Ok(thread_version)
     }
+
+    async fn find_labels_by_thread_version_id(
+        &self,
+        thread_version_id: &Uuid,
+    ) -> Result<Vec<ThreadVersionLabel>> {
+        self.db
+            .bind::<ThreadVersionLabel>()
+            .where_by(equals("thread_version_id", thread_version_id))
+            .order_by(asc("name"))
+            .all()
+            .await
+    }
+
+    /// Points the label at this version, moving it off whichever version of
+    /// the thread had it.
+    async fn set_label(
+        &self,
+        id: &Uuid,
+        name: String,
+        owner_id: Uuid,
+    ) -> Result<ThreadVersionLabel> {
+        validate_label(&name)?;
+        let thread_version = self
+            .find_by_id(id)
+            .await?
+            .ok_or(ThreadVersionError::NotFound)?;
+        if thread_version.status != ThreadVersionStatus::Published {
+            return Err(ThreadVersionError::InvalidLabel {
+                name,
+                reason: "only published versions can be labelled".to_string(),
+            }
+            .into());
+        }
+
+        let now = Utc::now().naive_utc();
+        let conn = self.db.get().await?;
+        let row = conn
+            .query_one(
+                "INSERT INTO thread_version_labels \
+                 (id, name, thread_id, thread_version_id, owner_id, created_at, updated_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $6) \
+                 ON CONFLICT (thread_id, name) DO UPDATE \
+                 SET thread_version_id = EXCLUDED.thread_version_id, \
+                 owner_id = EXCLUDED.owner_id, updated_at = EXCLUDED.updated_at \
+                 RETURNING id",
+                &[
+                    &Uuid::new_v4(),
+                    &name,
+                    &thread_version.thread_id,
+                    id,
+                    &owner_id,
+                    &now,
+                ],
+            )
+            .await?;
+        let label_id: Uuid = row.get(0);
+
+        self.db
+            .bind::<ThreadVersionLabel>()
+            .where_by(equals("id", &label_id))
+            .first()
+            .await?
+            .ok_or(anyhow::anyhow!("label not found"))
+    }
+
+    async fn delete_label(&self, thread_id: &Uuid, name: &str) -> Result<ThreadVersionLabel> {
+        self.db
+            .delete()
+            .where_by(and(&[
+                equals("thread_id", thread_id),
+                equals("name", &name),
+            ]))
+            .exec()
+            .await
+    }
+
+    async fn diff(&self, base_id: &Uuid, target_id: &Uuid) -> Result<ThreadVersionDiff> {
+        let base = self
+            .find_by_id(base_id)
+            .await?
+            .ok_or(ThreadVersionError::NotFound)?;
+        let target = self
+            .find_by_id(target_id)
+            .await?
+            .ok_or(ThreadVersionError::NotFound)?;
+
+        let base_messages = self
+            .message_service
+            .find_by_thread_version_id(&base.id)
+            .await?;
+        let target_messages = self
+            .message_service
+            .find_by_thread_version_id(&target.id)
+            .await?;
+        let base_parameters = self
+            .parameter_service
+            .find_by_thread_version_id(&base.id)
+            .await?;
+        let target_parameters = self
+            .parameter_service
+            .find_by_thread_version_id(&target.id)
+            .await?;
+
+        Ok(ThreadVersionDiff {
+            base_id: base.id,
+            target_id: target.id,
+            fields: diff_fields(&base, &target),
+            messages: diff_messages(&base_messages, &target_messages),
+            parameters: diff_parameters(&base_parameters, &target_parameters),
+        })
+    }
+
+    async fn find_unlocks_by_thread_version_id(
+        &self,
+        thread_version_id: &Uuid,
+    ) -> Result<Vec<ThreadVersionUnlock>> {
+        self.db
+            .bind::<ThreadVersionUnlock>()
+            .where_by(equals("thread_version_id", thread_version_id))
+            .order_by(desc("created_at"))
+            .all()
+            .await
+    }
+
+    /// Lets a published version be edited until the unlock expires or the
+    /// version is locked again.
+    async fn unlock(
+        &self,
+        id: &Uuid,
+        input: ThreadVersionUnlockInput,
+        unlocked_by_id: Uuid,
+    ) -> Result<ThreadVersionUnlock> {
+        let reason = input.reason.trim().to_string();
+        if reason.is_empty() {
+            return Err(
+                ThreadVersionError::InvalidUnlock("a reason is required".to_string()).into(),
+            );
+        }
+        if !(1..=MAX_UNLOCK_MINUTES).contains(&input.minutes) {
+            return Err(ThreadVersionError::InvalidUnlock(format!(
+                "minutes must be between 1 and {}",
+                MAX_UNLOCK_MINUTES
+            ))
+            .into());
+        }
+
+        let thread_version = self
+            .find_by_id(id)
+            .await?
+            .ok_or(ThreadVersionError::NotFound)?;
+        if thread_version.status != ThreadVersionStatus::Published {
+            return Err(ThreadVersionError::InvalidUnlock(
+                "only published versions are locked".to_string(),
+            )
+            .into());
+        }
+        if let Some(unlock) = find_active_unlock(&self.db, id).await? {
+            return Err(ThreadVersionError::InvalidUnlock(format!(
+                "already unlocked until {}",
+                unlock.expires_at
+            ))
+            .into());
+        }
+
+        let now = Utc::now().naive_utc();
+        let input = ThreadVersionUnlock {
+            id: Uuid::new_v4(),
+            thread_version_id: thread_version.id,
+            reason,
+            unlocked_by_id,
+            expires_at: now + Duration::minutes(input.minutes),
+            locked_at: None,
+            locked_by_id: None,
+            deleted_at: None,
+            deleted_by_id: None,
+            created_at: now,
+            updated_at: now,
+        };
+        info!(
+            "unlock thread_version: id: {}, by: {}, until: {}, reason: {}",
+            id, unlocked_by_id, input.expires_at, input.reason
+        );
+
+        self.db.insert(&input).exec().await
+    }
+
+    /// Ends the unlock in effect before it expires.
+    async fn lock(&self, id: &Uuid, locked_by_id: Uuid) -> Result<ThreadVersionUnlock> {
+        let unlock =
+            find_active_unlock(&self.db, id)
+                .await?
+                .ok_or(ThreadVersionError::InvalidUnlock(
+                    "thread version is not unlocked".to_string(),
+                ))?;
+
+        let now = Utc::now().naive_utc();
+        let changes = ThreadVersionUnlockChanges {
+            locked_at: Some(now),
+            locked_by_id: Some(locked_by_id),
+            deleted_at: None,
+            deleted_by_id: None,
+            updated_at: Some(now),
+        };
+        self.db
+            .update(&changes)
+            .where_by(equals("id", &unlock.id))
+            .exec()
+            .await
+    }
+}
+
+fn validate_label(name: &str) -> Result<(), ThreadVersionError> {
+    let invalid = |reason: &str| ThreadVersionError::InvalidLabel {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+
+    if name.is_empty() || name.len() > 64 {
+        return Err(invalid("must be 1 to 64 characters long"));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(invalid(
+            "may only contain lowercase letters, digits, `-`, `_` and `.`",
+        ));
+    }
+    if name == LATEST_LABEL {
+        return Err(invalid("is reserved for the latest published version"));
+    }
+
+    Ok(())
 }
 
 impl From<ThreadVersionService> for ThreadVersionServiceDyn {
@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::domains::models::{VariableDefinition, VariableType};
+use crate::domains::thread_version::thread_version_error::{ThreadVersionError, VariableViolation};
+
+/// Undeclared names at most this far from a declared one are reported as a
+/// likely typo of it.
+const MAX_TYPO_DISTANCE: usize = 2;
+
+/// Checks a thread version's variable schema when it is saved.
+pub fn validate_definitions(definitions: &[VariableDefinition]) -> Result<(), ThreadVersionError> {
+    let mut names = HashSet::new();
+    for definition in definitions {
+        let invalid = |reason: &str| ThreadVersionError::InvalidVariable {
+            name: definition.name.clone(),
+            reason: reason.to_string(),
+        };
+
+        let mut chars = definition.name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(invalid(
+                "names must start with a letter or `_` and contain only letters, digits and `_`",
+            ));
+        }
+        if !names.insert(definition.name.as_str()) {
+            return Err(invalid("declared more than once"));
+        }
+        match definition.r#type {
+            VariableType::Enum if definition.options.is_empty() => {
+                return Err(invalid("enum variables need options"));
+            }
+            VariableType::Enum => {}
+            _ if !definition.options.is_empty() => {
+                return Err(invalid("only enum variables take options"));
+            }
+            _ => {}
+        }
+        if let Some(default) = &definition.default {
+            check_value(definition, default)
+                .map_err(|reason| invalid(&format!("default {}", reason)))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the variables of an execution against the schema and fills in
+/// defaults. Every violation is reported, not just the first. Without a
+/// schema the variables are taken as they are.
+pub fn resolve_variables(
+    definitions: &[VariableDefinition],
+    variables: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, Vec<VariableViolation>> {
+    if definitions.is_empty() {
+        return Ok(variables.clone());
+    }
+
+    let mut resolved = HashMap::new();
+    let mut violations = vec![];
+    for definition in definitions {
+        let violation = |reason: String| VariableViolation {
+            name: definition.name.clone(),
+            reason,
+        };
+
+        match variables.get(&definition.name) {
+            Some(value) => match check_value(definition, value) {
+                Ok(()) => {
+                    resolved.insert(definition.name.clone(), value.clone());
+                }
+                Err(reason) => violations.push(violation(reason)),
+            },
+            None => match &definition.default {
+                Some(default) => {
+                    resolved.insert(definition.name.clone(), default.clone());
+                }
+                None if definition.required => {
+                    violations.push(violation("is required".to_string()))
+                }
+                None => {}
+            },
+        }
+    }
+
+    let mut undeclared = variables
+        .keys()
+        .filter(|name| !definitions.iter().any(|d| &d.name == *name))
+        .collect::<Vec<_>>();
+    undeclared.sort();
+    for name in undeclared {
+        let suggestion = definitions
+            .iter()
+            .map(|definition| (strsim::levenshtein(name, &definition.name), definition))
+            .filter(|(distance, _)| *distance <= MAX_TYPO_DISTANCE)
+            .min_by_key(|(distance, _)| *distance);
+        let reason = match suggestion {
+            Some((_, definition)) => {
+                format!("is not declared, did you mean `{}`?", definition.name)
+            }
+            None => "is not declared".to_string(),
+        };
+        violations.push(VariableViolation {
+            name: name.clone(),
+            reason,
+        });
+    }
+
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+
+    Ok(resolved)
+}
+
+fn check_value(definition: &VariableDefinition, value: &str) -> Result<(), String> {
+    match definition.r#type {
+        VariableType::String => Ok(()),
+        VariableType::Number => value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .map(|_| ())
+            .ok_or("must be a number".to_string()),
+        VariableType::Boolean => match value {
+            "true" | "false" => Ok(()),
+            _ => Err("must be `true` or `false`".to_string()),
+        },
+        VariableType::Enum => {
+            if definition.options.iter().any(|option| option == value) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "must be one of {}",
+                    definition
+                        .options
+                        .iter()
+                        .map(|option| format!("`{}`", option))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        }
+        VariableType::Json => serde_json::from_str::<serde_json::Value>(value)
+            .map(|_| ())
+            .map_err(|e| format!("must be valid JSON: {}", e)),
+    }
+}
@@ -0,0 +1,3 @@
+mod traffic_split_input;
+
+pub use traffic_split_input::*;
@@ -0,0 +1,11 @@
+use async_graphql::InputObject;
+use uuid::Uuid;
+
+use crate::domains::models::TrafficArm;
+
+#[derive(InputObject)]
+pub struct TrafficSplitCreateInput {
+    pub environment_id: Uuid,
+    pub name: String,
+    pub arms: Vec<TrafficArm>,
+}
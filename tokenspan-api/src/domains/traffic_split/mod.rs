@@ -0,0 +1,9 @@
+pub use traffic_split_mutation::*;
+pub use traffic_split_query::*;
+
+pub mod dto;
+pub mod traffic_split_error;
+pub mod traffic_split_model;
+mod traffic_split_mutation;
+mod traffic_split_query;
+pub mod traffic_split_service;
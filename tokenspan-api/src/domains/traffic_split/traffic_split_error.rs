@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TrafficSplitError {
+    #[error("traffic split not found")]
+    NotFound,
+
+    #[error("invalid arms: {0}")]
+    InvalidArms(String),
+
+    #[error("the environment already has an active traffic split")]
+    AlreadyActive,
+
+    #[error("the traffic split has already ended")]
+    Ended,
+
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
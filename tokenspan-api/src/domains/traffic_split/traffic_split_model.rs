@@ -0,0 +1,117 @@
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Result, SimpleObject};
+use chrono::NaiveDateTime;
+use dojo_macros::{EmbeddedModel, Model, Type};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domains::services::TrafficSplitServiceDyn;
+use crate::errors::AppError;
+
+/// Arm weights are percentages of an environment's runs and add up to this.
+pub const TRAFFIC_BUCKETS: u64 = 100;
+
+/// Routes an environment's runs between published versions of its thread
+/// while active, instead of to the version promoted to it.
+#[derive(SimpleObject, Debug, Clone, Deserialize, Model)]
+#[graphql(complex)]
+#[dojo(name = "traffic_splits", sort_keys = ["created_at", "id"])]
+pub struct TrafficSplit {
+    pub id: Uuid,
+    pub name: String,
+    pub thread_id: Uuid,
+    pub environment_id: Uuid,
+    pub arms: Vec<TrafficArm>,
+    pub status: TrafficSplitStatus,
+    pub owner_id: Uuid,
+    pub ended_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl TrafficSplit {
+    /// Picks the arm serving a run. Runs with the same subject always land on
+    /// the same arm while the weights stay the same; runs without one are
+    /// assigned at random. Buckets are handed out in arm order, so growing the
+    /// first arm's weight keeps its existing subjects on it.
+    pub fn assign(&self, subject: Option<&str>) -> Option<&TrafficArm> {
+        let bucket = match subject {
+            Some(subject) => traffic_bucket(&self.id, subject),
+            None => rand::thread_rng().gen_range(0..TRAFFIC_BUCKETS),
+        };
+
+        let mut upper = 0;
+        self.arms.iter().find(|arm| {
+            upper += arm.weight.max(0) as u64;
+            bucket < upper
+        })
+    }
+}
+
+/// The bucket a subject falls in for a split, from a hash that is stable
+/// across releases.
+pub fn traffic_bucket(traffic_split_id: &Uuid, subject: &str) -> u64 {
+    // 64-bit FNV-1a.
+    let hash = traffic_split_id
+        .as_bytes()
+        .iter()
+        .chain(subject.as_bytes())
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+    hash % TRAFFIC_BUCKETS
+}
+
+#[ComplexObject]
+impl TrafficSplit {
+    /// Executions served by each arm, with their latest evaluations.
+    pub async fn metrics<'a>(&self, ctx: &Context<'a>) -> Result<Vec<TrafficArmMetrics>> {
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let metrics = traffic_split_service.metrics(self).await?;
+
+        Ok(metrics)
+    }
+}
+
+#[derive(
+    SimpleObject, InputObject, Debug, Clone, PartialEq, Serialize, Deserialize, EmbeddedModel,
+)]
+#[graphql(input_name = "TrafficArmInput")]
+pub struct TrafficArm {
+    /// Recorded on the executions the arm serves, e.g. `control` or `canary`.
+    pub name: String,
+    pub thread_version_id: Uuid,
+    /// Percentage of runs sent to this arm.
+    pub weight: i32,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct TrafficArmMetrics {
+    pub arm: String,
+    pub thread_version_id: Uuid,
+    pub executions: i64,
+    pub failures: i64,
+    pub total_tokens: i64,
+    /// Averages leave out executions without the value, such as failed ones.
+    pub avg_latency: Option<f64>,
+    pub avg_cost: Option<f64>,
+    pub evaluated: i64,
+    pub passed: i64,
+    pub avg_score: Option<f64>,
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Display, EnumString, Deserialize, Type)]
+#[dojo(name = "traffic_split_status", rename_all = "lowercase")]
+pub enum TrafficSplitStatus {
+    #[strum(serialize = "active")]
+    #[serde(rename = "active")]
+    Active,
+    #[strum(serialize = "ended")]
+    #[serde(rename = "ended")]
+    Ended,
+}
@@ -0,0 +1,64 @@
+use async_graphql::{Context, ErrorExtensions, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::models::{ParsedToken, UserRole};
+use crate::domains::services::TrafficSplitServiceDyn;
+use crate::domains::traffic_split::dto::TrafficSplitCreateInput;
+use crate::domains::traffic_split::traffic_split_model::TrafficSplit;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct TrafficSplitMutation;
+
+#[Object]
+impl TrafficSplitMutation {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn create_traffic_split<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: TrafficSplitCreateInput,
+    ) -> Result<TrafficSplit> {
+        let parsed_token = ctx
+            .data::<Option<ParsedToken>>()
+            .map_err(|_| AppError::ContextExtractionError.extend())?
+            .as_ref()
+            .ok_or(AppError::Unauthorized("no token".to_string()).extend())?;
+
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let traffic_split = traffic_split_service
+            .create(input, parsed_token.user_id)
+            .await?;
+
+        Ok(traffic_split)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn end_traffic_split<'a>(&self, ctx: &Context<'a>, id: Uuid) -> Result<TrafficSplit> {
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let traffic_split = traffic_split_service.end_by_id(&id).await?;
+
+        Ok(traffic_split)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn delete_traffic_split<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+    ) -> Result<TrafficSplit> {
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let traffic_split = traffic_split_service.delete_by_id(&id).await?;
+
+        Ok(traffic_split)
+    }
+}
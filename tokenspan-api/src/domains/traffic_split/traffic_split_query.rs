@@ -0,0 +1,46 @@
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::domains::models::UserRole;
+use crate::domains::services::TrafficSplitServiceDyn;
+use crate::domains::traffic_split::traffic_split_model::TrafficSplit;
+use crate::errors::AppError;
+use crate::guards::RoleGuard;
+
+#[derive(Default)]
+pub struct TrafficSplitQuery;
+
+#[Object]
+impl TrafficSplitQuery {
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn traffic_splits<'a>(
+        &self,
+        ctx: &Context<'a>,
+        environment_id: Uuid,
+    ) -> Result<Vec<TrafficSplit>> {
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let traffic_splits = traffic_split_service
+            .find_by_environment_id(&environment_id)
+            .await?;
+
+        Ok(traffic_splits)
+    }
+
+    #[graphql(guard = "RoleGuard::new(UserRole::User)")]
+    pub async fn traffic_split<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: Uuid,
+    ) -> Result<Option<TrafficSplit>> {
+        let traffic_split_service = ctx
+            .data::<TrafficSplitServiceDyn>()
+            .map_err(|_| AppError::ContextExtractionError)?;
+
+        let traffic_split = traffic_split_service.find_by_id(&id).await?;
+
+        Ok(traffic_split)
+    }
+}
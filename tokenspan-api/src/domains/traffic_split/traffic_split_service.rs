@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::FromRef;
+use chrono::{NaiveDateTime, Utc};
+use dojo_macros::UpdateModel;
+use dojo_orm::prelude::*;
+use dojo_orm::Database;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+use crate::domains::dto::TrafficSplitCreateInput;
+use crate::domains::errors::TrafficSplitError;
+use crate::domains::models::{
+    Environment, ThreadVersionStatus, TrafficArm, TrafficArmMetrics, TrafficSplit,
+    TrafficSplitStatus, TRAFFIC_BUCKETS,
+};
+use crate::domains::services::{EnvironmentServiceDyn, ThreadVersionServiceDyn};
+use crate::state::AppState;
+
+#[async_trait::async_trait]
+pub trait TrafficSplitServiceExt {
+    async fn find_by_environment_id(&self, environment_id: &Uuid) -> Result<Vec<TrafficSplit>>;
+    async fn find_active_by_environment_id(
+        &self,
+        environment_id: &Uuid,
+    ) -> Result<Option<TrafficSplit>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<TrafficSplit>>;
+    async fn create(&self, input: TrafficSplitCreateInput, owner_id: Uuid) -> Result<TrafficSplit>;
+    async fn end_by_id(&self, id: &Uuid) -> Result<TrafficSplit>;
+    async fn delete_by_id(&self, id: &Uuid) -> Result<TrafficSplit>;
+    async fn metrics(&self, traffic_split: &TrafficSplit) -> Result<Vec<TrafficArmMetrics>>;
+}
+
+pub type TrafficSplitServiceDyn = Arc<dyn TrafficSplitServiceExt + Send + Sync>;
+
+impl FromRef<AppState> for TrafficSplitServiceDyn {
+    fn from_ref(input: &AppState) -> Self {
+        input.traffic_split_service.clone()
+    }
+}
+
+#[derive(UpdateModel)]
+struct TrafficSplitChanges {
+    status: Option<TrafficSplitStatus>,
+    ended_at: Option<NaiveDateTime>,
+    updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(TypedBuilder)]
+pub struct TrafficSplitService {
+    db: Database,
+    environment_service: EnvironmentServiceDyn,
+    thread_version_service: ThreadVersionServiceDyn,
+}
+
+impl TrafficSplitService {
+    /// Checks the arms add up and each serves a published version of the
+    /// environment's thread.
+    async fn validate_arms(
+        &self,
+        environment: &Environment,
+        arms: &[TrafficArm],
+    ) -> Result<(), TrafficSplitError> {
+        let invalid = TrafficSplitError::InvalidArms;
+
+        if arms.len() < 2 {
+            return Err(invalid("a split needs at least two arms".to_string()));
+        }
+        let mut names = HashSet::new();
+        for arm in arms {
+            if arm.name.is_empty() {
+                return Err(invalid("arm names cannot be empty".to_string()));
+            }
+            if !names.insert(arm.name.as_str()) {
+                return Err(invalid(format!("arm `{}` is given twice", arm.name)));
+            }
+            if arm.weight < 0 {
+                return Err(invalid(format!("arm `{}` has a negative weight", arm.name)));
+            }
+        }
+        let total: i64 = arms.iter().map(|arm| arm.weight as i64).sum();
+        if total != TRAFFIC_BUCKETS as i64 {
+            return Err(invalid(format!(
+                "weights add up to {}, not {}",
+                total, TRAFFIC_BUCKETS
+            )));
+        }
+
+        for arm in arms {
+            let thread_version = self
+                .thread_version_service
+                .find_by_id(&arm.thread_version_id)
+                .await?
+                .filter(|thread_version| thread_version.thread_id == environment.thread_id)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "arm `{}` serves a version of another thread",
+                        arm.name
+                    ))
+                })?;
+            if thread_version.status != ThreadVersionStatus::Published {
+                return Err(invalid(format!(
+                    "arm `{}` serves an unpublished version",
+                    arm.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TrafficSplitServiceExt for TrafficSplitService {
+    async fn find_by_environment_id(&self, environment_id: &Uuid) -> Result<Vec<TrafficSplit>> {
+        self.db
+            .bind::<TrafficSplit>()
+            .where_by(equals("environment_id", environment_id))
+            .order_by(desc("created_at"))
+            .all()
+            .await
+    }
+
+    async fn find_active_by_environment_id(
+        &self,
+        environment_id: &Uuid,
+    ) -> Result<Option<TrafficSplit>> {
+        self.db
+            .bind::<TrafficSplit>()
+            .where_by(and(&[
+                equals("environment_id", environment_id),
+                equals("status", &TrafficSplitStatus::Active),
+            ]))
+            .first()
+            .await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<TrafficSplit>> {
+        self.db
+            .bind::<TrafficSplit>()
+            .where_by(equals("id", id))
+            .first()
+            .await
+    }
+
+    async fn create(&self, input: TrafficSplitCreateInput, owner_id: Uuid) -> Result<TrafficSplit> {
+        let environment = self
+            .environment_service
+            .find_by_id(&input.environment_id)
+            .await?
+            .ok_or(anyhow::anyhow!("environment not found"))?;
+        self.validate_arms(&environment, &input.arms).await?;
+        if self
+            .find_active_by_environment_id(&environment.id)
+            .await?
+            .is_some()
+        {
+            return Err(TrafficSplitError::AlreadyActive.into());
+        }
+
+        let input = TrafficSplit {
+            id: Uuid::new_v4(),
+            name: input.name,
+            thread_id: environment.thread_id,
+            environment_id: environment.id,
+            arms: input.arms,
+            status: TrafficSplitStatus::Active,
+            owner_id,
+            ended_at: None,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        self.db.insert(&input).exec().await
+    }
+
+    /// Sends the environment's runs back to its promoted version. The split and
+    /// its metrics are kept.
+    async fn end_by_id(&self, id: &Uuid) -> Result<TrafficSplit> {
+        let traffic_split = self
+            .find_by_id(id)
+            .await?
+            .ok_or(TrafficSplitError::NotFound)?;
+        if traffic_split.status == TrafficSplitStatus::Ended {
+            return Err(TrafficSplitError::Ended.into());
+        }
+
+        let now = Utc::now().naive_utc();
+        let changes = TrafficSplitChanges {
+            status: Some(TrafficSplitStatus::Ended),
+            ended_at: Some(now),
+            updated_at: Some(now),
+        };
+        self.db
+            .update(&changes)
+            .where_by(equals("id", id))
+            .exec()
+            .await
+    }
+
+    async fn delete_by_id(&self, id: &Uuid) -> Result<TrafficSplit> {
+        self.db.delete().where_by(equals("id", id)).exec().await
+    }
+
+    async fn metrics(&self, traffic_split: &TrafficSplit) -> Result<Vec<TrafficArmMetrics>> {
+        let conn = self.db.get().await?;
+        let rows = conn
+            .query(
+                "SELECT e.traffic_arm, COUNT(*)::BIGINT, \
+                 (COUNT(*) FILTER (WHERE e.status = 'failed'))::BIGINT, \
+                 COALESCE(SUM((e.usage ->> 'total_tokens')::BIGINT), 0)::BIGINT, \
+                 AVG((e.elapsed ->> 'api_call')::DOUBLE PRECISION) \
+                 FILTER (WHERE e.status = 'success'), \
+                 AVG((e.cost ->> 'total')::DOUBLE PRECISION), \
+                 COUNT(ev.score)::BIGINT, \
+                 (COUNT(*) FILTER (WHERE ev.passed))::BIGINT, \
+                 AVG(ev.score) \
+                 FROM executions e \
+                 LEFT JOIN LATERAL ( \
+                 SELECT score, passed FROM evaluations \
+                 WHERE execution_id = e.id ORDER BY created_at DESC LIMIT 1 \
+                 ) ev ON TRUE \
+                 WHERE e.traffic_split_id = $1 \
+                 GROUP BY e.traffic_arm",
+                &[&traffic_split.id],
+            )
+            .await?;
+        let mut rows_by_arm: HashMap<String, _> = rows
+            .into_iter()
+            .filter_map(|row| Some((row.get::<_, Option<String>>(0)?, row)))
+            .collect();
+
+        let metrics = traffic_split
+            .arms
+            .iter()
+            .map(|arm| match rows_by_arm.remove(&arm.name) {
+                Some(row) => TrafficArmMetrics {
+                    arm: arm.name.clone(),
+                    thread_version_id: arm.thread_version_id,
+                    executions: row.get(1),
+                    failures: row.get(2),
+                    total_tokens: row.get(3),
+                    avg_latency: row.get(4),
+                    avg_cost: row.get(5),
+                    evaluated: row.get(6),
+                    passed: row.get(7),
+                    avg_score: row.get(8),
+                },
+                None => TrafficArmMetrics {
+                    arm: arm.name.clone(),
+                    thread_version_id: arm.thread_version_id,
+                    executions: 0,
+                    failures: 0,
+                    total_tokens: 0,
+                    avg_latency: None,
+                    avg_cost: None,
+                    evaluated: 0,
+                    passed: 0,
+                    avg_score: None,
+                },
+            })
+            .collect();
+
+        Ok(metrics)
+    }
+}
+
+impl From<TrafficSplitService> for TrafficSplitServiceDyn {
+    fn from(value: TrafficSplitService) -> Self {
+        Arc::new(value) as Self
+    }
+}
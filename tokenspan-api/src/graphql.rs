@@ -1,16 +1,18 @@
 use async_graphql::dataloader::DataLoader;
 use async_graphql::extensions::Tracing;
 use async_graphql::http::GraphiQLSource;
-use async_graphql::Schema;
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql::{Data, Schema};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
+use axum::extract::ws::WebSocketUpgrade;
 use axum::extract::Host;
-use axum::response::{IntoResponse, Redirect};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::{response, Extension};
 use axum_extra::headers::HeaderMap;
 
 use crate::configs::AppConfig;
 use crate::domains::loaders::*;
 use crate::domains::models::ParsedToken;
+use crate::domains::services::AuthService;
 use crate::domains::{MutationRoot, QueryRoot, SubscriptionRoot};
 use crate::state::AppState;
 
@@ -62,6 +64,13 @@ pub async fn build_schema(app_state: AppState) -> AppSchema {
     .data(app_state.parameter_service)
     .data(app_state.message_service)
     .data(app_state.function_service)
+    .data(app_state.budget_service)
+    .data(app_state.dataset_service)
+    .data(app_state.run_service)
+    .data(app_state.evaluation_service)
+    .data(app_state.comparison_service)
+    .data(app_state.environment_service)
+    .data(app_state.traffic_split_service)
     .data(api_key_loader)
     .data(model_loader)
     .data(provider_loader)
@@ -108,3 +117,44 @@ pub async fn graphql_handler(
 
     execute.into()
 }
+
+/// Browsers cannot set headers on a websocket upgrade, so the token may also be sent
+/// in the `connection_init` payload as `{ "Authorization": "Bearer <jwt>" }`.
+fn token_from_payload(payload: &serde_json::Value, config: &AppConfig) -> Option<ParsedToken> {
+    let authorization = payload
+        .get("Authorization")
+        .or_else(|| payload.get("authorization"))
+        .and_then(|value| value.as_str())?;
+    let jwt = authorization
+        .strip_prefix("Bearer ")
+        .unwrap_or(authorization);
+
+    AuthService::decode_token(
+        jwt,
+        config.auth.secret.as_ref(),
+        config.auth.iss.clone(),
+        config.auth.aud.clone(),
+    )
+    .ok()
+}
+
+pub async fn graphql_ws_handler(
+    Extension(schema): Extension<AppSchema>,
+    Extension(config): Extension<AppConfig>,
+    Extension(token): Extension<Option<ParsedToken>>,
+    protocol: GraphQLProtocol,
+    websocket: WebSocketUpgrade,
+) -> Response {
+    websocket
+        .protocols(async_graphql::http::ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(move |payload| async move {
+                    let mut data = Data::default();
+                    data.insert(token_from_payload(&payload, &config).or(token));
+                    data.insert(config);
+                    Ok(data)
+                })
+                .serve()
+        })
+}
@@ -1,5 +1,11 @@
+pub mod template;
+
 use async_graphql::{Enum, InputObject, SimpleObject};
-use async_openai::types::ChatCompletionRequestMessage;
+use async_openai::types::{
+    ChatCompletionMessageToolCall, ChatCompletionRequestMessage, ChatCompletionToolType,
+    FunctionCall,
+};
+use dojo_macros::EmbeddedModel;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 use validator::Validate;
@@ -15,12 +21,20 @@ pub enum PromptRole {
     #[serde(rename = "assistant")]
     #[strum(serialize = "ASSISTANT", serialize = "assistant")]
     Assistant,
+    #[serde(rename = "tool")]
+    #[strum(serialize = "TOOL", serialize = "tool")]
+    Tool,
 }
 
 #[derive(Deserialize, Serialize, InputObject, Debug, Validate, Clone)]
 pub struct ChatMessageInput {
     pub content: String,
     pub role: PromptRole,
+    pub name: Option<String>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub tool_calls: Vec<ToolCall>,
+    pub tool_call_id: Option<String>,
 }
 
 impl From<ChatMessageInput> for ChatMessage {
@@ -28,6 +42,9 @@ impl From<ChatMessageInput> for ChatMessage {
         Self {
             content: value.content,
             role: value.role,
+            name: value.name,
+            tool_calls: value.tool_calls,
+            tool_call_id: value.tool_call_id,
         }
     }
 }
@@ -36,6 +53,49 @@ impl From<ChatMessageInput> for ChatMessage {
 pub struct ChatMessage {
     pub content: String,
     pub role: PromptRole,
+    /// Optional participant name, e.g. to tell few-shot speakers apart.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Function calls requested by an assistant message.
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+    /// The call a tool message answers.
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+}
+
+/// A function call requested by the model, with its arguments as a JSON string.
+#[derive(
+    Deserialize, Serialize, SimpleObject, InputObject, Debug, Clone, PartialEq, EmbeddedModel,
+)]
+#[graphql(input_name = "ToolCallInput")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+impl ChatMessage {
+    pub fn new(role: PromptRole, content: String) -> Self {
+        Self {
+            content,
+            role,
+            name: None,
+            tool_calls: vec![],
+            tool_call_id: None,
+        }
+    }
+
+    pub fn assistant(content: String) -> Self {
+        Self::new(PromptRole::Assistant, content)
+    }
+
+    pub fn tool(tool_call_id: String, content: String) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id),
+            ..Self::new(PromptRole::Tool, content)
+        }
+    }
 }
 
 impl TryFrom<ChatMessage> for ChatCompletionRequestMessage {
@@ -45,22 +105,56 @@ impl TryFrom<ChatMessage> for ChatCompletionRequestMessage {
         let content = value.content.clone();
         let message = match value.role {
             PromptRole::User => {
-                async_openai::types::ChatCompletionRequestUserMessageArgs::default()
-                    .content(content)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!(e))?
-                    .into()
+                let mut args = async_openai::types::ChatCompletionRequestUserMessageArgs::default();
+                args.content(content);
+                if let Some(name) = value.name {
+                    args.name(name);
+                }
+                args.build().map_err(|e| anyhow::anyhow!(e))?.into()
             }
             PromptRole::System => {
-                async_openai::types::ChatCompletionRequestSystemMessageArgs::default()
-                    .content(content)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!(e))?
-                    .into()
+                let mut args =
+                    async_openai::types::ChatCompletionRequestSystemMessageArgs::default();
+                args.content(content);
+                if let Some(name) = value.name {
+                    args.name(name);
+                }
+                args.build().map_err(|e| anyhow::anyhow!(e))?.into()
             }
             PromptRole::Assistant => {
-                async_openai::types::ChatCompletionRequestAssistantMessageArgs::default()
+                let mut args =
+                    async_openai::types::ChatCompletionRequestAssistantMessageArgs::default();
+                if !content.is_empty() || value.tool_calls.is_empty() {
+                    args.content(content);
+                }
+                if let Some(name) = value.name {
+                    args.name(name);
+                }
+                if !value.tool_calls.is_empty() {
+                    args.tool_calls(
+                        value
+                            .tool_calls
+                            .into_iter()
+                            .map(|tool_call| ChatCompletionMessageToolCall {
+                                id: tool_call.id,
+                                r#type: ChatCompletionToolType::Function,
+                                function: FunctionCall {
+                                    name: tool_call.name,
+                                    arguments: tool_call.arguments,
+                                },
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                args.build().map_err(|e| anyhow::anyhow!(e))?.into()
+            }
+            PromptRole::Tool => {
+                let tool_call_id = value
+                    .tool_call_id
+                    .ok_or(anyhow::anyhow!("tool message without a tool_call_id"))?;
+                async_openai::types::ChatCompletionRequestToolMessageArgs::default()
                     .content(content)
+                    .tool_call_id(tool_call_id)
                     .build()
                     .map_err(|e| anyhow::anyhow!(e))?
                     .into()
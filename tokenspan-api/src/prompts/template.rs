@@ -0,0 +1,622 @@
+//! The template language of message contents.
+//!
+//! ```text
+//! Hello ${name | default("there") | trim}!
+//! ${#if context}Use this context: ${context | json}${#else}No context.${/if}
+//! ${#for example in examples}
+//! - ${example.question}: ${example.answer | upper}
+//! ${/for}
+//! Write \${this} literally.
+//! ```
+//!
+//! Variables are strings. A value holding JSON can be looped over when it is
+//! an array and navigated with dots when it is an object. Block tags that sit
+//! alone on their line take the whole line with them.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{reason} at line {line}, column {column}")]
+pub struct TemplateError {
+    pub reason: String,
+    /// 1-based.
+    pub line: usize,
+    /// 1-based, in characters.
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Expr(Expr),
+    If {
+        condition: Path,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        item: String,
+        list: Path,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Expr {
+    path: Path,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone)]
+struct Path {
+    segments: Vec<String>,
+    offset: usize,
+}
+
+impl Path {
+    fn name(&self) -> String {
+        self.segments.join(".")
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Json,
+    Default(String),
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    /// The content between `${` and `}`, and where it starts.
+    Tag(String, usize),
+}
+
+#[derive(Debug)]
+enum Tag {
+    Expr(Expr),
+    If(Path),
+    Else,
+    EndIf,
+    For(String, Path),
+    EndFor,
+}
+
+impl Tag {
+    fn is_block(&self) -> bool {
+        !matches!(self, Tag::Expr(_))
+    }
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let tokens = tokenize(source)?;
+        let mut tags = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            tags.push(match token {
+                Token::Text(text) => Err(text),
+                Token::Tag(content, offset) => Ok((parse_tag(source, &content, offset)?, offset)),
+            });
+        }
+        strip_standalone_lines(source, &mut tags);
+
+        let mut parser = Parser {
+            source,
+            tags: tags.into_iter(),
+        };
+        let (nodes, end) = parser.parse_nodes()?;
+        if let Some((_, offset)) = end {
+            return Err(error(source, offset, "unexpected closing tag"));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            nodes,
+        })
+    }
+
+    pub fn render(&self, variables: &HashMap<String, String>) -> Result<String, TemplateError> {
+        let mut renderer = Renderer {
+            source: &self.source,
+            variables,
+            scopes: vec![],
+        };
+        let mut output = String::new();
+        renderer.render(&self.nodes, &mut output)?;
+
+        Ok(output)
+    }
+}
+
+/// Parses and renders in one go.
+pub fn render(source: &str, variables: &HashMap<String, String>) -> Result<String, TemplateError> {
+    Template::parse(source)?.render(variables)
+}
+
+fn error(source: &str, offset: usize, reason: impl Into<String>) -> TemplateError {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    TemplateError {
+        reason: reason.into(),
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut rest = source;
+    let mut offset = 0;
+
+    while let Some(i) = rest.find(['\\', '$']) {
+        text.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if let Some(after) = tail.strip_prefix("\\${") {
+            text.push_str("${");
+            offset += i + 3;
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("${") {
+            let start = offset + i + 2;
+            let len = tag_len(after).ok_or_else(|| error(source, offset + i, "unclosed `${`"))?;
+            if !text.is_empty() {
+                tokens.push(Token::Text(std::mem::take(&mut text)));
+            }
+            tokens.push(Token::Tag(after[..len].to_string(), start));
+            offset = start + len + 1;
+            rest = &after[len + 1..];
+        } else {
+            text.push_str(&tail[..1]);
+            offset += i + 1;
+            rest = &tail[1..];
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+/// Length of a tag's content up to its closing `}`, skipping over string
+/// literals.
+fn tag_len(content: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in content.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '}' if !in_string => return Some(i),
+            '\n' => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_tag(source: &str, content: &str, offset: usize) -> Result<Tag, TemplateError> {
+    let mut cursor = Cursor {
+        source,
+        content,
+        offset,
+        position: 0,
+    };
+    cursor.skip_whitespace();
+
+    let tag = if cursor.eat("#if") {
+        cursor.expect_whitespace()?;
+        Tag::If(cursor.path()?)
+    } else if cursor.eat("#else") {
+        Tag::Else
+    } else if cursor.eat("/if") {
+        Tag::EndIf
+    } else if cursor.eat("#for") {
+        cursor.expect_whitespace()?;
+        let item = cursor.ident()?;
+        cursor.expect_whitespace()?;
+        if !cursor.eat("in") {
+            return Err(cursor.error("expected `in`"));
+        }
+        cursor.expect_whitespace()?;
+        Tag::For(item, cursor.path()?)
+    } else if cursor.eat("/for") {
+        Tag::EndFor
+    } else if cursor.peek().is_some_and(|c| c == '#' || c == '/') {
+        return Err(cursor.error("unknown block tag"));
+    } else {
+        let path = cursor.path()?;
+        let mut filters = vec![];
+        cursor.skip_whitespace();
+        while cursor.eat("|") {
+            cursor.skip_whitespace();
+            filters.push(cursor.filter()?);
+            cursor.skip_whitespace();
+        }
+        Tag::Expr(Expr { path, filters })
+    };
+
+    cursor.skip_whitespace();
+    if cursor.peek().is_some() {
+        return Err(cursor.error("unexpected character"));
+    }
+
+    Ok(tag)
+}
+
+struct Cursor<'a> {
+    source: &'a str,
+    content: &'a str,
+    offset: usize,
+    position: usize,
+}
+
+impl Cursor<'_> {
+    fn error(&self, reason: &str) -> TemplateError {
+        error(self.source, self.offset + self.position, reason)
+    }
+
+    fn rest(&self) -> &str {
+        &self.content[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        let matched = self.rest().starts_with(prefix)
+            && !self.rest()[prefix.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| prefix.ends_with(is_ident_char) && is_ident_char(c));
+        if matched {
+            self.position += prefix.len();
+        }
+
+        matched
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect_whitespace(&mut self) -> Result<(), TemplateError> {
+        if !self.peek().is_some_and(char::is_whitespace) {
+            return Err(self.error("expected a space"));
+        }
+        self.skip_whitespace();
+
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String, TemplateError> {
+        let rest = self.rest();
+        if !rest
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        {
+            return Err(self.error("expected a variable name"));
+        }
+        let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+        let ident = rest[..len].to_string();
+        self.position += len;
+
+        Ok(ident)
+    }
+
+    fn path(&mut self) -> Result<Path, TemplateError> {
+        let offset = self.offset + self.position;
+        let mut segments = vec![self.ident()?];
+        while self.eat(".") {
+            // List items are reached by their index.
+            let rest = self.rest();
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits > 0 {
+                segments.push(rest[..digits].to_string());
+                self.position += digits;
+            } else {
+                segments.push(self.ident()?);
+            }
+        }
+
+        Ok(Path { segments, offset })
+    }
+
+    fn filter(&mut self) -> Result<Filter, TemplateError> {
+        let start = self.position;
+        let filter = match self.ident()?.as_str() {
+            "upper" => Filter::Upper,
+            "lower" => Filter::Lower,
+            "trim" => Filter::Trim,
+            "json" => Filter::Json,
+            "default" => {
+                self.skip_whitespace();
+                if !self.eat("(") {
+                    return Err(self.error("expected `(`"));
+                }
+                self.skip_whitespace();
+                let value = self.string()?;
+                self.skip_whitespace();
+                if !self.eat(")") {
+                    return Err(self.error("expected `)`"));
+                }
+                Filter::Default(value)
+            }
+            name => {
+                self.position = start;
+                return Err(self.error(&format!("unknown filter `{}`", name)));
+            }
+        };
+
+        Ok(filter)
+    }
+
+    fn string(&mut self) -> Result<String, TemplateError> {
+        if !self.eat("\"") {
+            return Err(self.error("expected a string"));
+        }
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += i + 1;
+                    return Ok(value);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, c)) => value.push(c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+
+        Err(self.error("unclosed string"))
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+type ParsedTag = Result<(Tag, usize), String>;
+
+/// The nodes of a block and the tag that ended it, if any.
+type Block = (Vec<Node>, Option<(Tag, usize)>);
+
+/// Drops the indentation and line break around block tags that are the only
+/// thing on their line.
+fn strip_standalone_lines(source: &str, tags: &mut [ParsedTag]) {
+    for i in 0..tags.len() {
+        let Ok((tag, offset)) = &tags[i] else {
+            continue;
+        };
+        if !tag.is_block() || !is_standalone(source, *offset) {
+            continue;
+        }
+
+        if let Some(Err(text)) = i.checked_sub(1).map(|j| &mut tags[j]) {
+            text.truncate(text.rfind('\n').map_or(0, |j| j + 1));
+        }
+        if let Some(Err(text)) = tags.get_mut(i + 1) {
+            let end = text.find('\n').map_or(text.len(), |j| j + 1);
+            text.drain(..end);
+        }
+    }
+}
+
+/// Whether the tag whose content starts at `offset` has only whitespace
+/// around it on its line.
+fn is_standalone(source: &str, offset: usize) -> bool {
+    let start = offset - 2;
+    let end = offset + tag_len(&source[offset..]).unwrap_or(0) + 1;
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+
+    source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty()
+}
+
+struct Parser<'a, I: Iterator<Item = ParsedTag>> {
+    source: &'a str,
+    tags: I,
+}
+
+impl<I: Iterator<Item = ParsedTag>> Parser<'_, I> {
+    /// Parses up to the end of input or the first tag closing a block.
+    fn parse_nodes(&mut self) -> Result<Block, TemplateError> {
+        let mut nodes = vec![];
+        while let Some(tag) = self.tags.next() {
+            match tag {
+                Err(text) if !text.is_empty() => nodes.push(Node::Text(text)),
+                Err(_) => {}
+                Ok((Tag::Expr(expr), _)) => nodes.push(Node::Expr(expr)),
+                Ok((Tag::If(condition), offset)) => {
+                    let (then, end) = self.parse_nodes()?;
+                    let (otherwise, end) = match end {
+                        Some((Tag::Else, _)) => self.parse_nodes()?,
+                        end => (vec![], end),
+                    };
+                    match end {
+                        Some((Tag::EndIf, _)) => {}
+                        Some((_, offset)) => {
+                            return Err(error(self.source, offset, "expected `/if`"))
+                        }
+                        None => return Err(error(self.source, offset, "unclosed `#if`")),
+                    }
+                    nodes.push(Node::If {
+                        condition,
+                        then,
+                        otherwise,
+                    });
+                }
+                Ok((Tag::For(item, list), offset)) => {
+                    let (body, end) = self.parse_nodes()?;
+                    match end {
+                        Some((Tag::EndFor, _)) => {}
+                        Some((_, offset)) => {
+                            return Err(error(self.source, offset, "expected `/for`"))
+                        }
+                        None => return Err(error(self.source, offset, "unclosed `#for`")),
+                    }
+                    nodes.push(Node::For { item, list, body });
+                }
+                Ok(end) => return Ok((nodes, Some(end))),
+            }
+        }
+
+        Ok((nodes, None))
+    }
+}
+
+struct Renderer<'a> {
+    source: &'a str,
+    variables: &'a HashMap<String, String>,
+    scopes: Vec<(String, Value)>,
+}
+
+impl Renderer<'_> {
+    fn lookup(&self, path: &Path) -> Option<Value> {
+        let (first, rest) = path.segments.split_first()?;
+        let mut value = self
+            .scopes
+            .iter()
+            .rev()
+            .find(|(name, _)| name == first)
+            .map(|(_, value)| value.clone())
+            .or_else(|| self.variables.get(first).cloned().map(Value::String))?;
+
+        for segment in rest {
+            if let Value::String(text) = &value {
+                value = serde_json::from_str(text).ok()?;
+            }
+            value = match value {
+                Value::Object(mut object) => object.remove(segment)?,
+                Value::Array(mut array) => {
+                    let index = segment.parse::<usize>().ok()?;
+                    (index < array.len()).then(|| array.swap_remove(index))?
+                }
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+
+    fn render(&mut self, nodes: &[Node], output: &mut String) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Expr(expr) => output.push_str(&self.evaluate(expr)?),
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    if self
+                        .lookup(condition)
+                        .is_some_and(|value| is_truthy(&value))
+                    {
+                        self.render(then, output)?;
+                    } else {
+                        self.render(otherwise, output)?;
+                    }
+                }
+                Node::For { item, list, body } => {
+                    let value = self.lookup(list).ok_or_else(|| self.undefined(list))?;
+                    let items = match value {
+                        Value::Array(items) => items,
+                        Value::String(text) => match serde_json::from_str(&text) {
+                            Ok(Value::Array(items)) => items,
+                            _ => return Err(self.not_a_list(list)),
+                        },
+                        _ => return Err(self.not_a_list(list)),
+                    };
+                    for value in items {
+                        self.scopes.push((item.clone(), value));
+                        let rendered = self.render(body, output);
+                        self.scopes.pop();
+                        rendered?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&self, expr: &Expr) -> Result<String, TemplateError> {
+        let mut value = self.lookup(&expr.path);
+        for filter in &expr.filters {
+            value = match (filter, value) {
+                (Filter::Default(default), None) => Some(Value::String(default.clone())),
+                (_, None) => None,
+                (Filter::Default(_), value) => value,
+                (Filter::Upper, Some(value)) => Some(Value::String(to_text(value).to_uppercase())),
+                (Filter::Lower, Some(value)) => Some(Value::String(to_text(value).to_lowercase())),
+                (Filter::Trim, Some(value)) => {
+                    Some(Value::String(to_text(value).trim().to_string()))
+                }
+                (Filter::Json, Some(value)) => Some(Value::String(value.to_string())),
+            };
+        }
+
+        value.map(to_text).ok_or_else(|| self.undefined(&expr.path))
+    }
+
+    fn undefined(&self, path: &Path) -> TemplateError {
+        error(
+            self.source,
+            path.offset,
+            format!("variable `{}` is not defined", path.name()),
+        )
+    }
+
+    fn not_a_list(&self, path: &Path) -> TemplateError {
+        error(
+            self.source,
+            path.offset,
+            format!("variable `{}` is not a list", path.name()),
+        )
+    }
+}
+
+fn to_text(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !text.trim().is_empty() && text != "false",
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
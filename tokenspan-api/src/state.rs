@@ -2,8 +2,16 @@ use anyhow::Result;
 use dojo_orm::Database;
 use magic_crypt::new_magic_crypt;
 use std::ops::DerefMut;
+use std::time::Duration;
 
-use crate::configs::AppConfig;
+use crate::adapters::{
+    AnthropicAdapter, GeminiAdapter, OpenAIAdapter, ProviderAdapterRegistry, ToolHandlerRegistry,
+    WebhookToolHandler,
+};
+use crate::configs::{AppConfig, CacheBackend};
+use crate::domains::caches::execution_cache::{
+    MemoryResponseCache, PostgresResponseCache, ResponseCacheDyn,
+};
 use crate::domains::services::*;
 
 mod embedded {
@@ -25,6 +33,13 @@ pub struct AppState {
     pub parameter_service: ParameterServiceDyn,
     pub message_service: MessageServiceDyn,
     pub function_service: FunctionServiceDyn,
+    pub budget_service: BudgetServiceDyn,
+    pub dataset_service: DatasetServiceDyn,
+    pub run_service: RunServiceDyn,
+    pub evaluation_service: EvaluationServiceDyn,
+    pub comparison_service: ComparisonServiceDyn,
+    pub environment_service: EnvironmentServiceDyn,
+    pub traffic_split_service: TrafficSplitServiceDyn,
 }
 
 impl AppState {
@@ -61,16 +76,61 @@ impl AppState {
         let parameter_service: ParameterServiceDyn =
             ParameterService::builder().db(db.clone()).build().into();
 
+        let evaluation_service: EvaluationServiceDyn =
+            EvaluationService::builder().db(db.clone()).build().into();
+
         let thread_version_service: ThreadVersionServiceDyn = ThreadVersionService::builder()
             .db(db.clone())
             .parameter_service(parameter_service.clone())
             .message_service(message_service.clone())
+            .evaluation_service(evaluation_service.clone())
+            .build()
+            .into();
+
+        let environment_service: EnvironmentServiceDyn = EnvironmentService::builder()
+            .db(db.clone())
+            .thread_version_service(thread_version_service.clone())
+            .build()
+            .into();
+
+        let traffic_split_service: TrafficSplitServiceDyn = TrafficSplitService::builder()
+            .db(db.clone())
+            .environment_service(environment_service.clone())
+            .thread_version_service(thread_version_service.clone())
             .build()
             .into();
 
         let function_service: FunctionServiceDyn =
             FunctionService::builder().db(db.clone()).build().into();
 
+        let budget_service: BudgetServiceDyn =
+            BudgetService::builder().db(db.clone()).build().into();
+
+        let dataset_service: DatasetServiceDyn =
+            DatasetService::builder().db(db.clone()).build().into();
+
+        let provider_adapters = ProviderAdapterRegistry::default()
+            .register(OpenAIAdapter::default().into())
+            .register(AnthropicAdapter::default().into())
+            .register(GeminiAdapter::default().into());
+
+        let tool_handlers = app_config.tool.handlers.iter().fold(
+            ToolHandlerRegistry::default(),
+            |registry, (name, url)| {
+                registry.register(name, WebhookToolHandler::new(url.clone()).into())
+            },
+        );
+
+        let response_cache_config = &app_config.response_cache;
+        let response_cache_ttl = Duration::from_secs(response_cache_config.ttl);
+        let response_cache: Option<ResponseCacheDyn> = match response_cache_config.backend {
+            _ if !response_cache_config.enabled => None,
+            CacheBackend::Memory => Some(MemoryResponseCache::new(response_cache_ttl).into()),
+            CacheBackend::Postgres => {
+                Some(PostgresResponseCache::new(db.clone(), response_cache_ttl).into())
+            }
+        };
+
         let thread_service: ThreadServiceDyn = ThreadService::builder()
             .db(db.clone())
             .api_key_service(api_key_service.clone())
@@ -81,6 +141,29 @@ impl AppState {
             .message_service(message_service.clone())
             .provider_service(provider_service.clone())
             .function_service(function_service.clone())
+            .provider_adapters(provider_adapters)
+            .tool_handlers(tool_handlers)
+            .retry_policy(app_config.retry.clone())
+            .response_cache(response_cache)
+            .budget_service(budget_service.clone())
+            .evaluation_service(evaluation_service.clone())
+            .environment_service(environment_service.clone())
+            .traffic_split_service(traffic_split_service.clone())
+            .build()
+            .into();
+
+        let run_service: RunServiceDyn = RunService::builder()
+            .db(db.clone())
+            .thread_service(thread_service.clone())
+            .dataset_service(dataset_service.clone())
+            .build()
+            .into();
+
+        let comparison_service: ComparisonServiceDyn = ComparisonService::builder()
+            .db(db.clone())
+            .run_service(run_service.clone())
+            .execution_service(execution_service.clone())
+            .evaluation_service(evaluation_service.clone())
             .build()
             .into();
 
@@ -96,6 +179,13 @@ impl AppState {
             parameter_service,
             message_service,
             function_service,
+            budget_service,
+            dataset_service,
+            run_service,
+            evaluation_service,
+            comparison_service,
+            environment_service,
+            traffic_split_service,
         })
     }
 }
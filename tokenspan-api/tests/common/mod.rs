@@ -1,6 +1,9 @@
 #[macro_export]
 macro_rules! setup {
     ($state: ident, $server: ident) => {
+        setup!($state, $server, axum_test::TestServerConfig::default());
+    };
+    ($state: ident, $server: ident, $server_config: expr) => {
         // Setup
         std::env::set_var("APP__ENV", "test");
 
@@ -17,6 +20,6 @@ macro_rules! setup {
 
         $state = tokenspan_api::state::AppState::new(&config).await?;
         let app = tokenspan_api::app::make_app_with_state(config, $state.clone()).await?;
-        $server = axum_test::TestServer::new(app)?;
+        $server = axum_test::TestServer::new_with_config(app, $server_config)?;
     };
 }
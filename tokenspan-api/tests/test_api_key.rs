@@ -6,7 +6,7 @@ use googletest::prelude::*;
 use graphql_client::{GraphQLQuery, Response};
 
 use tokenspan_api::domains::dto::{ApiKeyCreateInput, ProviderCreateInput};
-use tokenspan_api::domains::models::UserRole;
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
 use tokenspan_api::state::AppState;
 
 use crate::graphql::{
@@ -75,6 +75,8 @@ async fn test_paginate_forward_api_keys() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -188,6 +190,8 @@ async fn test_paginate_backward_api_keys() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -295,6 +299,8 @@ async fn test_get_api_keys() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -402,6 +408,8 @@ async fn test_get_api_key_by_id() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -485,6 +493,8 @@ async fn test_create_api_key() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -562,6 +572,8 @@ async fn test_update_api_key() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -635,6 +647,8 @@ async fn test_delete_api_key() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
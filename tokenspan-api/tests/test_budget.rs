@@ -0,0 +1,281 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::json;
+
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, BudgetCreateInput, BudgetUpdateInput, ModelCreateInput, PricingInput,
+    ProviderCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::errors::BudgetError;
+use tokenspan_api::domains::models::{
+    BudgetEnforcement, BudgetPeriod, BudgetScope, ProviderKind, UserRole,
+};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const CHAT_COMPLETION_RESPONSE: &str = r#"{
+    "id": "chatcmpl-1",
+    "object": "chat.completion",
+    "created": 1705212532,
+    "model": "gpt-3.5-turbo-0613",
+    "choices": [{
+        "index": 0,
+        "message": { "role": "assistant", "content": "Hello" },
+        "finish_reason": "stop"
+    }],
+    "usage": { "prompt_tokens": 20, "completion_tokens": 10, "total_tokens": 30 }
+}"#;
+
+#[tokio::test]
+async fn test_budget_blocks_execution() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo is a language model that can generate text from a prompt."
+                .to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let result = state
+        .budget_service
+        .create(BudgetCreateInput {
+            scope: BudgetScope::User,
+            scope_id: auth_fixture.user.id,
+            period: BudgetPeriod::Monthly,
+            token_limit: None,
+            cost_limit: None,
+            currency: "USD".to_string(),
+            enforcement: BudgetEnforcement::Soft,
+        })
+        .await;
+    assert_that!(result.is_err(), eq(true));
+
+    let budget_fixture = state
+        .budget_service
+        .create(BudgetCreateInput {
+            scope: BudgetScope::Thread,
+            scope_id: thread_fixture.id,
+            period: BudgetPeriod::Daily,
+            token_limit: Some(30),
+            cost_limit: None,
+            currency: "USD".to_string(),
+            enforcement: BudgetEnforcement::Hard,
+        })
+        .await?;
+
+    let input = json!({
+        "thread_version_id": thread_version_fixture.id,
+        "parameter_id": parameter_fixture.id,
+        "api_key_id": api_key_fixture.id,
+        "variables": {}
+    });
+
+    let resp = server.post("/api/v1/threads/execute").json(&input).await;
+    resp.assert_status_ok();
+
+    let usage = state.budget_service.usage(&budget_fixture).await?;
+    assert_that!(usage.tokens, eq(30));
+    assert_that!(usage.cost, approx_eq(1.8));
+    assert_that!(usage.exceeded, eq(true));
+
+    let resp = server.post("/api/v1/threads/execute").json(&input).await;
+    resp.assert_status(axum_test::http::StatusCode::TOO_MANY_REQUESTS);
+    let body = resp.json::<serde_json::Value>();
+    assert_that!(body["error"].as_str(), some(contains_substring("budget")));
+    mock.assert_hits(1);
+
+    // A limit can be cleared as long as another one is left.
+    let update = |clear_token_limit: bool, cost_limit: Option<f64>| BudgetUpdateInput {
+        period: None,
+        token_limit: None,
+        cost_limit,
+        clear_token_limit,
+        clear_cost_limit: false,
+        currency: None,
+        enforcement: None,
+    };
+    let error = state
+        .budget_service
+        .update_by_id(&budget_fixture.id, update(true, None))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BudgetError>(),
+        Some(BudgetError::NoLimit)
+    ));
+
+    let budget = state
+        .budget_service
+        .update_by_id(&budget_fixture.id, update(true, Some(100.0)))
+        .await?;
+    assert_that!(budget.token_limit, none());
+    assert_that!(budget.cost_limit, some(approx_eq(100.0)));
+
+    let resp = server.post("/api/v1/threads/execute").json(&input).await;
+    resp.assert_status_ok();
+    mock.assert_hits(2);
+
+    // A fallback on another provider is charged to the owner's key for it, so
+    // that key's budget is checked too.
+    let backup_provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "Backup".to_string(),
+            slug: "backup".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+    let backup_model_fixture = state
+        .model_service
+        .create(ModelCreateInput {
+            name: "backup".to_string(),
+            slug: "backup".to_string(),
+            description: "Backup model".to_string(),
+            provider_id: backup_provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+    let backup_api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "Backup".to_string(),
+                key: "sk-456".to_string(),
+                provider_id: backup_provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    state
+        .budget_service
+        .create(BudgetCreateInput {
+            scope: BudgetScope::ApiKey,
+            scope_id: backup_api_key_fixture.id,
+            period: BudgetPeriod::Daily,
+            token_limit: Some(0),
+            cost_limit: None,
+            currency: "USD".to_string(),
+            enforcement: BudgetEnforcement::Hard,
+        })
+        .await?;
+
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "variables": {},
+            "fallbacks": [{ "type": "model", "id": backup_model_fixture.id }]
+        }))
+        .await;
+    resp.assert_status(axum_test::http::StatusCode::TOO_MANY_REQUESTS);
+    mock.assert_hits(2);
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use googletest::prelude::*;
+use serde_json::json;
+use uuid::Uuid;
+
+use tokenspan_api::domains::models::{
+    AssertionResult, ComparisonOutcome, Cost, Elapsed, Evaluation, EvaluatorKind, Execution,
+    ExecutionStatus, Parameter, RunItem, RunItemStatus,
+};
+use tokenspan_api::domains::services::{build_report, sign_test};
+
+fn execution() -> Execution {
+    let now = Utc::now().naive_utc();
+    let thread_version_id = Uuid::new_v4();
+
+    Execution {
+        id: Uuid::new_v4(),
+        thread_id: Uuid::new_v4(),
+        thread_version_id,
+        api_key_id: None,
+        parent_execution_id: None,
+        traffic_split_id: None,
+        traffic_arm: None,
+        executed_by_id: Uuid::new_v4(),
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec![],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id,
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        elapsed: Elapsed {
+            api_call: 1.5,
+            ..Default::default()
+        },
+        input_messages: vec![],
+        output_messages: vec![],
+        response: None,
+        error: None,
+        usage: None,
+        cost: Some(Cost {
+            input: 0.01,
+            output: 0.02,
+            total: 0.03,
+            currency: "USD".to_string(),
+        }),
+        attempts: vec![],
+        cache_hit: false,
+        status: ExecutionStatus::Success,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+struct Side {
+    items: Vec<RunItem>,
+    executions: HashMap<Uuid, Execution>,
+    evaluations: HashMap<Uuid, Evaluation>,
+}
+
+fn side(scores: &[f64]) -> Side {
+    let now = Utc::now().naive_utc();
+    let run_id = Uuid::new_v4();
+    let mut side = Side {
+        items: vec![],
+        executions: HashMap::new(),
+        evaluations: HashMap::new(),
+    };
+
+    for (index, score) in scores.iter().enumerate() {
+        let execution = execution();
+        side.items.push(RunItem {
+            id: Uuid::new_v4(),
+            run_id,
+            index: index as i32,
+            dataset_row_id: None,
+            variables: json!({ "question": index.to_string() }),
+            expected_output: None,
+            status: RunItemStatus::Succeeded,
+            execution_id: Some(execution.id),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        });
+        side.evaluations.insert(
+            execution.id,
+            Evaluation {
+                id: Uuid::new_v4(),
+                execution_id: execution.id,
+                thread_version_id: execution.thread_version_id,
+                expected_output: None,
+                results: vec![AssertionResult {
+                    evaluator_id: Uuid::new_v4(),
+                    name: "exact".to_string(),
+                    kind: EvaluatorKind::ExactMatch,
+                    passed: *score == 1.0,
+                    score: *score,
+                    reason: None,
+                    rationale: None,
+                    execution_id: None,
+                }],
+                score: *score,
+                passed: *score == 1.0,
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        side.executions.insert(execution.id, execution);
+    }
+
+    side
+}
+
+#[tokio::test]
+async fn test_sign_test() -> anyhow::Result<()> {
+    assert_that!(sign_test(0, 0), none());
+    assert_that!(sign_test(5, 5), some(approx_eq(1.0)));
+    assert_that!(sign_test(9, 1), some(near(0.02148, 1e-5)));
+    assert_that!(sign_test(1, 9), some(near(0.02148, 1e-5)));
+    assert_that!(sign_test(100, 0), some(lt(1e-20)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_build_report() -> anyhow::Result<()> {
+    let baseline = side(&[1.0, 0.0, 0.5]);
+    let candidate = side(&[1.0, 1.0, 1.0]);
+    let executions = baseline
+        .executions
+        .into_iter()
+        .chain(candidate.executions)
+        .collect();
+    let evaluations = baseline
+        .evaluations
+        .into_iter()
+        .chain(candidate.evaluations)
+        .collect();
+
+    let report = build_report(&baseline.items, &candidate.items, &executions, &evaluations);
+
+    assert_that!(report.rows.len(), eq(3));
+    assert_that!(report.rows[0].outcome, some(eq(ComparisonOutcome::Tie)));
+    assert_that!(report.rows[1].outcome, some(eq(ComparisonOutcome::Win)));
+    assert_that!(report.rows[2].score_delta, some(approx_eq(0.5)));
+    assert_that!(report.wins, eq(2));
+    assert_that!(report.losses, eq(0));
+    assert_that!(report.ties, eq(1));
+    assert_that!(report.mean_score_delta, some(approx_eq(0.5)));
+    assert_that!(report.baseline.pass_rate, some(near(1.0 / 3.0, 1e-9)));
+    assert_that!(report.candidate.pass_rate, some(approx_eq(1.0)));
+    assert_that!(report.cost_delta, near(0.0, 1e-9));
+    assert_that!(report.p_value, some(approx_eq(0.5)));
+    assert_that!(report.significant, eq(false));
+    assert_that!(report.evaluators.len(), eq(1));
+    assert_that!(report.evaluators[0].delta, some(approx_eq(0.5)));
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use axum_test::TestServer;
+use googletest::prelude::*;
+use serde_json::json;
+
+use tokenspan_api::domains::dto::{DatasetCreateInput, ThreadCreateInput};
+use tokenspan_api::domains::errors::DatasetError;
+use tokenspan_api::domains::models::{DatasetFormat, UserRole};
+use tokenspan_api::domains::services::{parse_rows, to_variables};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+#[tokio::test]
+async fn test_parse_rows() -> anyhow::Result<()> {
+    let jsonl = r#"{"variables":{"city":"Hanoi","days":3},"expected_output":"Sunny"}
+{"city":"Saigon"}
+"#;
+    let rows = parse_rows(DatasetFormat::Jsonl, jsonl)?;
+    assert_that!(rows.len(), eq(2));
+    assert_that!(rows[0].variables["city"], eq(json!("Hanoi")));
+    assert_that!(rows[0].variables["days"], eq(json!("3")));
+    assert_that!(rows[0].expected_output, some(eq("Sunny")));
+    assert_that!(rows[1].variables["city"], eq(json!("Saigon")));
+    assert_that!(rows[1].expected_output, none());
+
+    let csv = "city,expected_output\nHanoi,Sunny\n\"Da Nang, Vietnam\",\n";
+    let rows = parse_rows(DatasetFormat::Csv, csv)?;
+    assert_that!(rows.len(), eq(2));
+    assert_that!(rows[1].variables["city"], eq(json!("Da Nang, Vietnam")));
+    assert_that!(rows[1].expected_output, none());
+
+    let error = parse_rows(DatasetFormat::Jsonl, "{\"city\":\"Hanoi\"}\n[1]\n").unwrap_err();
+    assert!(matches!(error, DatasetError::InvalidRow { line: 2, .. }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_to_variables() -> anyhow::Result<()> {
+    let variables = to_variables(&json!({
+        "city": "Hanoi",
+        "days": 3,
+        "rainy": false,
+        "note": null,
+        "tags": ["a", "b"],
+    }))
+    .ok_or(anyhow::anyhow!("Variables not found"))?;
+    assert_that!(variables["city"], eq("Hanoi"));
+    assert_that!(variables["days"], eq("3"));
+    assert_that!(variables["rainy"], eq("false"));
+    assert_that!(variables["note"], eq(""));
+    assert_that!(variables["tags"], eq(r#"["a","b"]"#));
+
+    assert_that!(to_variables(&json!(["Hanoi"])), none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dataset_import_export() -> anyhow::Result<()> {
+    // Setup
+    let state: AppState;
+    let _server: TestServer;
+    setup!(state, _server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::User,
+        )
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let dataset_fixture = state
+        .dataset_service
+        .create(
+            DatasetCreateInput {
+                name: "weather".to_string(),
+                description: None,
+                thread_id: Some(thread_fixture.id),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let csv = "city,expected_output\nHanoi,Sunny\nSaigon,Rainy\n";
+    state
+        .dataset_service
+        .import_rows(&dataset_fixture.id, DatasetFormat::Csv, csv, false)
+        .await?;
+    let rows = state
+        .dataset_service
+        .import_rows(
+            &dataset_fixture.id,
+            DatasetFormat::Jsonl,
+            r#"{"city":"Hue"}"#,
+            false,
+        )
+        .await?;
+    assert_that!(rows[0].index, eq(2));
+
+    let exported = state
+        .dataset_service
+        .export_rows(&dataset_fixture.id, DatasetFormat::Csv)
+        .await?;
+    assert_that!(
+        exported,
+        eq("city,expected_output\nHanoi,Sunny\nSaigon,Rainy\nHue,\n")
+    );
+
+    let rows = state
+        .dataset_service
+        .import_rows(&dataset_fixture.id, DatasetFormat::Csv, csv, true)
+        .await?;
+    assert_that!(rows.len(), eq(2));
+    let rows = state.dataset_service.find_rows(&dataset_fixture.id).await?;
+    assert_that!(rows.len(), eq(2));
+    assert_that!(rows[1].variable_map()["city"], eq("Saigon"));
+
+    Ok(())
+}
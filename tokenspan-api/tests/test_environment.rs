@@ -0,0 +1,268 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, EnvironmentPromoteInput, ModelCreateInput, PricingInput,
+    ProviderCreateInput, ThreadCreateInput, ThreadRunInput, ThreadVersionPublishInput,
+    ThreadVersionUnlockInput,
+};
+use tokenspan_api::domains::errors::{EnvironmentError, ThreadError};
+use tokenspan_api::domains::models::{PromotionAction, ProviderKind, UserRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+fn run_input(environment: &str) -> ThreadRunInput {
+    ThreadRunInput {
+        semver: None,
+        label: None,
+        environment: Some(environment.to_string()),
+        subject: None,
+        tools: vec![],
+        variables: Default::default(),
+        max_tool_iterations: None,
+        retry: None,
+        fallbacks: vec![],
+        bypass_cache: false,
+        expected_output: None,
+        stream: false,
+    }
+}
+
+#[tokio::test]
+async fn test_environment() -> anyhow::Result<()> {
+    // Setup
+    let state: AppState;
+    let _server: TestServer;
+    setup!(state, _server);
+
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "greeter".to_string(),
+                slug: "greeter".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let first_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+    let second_fixture = state
+        .thread_version_service
+        .publish(
+            &first_fixture.id,
+            ThreadVersionPublishInput {
+                semver: Some("0.1.0".to_string()),
+                bump: None,
+                release_note: "first".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    // Drafts cannot be promoted.
+    let error = state
+        .environment_service
+        .promote(
+            EnvironmentPromoteInput {
+                thread_version_id: second_fixture.id,
+                environment: "production".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<EnvironmentError>(),
+        Some(EnvironmentError::NotPublished)
+    ));
+
+    // The first promotion creates the environment.
+    let promotion = state
+        .environment_service
+        .promote(
+            EnvironmentPromoteInput {
+                thread_version_id: first_fixture.id,
+                environment: "production".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(promotion.action, eq(PromotionAction::Promote));
+    assert_that!(promotion.previous_thread_version_id, none());
+    assert_that!(promotion.promoted_by_id, eq(auth_fixture.user.id));
+
+    state
+        .thread_version_service
+        .publish(
+            &second_fixture.id,
+            ThreadVersionPublishInput {
+                semver: Some("0.2.0".to_string()),
+                bump: None,
+                release_note: "second".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    let promotion = state
+        .environment_service
+        .promote(
+            EnvironmentPromoteInput {
+                thread_version_id: second_fixture.id,
+                environment: "production".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(
+        promotion.previous_thread_version_id,
+        some(eq(first_fixture.id))
+    );
+
+    let resolved = state
+        .thread_service
+        .resolve_run(
+            "greeter".to_string(),
+            run_input("production"),
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(resolved.thread_version_id, eq(second_fixture.id));
+
+    // Rolling back returns to the first version, and there is nothing before it.
+    let rollback = state
+        .environment_service
+        .rollback(&promotion.environment_id, auth_fixture.user.id)
+        .await?;
+    assert_that!(rollback.action, eq(PromotionAction::Rollback));
+    assert_that!(rollback.thread_version_id, eq(first_fixture.id));
+    assert_that!(
+        rollback.previous_thread_version_id,
+        some(eq(second_fixture.id))
+    );
+
+    let error = state
+        .environment_service
+        .rollback(&promotion.environment_id, auth_fixture.user.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<EnvironmentError>(),
+        Some(EnvironmentError::NothingToRollBack)
+    ));
+
+    let resolved = state
+        .thread_service
+        .resolve_run(
+            "greeter".to_string(),
+            run_input("production"),
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(resolved.thread_version_id, eq(first_fixture.id));
+
+    let promotions = state
+        .environment_service
+        .paginate_promotions(&promotion.environment_id, Default::default())
+        .await?;
+    assert_that!(promotions.items.len(), eq(3));
+
+    // A version that was promoted keeps its history and cannot be deleted.
+    state
+        .thread_version_service
+        .unlock(
+            &second_fixture.id,
+            ThreadVersionUnlockInput::builder()
+                .reason("released by mistake".to_string())
+                .build(),
+            auth_fixture.user.id,
+        )
+        .await?;
+    let result = state
+        .thread_version_service
+        .delete_by_id(&second_fixture.id, auth_fixture.user.id)
+        .await;
+    assert!(result.is_err());
+    let promotions = state
+        .environment_service
+        .paginate_promotions(&promotion.environment_id, Default::default())
+        .await?;
+    assert_that!(promotions.items.len(), eq(3));
+
+    // Unknown environments are not found.
+    let error = state
+        .thread_service
+        .resolve_run(
+            "greeter".to_string(),
+            run_input("staging"),
+            auth_fixture.user.id,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        ThreadError::from_execution(error),
+        ThreadError::NotFound(_)
+    ));
+
+    Ok(())
+}
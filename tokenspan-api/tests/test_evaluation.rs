@@ -0,0 +1,198 @@
+use chrono::Utc;
+use googletest::prelude::*;
+use uuid::Uuid;
+
+use tokenspan_api::domains::models::{
+    Cost, Elapsed, Evaluator, EvaluatorKind, Execution, ExecutionStatus, Message, Parameter,
+};
+use tokenspan_api::domains::services::{
+    assert_judgement, assert_output, parse_judgement, validate_evaluator,
+};
+
+fn execution(output: &str) -> Execution {
+    let now = Utc::now().naive_utc();
+    let thread_version_id = Uuid::new_v4();
+
+    Execution {
+        id: Uuid::new_v4(),
+        thread_id: Uuid::new_v4(),
+        thread_version_id,
+        api_key_id: None,
+        parent_execution_id: None,
+        traffic_split_id: None,
+        traffic_arm: None,
+        executed_by_id: Uuid::new_v4(),
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec![],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id,
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        elapsed: Elapsed {
+            api_call: 1.5,
+            ..Default::default()
+        },
+        input_messages: vec![],
+        output_messages: vec![Message {
+            id: Uuid::new_v4(),
+            thread_version_id,
+            owner_id: Uuid::new_v4(),
+            raw: output.to_string(),
+            content: output.to_string(),
+            role: "assistant".to_string(),
+            index: 0,
+            name: None,
+            tool_call_id: None,
+            tool_calls: vec![],
+            created_at: now,
+            updated_at: now,
+        }],
+        response: None,
+        error: None,
+        usage: None,
+        cost: Some(Cost {
+            input: 0.01,
+            output: 0.02,
+            total: 0.03,
+            currency: "USD".to_string(),
+        }),
+        attempts: vec![],
+        cache_hit: false,
+        status: ExecutionStatus::Success,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn evaluator(kind: EvaluatorKind, value: Option<&str>, threshold: Option<f64>) -> Evaluator {
+    let now = Utc::now().naive_utc();
+
+    Evaluator {
+        id: Uuid::new_v4(),
+        thread_version_id: Uuid::new_v4(),
+        name: kind.to_string(),
+        kind,
+        value: value.map(str::to_string),
+        threshold,
+        weight: 1.0,
+        judge_parameter_id: None,
+        judge_api_key_id: None,
+        judge_max_score: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[tokio::test]
+async fn test_assertions() -> anyhow::Result<()> {
+    let execution = execution(r#"{"city": "Hanoi", "temperature": 31}"#);
+    let schema = r#"{
+        "type": "object",
+        "required": ["city", "temperature"],
+        "properties": { "temperature": { "type": "number" } }
+    }"#;
+
+    let cases = [
+        (EvaluatorKind::Contains, Some("Hanoi"), None, true),
+        (EvaluatorKind::ExactMatch, Some("Hanoi"), None, false),
+        (
+            EvaluatorKind::Regex,
+            Some(r#""temperature": \d+"#),
+            None,
+            true,
+        ),
+        (EvaluatorKind::JsonValid, None, None, true),
+        (EvaluatorKind::JsonSchema, Some(schema), None, true),
+        (
+            EvaluatorKind::JsonSchema,
+            Some(r#"{ "required": ["humidity"] }"#),
+            None,
+            false,
+        ),
+        (EvaluatorKind::MaxLength, None, Some(10.0), false),
+        (EvaluatorKind::Latency, None, Some(2.0), true),
+        (EvaluatorKind::Cost, None, Some(0.01), false),
+    ];
+    for (kind, value, threshold, passed) in cases {
+        let result = assert_output(&evaluator(kind, value, threshold), &execution, None);
+        assert_that!((kind, result.passed), eq((kind, passed)));
+        assert_that!(result.reason.is_none(), eq(passed));
+    }
+
+    let similarity = evaluator(EvaluatorKind::Similarity, None, Some(0.9));
+    let result = assert_output(
+        &similarity,
+        &execution,
+        Some(r#"{"city": "Hanoi", "temperature": 30}"#),
+    );
+    assert_that!(result.passed, eq(true));
+    assert_that!(result.score, gt(0.9));
+    let result = assert_output(&similarity, &execution, None);
+    assert_that!(result.passed, eq(false));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_validate_evaluator() -> anyhow::Result<()> {
+    let invalid = [
+        evaluator(EvaluatorKind::Regex, Some("("), None),
+        evaluator(EvaluatorKind::JsonSchema, None, None),
+        evaluator(EvaluatorKind::JsonSchema, Some(r#"{"type": 1}"#), None),
+        evaluator(EvaluatorKind::Latency, None, None),
+        evaluator(EvaluatorKind::Similarity, None, Some(1.5)),
+        evaluator(EvaluatorKind::Judge, None, None),
+    ];
+    for evaluator in invalid {
+        assert_that!(validate_evaluator(&evaluator).is_err(), eq(true));
+    }
+    assert_that!(
+        validate_evaluator(&evaluator(EvaluatorKind::Similarity, None, None)).is_ok(),
+        eq(true)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_judge() -> anyhow::Result<()> {
+    assert_that!(
+        parse_judgement("```json\n{\"score\": 4, \"rationale\": \"Polite\"}\n```"),
+        some(eq((4.0, Some("Polite".to_string()))))
+    );
+    assert_that!(
+        parse_judgement("Score: 7.5\nRationale: Mostly helpful."),
+        some(eq((7.5, Some("Mostly helpful.".to_string()))))
+    );
+    assert_that!(parse_judgement(" 0.9 "), some(eq((0.9, None))));
+    assert_that!(parse_judgement("I cannot grade this."), none());
+
+    let judge = Evaluator {
+        judge_max_score: Some(5.0),
+        threshold: Some(0.7),
+        ..evaluator(EvaluatorKind::Judge, None, None)
+    };
+    let judge_execution = execution(r#"{"score": 4, "rationale": "Polite"}"#);
+    let result = assert_judgement(&judge, &judge_execution);
+    assert_that!(result.passed, eq(true));
+    assert_that!(result.score, approx_eq(0.8));
+    assert_that!(result.rationale, some(eq("Polite")));
+    assert_that!(result.execution_id, some(eq(judge_execution.id)));
+
+    let result = assert_judgement(&judge, &execution("Score: 2"));
+    assert_that!(result.passed, eq(false));
+    assert_that!(result.score, approx_eq(0.4));
+
+    Ok(())
+}
@@ -6,7 +6,7 @@ use googletest::prelude::*;
 use graphql_client::{GraphQLQuery, Response};
 
 use tokenspan_api::domains::dto::{ModelCreateInput, PricingInput, ProviderCreateInput};
-use tokenspan_api::domains::models::UserRole;
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
 use tokenspan_api::state::AppState;
 
 use crate::graphql::{
@@ -84,6 +84,8 @@ async fn test_paginate_forward_models() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "http://localhost:8080".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -248,6 +250,8 @@ async fn test_paginate_backward_models() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "http://localhost:8080".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -412,6 +416,8 @@ async fn test_get_model_by_id() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "http://localhost:8080".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -509,6 +515,8 @@ async fn test_create_model() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "http://localhost:8080".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -603,6 +611,8 @@ async fn test_update_model() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "http://localhost:8080".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -719,6 +729,8 @@ async fn test_delete_model() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "http://localhost:8080".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
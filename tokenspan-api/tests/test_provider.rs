@@ -6,7 +6,7 @@ use googletest::prelude::*;
 use graphql_client::{GraphQLQuery, Response};
 
 use tokenspan_api::domains::dto::ProviderCreateInput;
-use tokenspan_api::domains::models::UserRole;
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
 use tokenspan_api::state::AppState;
 
 use crate::graphql::{
@@ -25,6 +25,9 @@ macro_rules! create_provider {
             .create(ProviderCreateInput {
                 name: $name.to_string(),
                 slug: $slug.to_string(),
+                base_url: "https://api.openai.com/v1".to_string(),
+                kind: ProviderKind::OpenAI,
+                ..Default::default()
             })
             .await?;
     };
@@ -364,6 +367,8 @@ async fn test_get_provider_by_id() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -478,6 +483,8 @@ async fn test_update_provider() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -544,6 +551,8 @@ async fn test_delete_provider() -> Result<()> {
             name: "OpenAI".to_string(),
             slug: "openai".to_string(),
             base_url: "https://api.openai.com".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
         })
         .await?;
 
@@ -0,0 +1,414 @@
+use std::time::Duration;
+
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::json;
+use uuid::Uuid;
+
+use tokenspan_api::adapters::{ChatRequest, ChatResponse};
+use tokenspan_api::domains::caches::execution_cache::MemoryResponseCache;
+use tokenspan_api::domains::caches::CacheExt;
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{Model, Parameter, Pricing, ProviderKind, UserRole};
+use tokenspan_api::prompts::{ChatMessage, PromptRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const CHAT_COMPLETION_RESPONSE: &str = r#"{
+    "id": "chatcmpl-1",
+    "object": "chat.completion",
+    "created": 1705212532,
+    "model": "gpt-3.5-turbo-0613",
+    "choices": [{
+        "index": 0,
+        "message": { "role": "assistant", "content": "Hello" },
+        "finish_reason": "stop"
+    }],
+    "usage": { "prompt_tokens": 20, "completion_tokens": 10, "total_tokens": 30 }
+}"#;
+
+fn chat_request() -> ChatRequest {
+    let now = Utc::now().naive_utc();
+    let pricing = Pricing {
+        price: 0.5,
+        tokens: 1000000,
+        currency: "USD".to_string(),
+    };
+
+    ChatRequest {
+        model: Model {
+            id: Uuid::new_v4(),
+            name: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            context: 16000,
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+            training_at: now,
+            provider_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        },
+        messages: vec![ChatMessage::new(PromptRole::User, "Hello".to_string())],
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec![],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        functions: vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_memory_response_cache() -> anyhow::Result<()> {
+    let request = chat_request();
+    let same_request = ChatRequest {
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            ..request.parameter.clone()
+        },
+        ..request.clone()
+    };
+    let other_request = ChatRequest {
+        parameter: Parameter {
+            temperature: 0.9,
+            ..request.parameter.clone()
+        },
+        ..request.clone()
+    };
+    assert_that!(request.cache_key(), eq(same_request.cache_key()));
+    assert_that!(request.cache_key(), not(eq(other_request.cache_key())));
+
+    let response = ChatResponse {
+        messages: vec![ChatMessage::assistant("Hello".to_string())],
+        tool_calls: vec![],
+        usage: None,
+        finish_reason: Some("stop".to_string()),
+        raw: json!({}),
+    };
+    let cache = MemoryResponseCache::new(Duration::from_millis(50));
+    cache.set(request.cache_key(), response).await;
+
+    let cached = cache.get(same_request.cache_key()).await;
+    assert_that!(
+        cached.map(|response| response.messages[0].content.clone()),
+        some(eq("Hello"))
+    );
+    assert_that!(
+        cache.get(other_request.cache_key()).await.is_none(),
+        eq(true)
+    );
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_that!(cache.get(request.cache_key()).await.is_none(), eq(true));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_cache_hit() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    // Setup
+    std::env::set_var("APP__RESPONSE_CACHE__ENABLED", "true");
+    std::env::set_var("APP__RESPONSE_CACHE__BACKEND", "postgres");
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 16000,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let input = json!({
+        "thread_version_id": thread_version_fixture.id,
+        "parameter_id": parameter_fixture.id,
+        "api_key_id": api_key_fixture.id,
+        "variables": {}
+    });
+
+    let first = server
+        .post("/api/v1/threads/execute")
+        .json(&input)
+        .await
+        .json::<serde_json::Value>();
+    let second = server
+        .post("/api/v1/threads/execute")
+        .json(&input)
+        .await
+        .json::<serde_json::Value>();
+    mock.assert_hits(1);
+
+    assert_that!(first["cache_hit"], eq(json!(false)));
+    assert_that!(second["cache_hit"], eq(json!(true)));
+    assert_that!(second["status"], eq(json!("success")));
+    assert_that!(second["cost"]["total"], eq(json!(0.0)));
+    assert_that!(second["usage"], eq(json!(null)));
+    assert_that!(second["output_messages"][0]["content"], eq(json!("Hello")));
+
+    let mut bypass_input = input.clone();
+    bypass_input["bypass_cache"] = json!(true);
+    let bypassed = server
+        .post("/api/v1/threads/execute")
+        .json(&bypass_input)
+        .await
+        .json::<serde_json::Value>();
+    mock.assert_hits(2);
+    assert_that!(bypassed["cache_hit"], eq(json!(false)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_fallback_cache() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let primary_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(json!({ "model": "gpt-4" }).to_string());
+
+        then.status(503)
+            .header("content-type", "application/json")
+            .body(r#"{"error":{"message":"The server is overloaded"}}"#);
+    });
+    let fallback_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(json!({ "model": "gpt-3.5-turbo" }).to_string());
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    // Setup
+    std::env::set_var("APP__RESPONSE_CACHE__ENABLED", "true");
+    std::env::set_var("APP__RESPONSE_CACHE__BACKEND", "postgres");
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    let pricing = PricingInput {
+        currency: "USD".to_string(),
+        price: 0.06,
+        tokens: 1,
+    };
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-4".to_string(),
+            slug: "gpt-4".to_string(),
+            description: "GPT-4".to_string(),
+            provider_id: provider_fixture.id,
+            context: 8192,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: pricing.clone(),
+            output_pricing: pricing.clone(),
+        })
+        .await?;
+    let fallback_model_fixture = state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 16000,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    // The fallback's answer is not served for the primary model.
+    let input = json!({
+        "thread_version_id": thread_version_fixture.id,
+        "parameter_id": parameter_fixture.id,
+        "api_key_id": api_key_fixture.id,
+        "variables": {},
+        "fallbacks": [{ "type": "model", "id": fallback_model_fixture.id }]
+    });
+    let first = server
+        .post("/api/v1/threads/execute")
+        .json(&input)
+        .await
+        .json::<serde_json::Value>();
+    let second = server
+        .post("/api/v1/threads/execute")
+        .json(&input)
+        .await
+        .json::<serde_json::Value>();
+    primary_mock.assert_hits(2);
+    fallback_mock.assert_hits(2);
+
+    assert_that!(first["cache_hit"], eq(json!(false)));
+    assert_that!(second["cache_hit"], eq(json!(false)));
+    assert_that!(
+        second["parameter"]["model_id"],
+        eq(json!(fallback_model_fixture.id))
+    );
+    assert_that!(second["attempts"].as_array().map(Vec::len), some(eq(2)));
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, EvaluatorCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput,
+    RunCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{
+    EvaluatorKind, ProviderKind, RunItemStatus, RunStatus, UserRole,
+};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const CHAT_COMPLETION_RESPONSE: &str = r#"{
+    "id": "chatcmpl-1",
+    "object": "chat.completion",
+    "created": 1705212532,
+    "model": "gpt-3.5-turbo-0613",
+    "choices": [{
+        "index": 0,
+        "message": { "role": "assistant", "content": "Hello" },
+        "finish_reason": "stop"
+    }],
+    "usage": { "prompt_tokens": 20, "completion_tokens": 10, "total_tokens": 30 }
+}"#;
+
+#[tokio::test]
+async fn test_run_over_rows() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let _server: TestServer;
+    setup!(state, _server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::User,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo is a language model that can generate text from a prompt."
+                .to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    state
+        .evaluation_service
+        .create_evaluator(EvaluatorCreateInput {
+            thread_version_id: thread_version_fixture.id,
+            name: "greets".to_string(),
+            kind: EvaluatorKind::ExactMatch,
+            value: None,
+            threshold: None,
+            weight: 1.0,
+            judge_parameter_id: None,
+            judge_api_key_id: None,
+            judge_max_score: None,
+        })
+        .await?;
+
+    let input = RunCreateInput {
+        thread_version_id: thread_version_fixture.id,
+        parameter_id: parameter_fixture.id,
+        api_key_id: api_key_fixture.id,
+        dataset_id: None,
+        rows: Some(
+            concat!(
+                "{\"variables\":{\"city\":\"Hanoi\"},\"expected_output\":\"Hello\"}\n",
+                "{\"variables\":{\"city\":\"Saigon\"},\"expected_output\":\"Hello\"}\n",
+                "{\"variables\":{\"city\":\"Hue\"},\"expected_output\":\"Goodbye\"}\n",
+            )
+            .to_string(),
+        ),
+        concurrency: 2,
+        tools: vec![],
+        max_tool_iterations: None,
+        retry: None,
+        fallbacks: vec![],
+        bypass_cache: false,
+    };
+    let run_fixture = state
+        .run_service
+        .create(input, auth_fixture.user.id)
+        .await?;
+    assert_that!(run_fixture.total, eq(3));
+    assert_that!(run_fixture.status, eq(RunStatus::Running));
+
+    let mut run = run_fixture.clone();
+    for _ in 0..50 {
+        run = state
+            .run_service
+            .find_by_id(&run_fixture.id)
+            .await?
+            .ok_or(anyhow::anyhow!("Run not found"))?;
+        if run.status != RunStatus::Running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_that!(run.status, eq(RunStatus::Completed));
+    assert_that!(run.succeeded, eq(3));
+    assert_that!(run.failed, eq(0));
+    mock.assert_hits(3);
+
+    let items = state.run_service.find_items(&run_fixture.id).await?;
+    assert_that!(items.len(), eq(3));
+    assert_that!(items[1].variables["city"].as_str(), some(eq("Saigon")));
+    assert_that!(items[1].status, eq(RunItemStatus::Succeeded));
+    assert_that!(items[1].execution_id.is_some(), eq(true));
+
+    let usage = state.run_service.usage(&run_fixture.id).await?;
+    assert_that!(usage.total_tokens, eq(90));
+    assert_that!(usage.cost, some(approx_eq(5.4)));
+    assert_that!(usage.currency, some(eq("USD")));
+
+    let summary = state
+        .evaluation_service
+        .summarize_run(&run_fixture.id)
+        .await?;
+    assert_that!(summary.evaluated, eq(3));
+    assert_that!(summary.passed, eq(2));
+
+    Ok(())
+}
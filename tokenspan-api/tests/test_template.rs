@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+
+use tokenspan_api::prompts::template::{render, TemplateError};
+
+fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_render() -> anyhow::Result<()> {
+    let variables = variables(&[
+        ("user_name2", "  Ada "),
+        ("context", ""),
+        (
+            "examples",
+            r#"[{"q": "2+2", "a": "four"}, {"q": "1+1", "a": "two"}]"#,
+        ),
+        ("profile", r#"{"city": "Hanoi", "tags": ["a", "b"]}"#),
+        ("quote", r#"say "hi""#),
+    ]);
+
+    let cases = [
+        ("Hi ${user_name2 | trim | upper}!", "Hi ADA!"),
+        ("${ missing | default(\"there\") | upper }", "THERE"),
+        ("${user_name2 | default(\"there\") | trim}", "Ada"),
+        ("${#if context}yes${#else}no${/if}", "no"),
+        ("${#if examples}yes${/if}", "yes"),
+        ("${#if missing}yes${/if}", ""),
+        ("${profile.city} ${profile.tags.1}", "Hanoi b"),
+        ("${quote | json}", r#""say \"hi\"""#),
+        ("${examples.0 | json}", r#"{"a":"four","q":"2+2"}"#),
+        ("\\${literal} costs $5", "${literal} costs $5"),
+        (
+            "${#for example in examples}${example.q}=${example.a};${/for}",
+            "2+2=four;1+1=two;",
+        ),
+        (
+            "Examples:\n  ${#for example in examples}\n- ${example.a}\n  ${/for}\nDone",
+            "Examples:\n- four\n- two\nDone",
+        ),
+        ("${#if context}\nA\n${#else}\nB\n${/if}\n", "B\n"),
+    ];
+    for (source, expected) in cases {
+        assert_that!(
+            (source, render(source, &variables)?),
+            eq((source, expected.to_string()))
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_render_errors() -> anyhow::Result<()> {
+    let variables = variables(&[("name", "Ada")]);
+
+    let cases = [
+        ("Hello ${name", "unclosed `${`", 1, 7),
+        (
+            "Hello\n  ${missing}",
+            "variable `missing` is not defined",
+            2,
+            5,
+        ),
+        ("${name | shout}", "unknown filter `shout`", 1, 10),
+        (
+            "${#for x in name}${x}${/for}",
+            "variable `name` is not a list",
+            1,
+            13,
+        ),
+        ("${#if name}\nyes", "unclosed `#if`", 1, 3),
+        ("yes${/if}", "unexpected closing tag", 1, 6),
+        ("${#for x in name}${/if}", "expected `/for`", 1, 20),
+        ("${name ?}", "unexpected character", 1, 8),
+        ("${1st}", "expected a variable name", 1, 3),
+    ];
+    for (source, reason, line, column) in cases {
+        assert_that!(
+            render(source, &variables),
+            err(eq(TemplateError {
+                reason: reason.to_string(),
+                line,
+                column,
+            }))
+        );
+    }
+
+    Ok(())
+}
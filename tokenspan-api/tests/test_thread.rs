@@ -6,7 +6,7 @@ use googletest::prelude::*;
 use graphql_client::{GraphQLQuery, Response};
 
 use tokenspan_api::domains::dto::ThreadCreateInput;
-use tokenspan_api::domains::models::UserRole;
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
 use tokenspan_api::state::AppState;
 
 use crate::graphql::{
@@ -25,6 +25,9 @@ macro_rules! create_model {
             .create(tokenspan_api::domains::dto::ProviderCreateInput {
                 name: "OpenAI".to_string(),
                 slug: "openai".to_string(),
+                base_url: "https://api.openai.com/v1".to_string(),
+                kind: ProviderKind::OpenAI,
+                ..Default::default()
             })
             .await?;
 
@@ -0,0 +1,381 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::json;
+use uuid::Uuid;
+
+use futures::StreamExt;
+use tokenspan_api::adapters::{AnthropicAdapter, ChatRequest, ChatStreamEvent, ProviderAdapter};
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{
+    AuthScheme, ExecutionStatus, Function, Model, Parameter, Pricing, Provider, ProviderKind,
+    Usage, UserRole,
+};
+use tokenspan_api::prompts::{ChatMessage, PromptRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const MESSAGES_RESPONSE: &str = r#"{
+    "id": "msg_01XFDUDYJgAACzvnptvVoYEL",
+    "type": "message",
+    "role": "assistant",
+    "model": "claude-3-haiku-20240307",
+    "content": [
+        { "type": "text", "text": "Let me check the weather." },
+        {
+            "type": "tool_use",
+            "id": "toolu_01A09q90qw90lq917835lq9",
+            "name": "get_weather",
+            "input": { "location": "Hanoi" }
+        }
+    ],
+    "stop_reason": "tool_use",
+    "stop_sequence": null,
+    "usage": { "input_tokens": 36, "output_tokens": 8 }
+}"#;
+
+fn chat_request() -> ChatRequest {
+    let now = Utc::now().naive_utc();
+    let pricing = Pricing {
+        price: 0.25,
+        tokens: 1000000,
+        currency: "USD".to_string(),
+    };
+
+    ChatRequest {
+        model: Model {
+            id: Uuid::new_v4(),
+            name: "claude-3-haiku-20240307".to_string(),
+            description: "Claude 3 Haiku".to_string(),
+            slug: "claude-3-haiku".to_string(),
+            context: 200000,
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+            training_at: now,
+            provider_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        },
+        messages: vec![
+            ChatMessage::new(PromptRole::System, "You are a weather bot.".to_string()),
+            ChatMessage::new(
+                PromptRole::User,
+                "What is the weather in Hanoi?".to_string(),
+            ),
+        ],
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec!["END".to_string()],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        functions: vec![Function {
+            id: Uuid::new_v4(),
+            owner_id: Uuid::new_v4(),
+            name: "get_weather".to_string(),
+            description: "Get the current weather".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "location": { "type": "string" } },
+            }),
+            response: None,
+            created_at: now,
+            updated_at: now,
+        }],
+    }
+}
+
+#[tokio::test]
+async fn test_anthropic_chat_completion() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .header("x-api-key", "sk-ant-123")
+            .header("anthropic-version", "2023-06-01")
+            .json_body(json!({
+                "model": "claude-3-haiku-20240307",
+                "system": "You are a weather bot.",
+                "messages": [{
+                    "role": "user",
+                    "content": [{ "type": "text", "text": "What is the weather in Hanoi?" }],
+                }],
+                "max_tokens": 256,
+                "temperature": 0.5,
+                "top_p": 1.0,
+                "stop_sequences": ["END"],
+                "tools": [{
+                    "name": "get_weather",
+                    "description": "Get the current weather",
+                    "input_schema": {
+                        "type": "object",
+                        "properties": { "location": { "type": "string" } },
+                    },
+                }],
+            }));
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(MESSAGES_RESPONSE);
+    });
+
+    let now = Utc::now().naive_utc();
+    let provider = Provider {
+        id: Uuid::new_v4(),
+        name: "Anthropic".to_string(),
+        slug: "anthropic".to_string(),
+        base_url: format!("{}/v1", mock_server.base_url()),
+        kind: ProviderKind::Anthropic,
+        auth_scheme: AuthScheme::Native,
+        auth_name: None,
+        headers: vec![],
+        query_params: vec![],
+        url_template: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let response = AnthropicAdapter::default()
+        .chat_completion(&provider, "sk-ant-123", &chat_request())
+        .await?;
+    mock.assert();
+
+    assert_that!(
+        response.messages[0].content,
+        eq("Let me check the weather.")
+    );
+    assert_that!(response.tool_calls.len(), eq(1));
+    assert_that!(response.tool_calls[0].name, eq("get_weather"));
+    assert_that!(
+        response.tool_calls[0].arguments,
+        eq(r#"{"location":"Hanoi"}"#)
+    );
+    assert_that!(response.finish_reason, some(eq("tool_use")));
+    assert_that!(
+        response.usage,
+        some(pat!(Usage {
+            input_tokens: eq(36),
+            output_tokens: eq(8),
+            total_tokens: eq(44),
+        }))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_anthropic_chat_completion_stream() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .json_body_partial(r#"{ "stream": true }"#);
+
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(
+                r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-3-haiku-20240307","usage":{"input_tokens":36,"output_tokens":1}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"She did not"}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":" go to the market."}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":0}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":8}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#,
+            );
+    });
+
+    let now = Utc::now().naive_utc();
+    let provider = Provider {
+        id: Uuid::new_v4(),
+        name: "Anthropic".to_string(),
+        slug: "anthropic".to_string(),
+        base_url: format!("{}/v1", mock_server.base_url()),
+        kind: ProviderKind::Anthropic,
+        auth_scheme: AuthScheme::Native,
+        auth_name: None,
+        headers: vec![],
+        query_params: vec![],
+        url_template: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let events = AnthropicAdapter::default()
+        .chat_completion_stream(&provider, "sk-ant-123", &chat_request())
+        .await?
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut content = String::new();
+    let mut usage = None;
+    for event in events {
+        match event? {
+            ChatStreamEvent::Content(delta) => content.push_str(&delta),
+            ChatStreamEvent::Usage(value) => usage = Some(value),
+            _ => {}
+        }
+    }
+
+    assert_that!(content, eq("She did not go to the market."));
+    assert_that!(
+        usage,
+        some(pat!(Usage {
+            input_tokens: eq(36),
+            output_tokens: eq(8),
+            total_tokens: eq(44),
+        }))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_anthropic() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    mock_server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(MESSAGES_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "Anthropic".to_string(),
+            slug: "anthropic".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::Anthropic,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "claude-3-haiku-20240307".to_string(),
+            slug: "claude-3-haiku".to_string(),
+            description: "Claude 3 Haiku".to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "Anthropic".to_string(),
+                key: "sk-ant-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&serde_json::json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "variables": {
+                "sentence": "She did not go to the market."
+            }
+        }))
+        .await;
+    let execution = resp.json::<serde_json::Value>();
+    println!("{:?}", execution);
+
+    assert_that!(
+        execution["status"],
+        eq(json!(ExecutionStatus::Success.to_string()))
+    );
+    assert_that!(execution["usage"]["total_tokens"], eq(json!(44)));
+
+    Ok(())
+}
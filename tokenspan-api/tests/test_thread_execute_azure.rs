@@ -0,0 +1,176 @@
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use uuid::Uuid;
+
+use tokenspan_api::adapters::{ChatRequest, OpenAIAdapter, ProviderAdapter};
+use tokenspan_api::domains::models::{
+    AuthScheme, Model, Parameter, Pricing, Provider, ProviderKind, ProviderParam,
+};
+use tokenspan_api::prompts::{ChatMessage, PromptRole};
+
+const CHAT_COMPLETION_RESPONSE: &str = r#"{
+    "id": "chatcmpl-123",
+    "object": "chat.completion",
+    "created": 1677652288,
+    "model": "gpt-35-turbo",
+    "choices": [{
+        "index": 0,
+        "message": {
+            "role": "assistant",
+            "content": "Hello there, how may I assist you today?"
+        },
+        "finish_reason": "stop"
+    }],
+    "usage": {
+        "prompt_tokens": 9,
+        "completion_tokens": 12,
+        "total_tokens": 21
+    }
+}"#;
+
+fn chat_request() -> ChatRequest {
+    let now = Utc::now().naive_utc();
+    let pricing = Pricing {
+        price: 0.5,
+        tokens: 1000000,
+        currency: "USD".to_string(),
+    };
+
+    ChatRequest {
+        model: Model {
+            id: Uuid::new_v4(),
+            name: "my-deployment".to_string(),
+            description: "Azure deployment".to_string(),
+            slug: "my-deployment".to_string(),
+            context: 16000,
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+            training_at: now,
+            provider_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        },
+        messages: vec![ChatMessage::new(PromptRole::User, "Hello".to_string())],
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec![],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        functions: vec![],
+    }
+}
+
+fn provider(base_url: String) -> Provider {
+    let now = Utc::now().naive_utc();
+    Provider {
+        id: Uuid::new_v4(),
+        name: "Azure OpenAI".to_string(),
+        slug: "azure-openai".to_string(),
+        base_url,
+        kind: ProviderKind::OpenAI,
+        auth_scheme: AuthScheme::Header,
+        auth_name: Some("api-key".to_string()),
+        headers: vec![ProviderParam {
+            name: "x-ms-client-request-id".to_string(),
+            value: "tokenspan".to_string(),
+        }],
+        query_params: vec![ProviderParam {
+            name: "api-version".to_string(),
+            value: "2024-02-01".to_string(),
+        }],
+        url_template: Some("{base_url}/openai/deployments/{model}/{path}".to_string()),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[tokio::test]
+async fn test_azure_chat_completion() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/openai/deployments/my-deployment/chat/completions")
+            .query_param("api-version", "2024-02-01")
+            .header("api-key", "azure-key")
+            .header("x-ms-client-request-id", "tokenspan")
+            .matches(|request| {
+                request
+                    .headers
+                    .as_ref()
+                    .map(|headers| {
+                        headers
+                            .iter()
+                            .all(|(name, _)| !name.eq_ignore_ascii_case("authorization"))
+                    })
+                    .unwrap_or(true)
+            });
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    let response = OpenAIAdapter::default()
+        .chat_completion(
+            &provider(mock_server.base_url()),
+            "azure-key",
+            &chat_request(),
+        )
+        .await?;
+    mock.assert();
+
+    assert_that!(
+        response.messages[0].content,
+        eq("Hello there, how may I assist you today?")
+    );
+    assert_that!(response.usage.map(|usage| usage.total_tokens), some(eq(21)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_auth_scheme() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .query_param("key", "query-key")
+            .header("x-proxy-token", "Token query-key");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    let provider = Provider {
+        auth_scheme: AuthScheme::Query,
+        auth_name: Some("key".to_string()),
+        headers: vec![ProviderParam {
+            name: "x-proxy-token".to_string(),
+            value: "Token {api_key}".to_string(),
+        }],
+        query_params: vec![],
+        url_template: None,
+        ..provider(format!("{}/v1", mock_server.base_url()))
+    };
+    OpenAIAdapter::default()
+        .chat_completion(&provider, "query-key", &chat_request())
+        .await?;
+    mock.assert();
+
+    Ok(())
+}
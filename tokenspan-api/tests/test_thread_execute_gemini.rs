@@ -0,0 +1,400 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::json;
+use uuid::Uuid;
+
+use futures::StreamExt;
+use tokenspan_api::adapters::{ChatRequest, ChatStreamEvent, GeminiAdapter, ProviderAdapter};
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{
+    AuthScheme, ExecutionStatus, Function, Model, Parameter, Pricing, Provider, ProviderKind,
+    Usage, UserRole,
+};
+use tokenspan_api::prompts::{ChatMessage, PromptRole, ToolCall};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const GENERATE_CONTENT_RESPONSE: &str = r#"{
+    "candidates": [{
+        "content": {
+            "role": "model",
+            "parts": [
+                { "text": "Let me check the weather." },
+                { "functionCall": { "name": "get_weather", "args": { "location": "Hanoi" } } }
+            ]
+        },
+        "finishReason": "STOP",
+        "index": 0
+    }],
+    "usageMetadata": {
+        "promptTokenCount": 36,
+        "candidatesTokenCount": 8,
+        "totalTokenCount": 44
+    }
+}"#;
+
+fn chat_request() -> ChatRequest {
+    let now = Utc::now().naive_utc();
+    let pricing = Pricing {
+        price: 0.25,
+        tokens: 1000000,
+        currency: "USD".to_string(),
+    };
+
+    ChatRequest {
+        model: Model {
+            id: Uuid::new_v4(),
+            name: "gemini-1.5-flash".to_string(),
+            description: "Gemini 1.5 Flash".to_string(),
+            slug: "gemini-1.5-flash".to_string(),
+            context: 200000,
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+            training_at: now,
+            provider_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        },
+        messages: vec![
+            ChatMessage::new(PromptRole::System, "You are a weather bot.".to_string()),
+            ChatMessage::new(
+                PromptRole::User,
+                "What is the weather in Hanoi?".to_string(),
+            ),
+        ],
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec!["END".to_string()],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        functions: vec![Function {
+            id: Uuid::new_v4(),
+            owner_id: Uuid::new_v4(),
+            name: "get_weather".to_string(),
+            description: "Get the current weather".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "location": { "type": "string" } },
+            }),
+            response: None,
+            created_at: now,
+            updated_at: now,
+        }],
+    }
+}
+
+#[tokio::test]
+async fn test_gemini_chat_completion() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1beta/models/gemini-1.5-flash:generateContent")
+            .header("x-goog-api-key", "gm-123")
+            .json_body(json!({
+                "systemInstruction": { "parts": [{ "text": "You are a weather bot." }] },
+                "contents": [{
+                    "role": "user",
+                    "parts": [{ "text": "What is the weather in Hanoi?" }],
+                }],
+                "generationConfig": {
+                    "maxOutputTokens": 256,
+                    "temperature": 0.5,
+                    "topP": 1.0,
+                    "stopSequences": ["END"],
+                },
+                "tools": [{
+                    "functionDeclarations": [{
+                        "name": "get_weather",
+                        "description": "Get the current weather",
+                        "parameters": {
+                            "type": "object",
+                            "properties": { "location": { "type": "string" } },
+                        },
+                    }],
+                }],
+            }));
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(GENERATE_CONTENT_RESPONSE);
+    });
+
+    let now = Utc::now().naive_utc();
+    let provider = Provider {
+        id: Uuid::new_v4(),
+        name: "Gemini".to_string(),
+        slug: "gemini".to_string(),
+        base_url: format!("{}/v1beta", mock_server.base_url()),
+        kind: ProviderKind::Gemini,
+        auth_scheme: AuthScheme::Native,
+        auth_name: None,
+        headers: vec![],
+        query_params: vec![],
+        url_template: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let response = GeminiAdapter::default()
+        .chat_completion(&provider, "gm-123", &chat_request())
+        .await?;
+    mock.assert();
+
+    assert_that!(
+        response.messages[0].content,
+        eq("Let me check the weather.")
+    );
+    assert_that!(response.tool_calls.len(), eq(1));
+    assert_that!(response.tool_calls[0].name, eq("get_weather"));
+    assert_that!(response.tool_calls[0].id, starts_with("call_"));
+    assert_that!(
+        response.tool_calls[0].arguments,
+        eq(r#"{"location":"Hanoi"}"#)
+    );
+    assert_that!(response.finish_reason, some(eq("STOP")));
+    assert_that!(
+        response.usage,
+        some(pat!(Usage {
+            input_tokens: eq(36),
+            output_tokens: eq(8),
+            total_tokens: eq(44),
+        }))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gemini_function_response_name() -> anyhow::Result<()> {
+    // Two rounds that reused the same call id, as older executions did.
+    let tool_call = |name: &str| ChatMessage {
+        tool_calls: vec![ToolCall {
+            id: "call_0".to_string(),
+            name: name.to_string(),
+            arguments: "{}".to_string(),
+        }],
+        ..ChatMessage::assistant(String::new())
+    };
+    let mut request = chat_request();
+    request.messages.extend([
+        tool_call("get_weather"),
+        ChatMessage::tool("call_0".to_string(), "sunny".to_string()),
+        tool_call("get_time"),
+        ChatMessage::tool("call_0".to_string(), "noon".to_string()),
+    ]);
+
+    let body = GeminiAdapter::build_request(&request);
+    let names = body["contents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|content| content["parts"].as_array().unwrap())
+        .filter_map(|part| part["functionResponse"]["name"].as_str())
+        .collect::<Vec<_>>();
+    assert_that!(names, elements_are![eq("get_weather"), eq("get_time")]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gemini_chat_completion_stream() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1beta/models/gemini-1.5-flash:streamGenerateContent")
+            .query_param("alt", "sse");
+
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(
+                r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"She did not"}]},"index":0}],"usageMetadata":{"promptTokenCount":36,"candidatesTokenCount":3,"totalTokenCount":39}}
+
+data: {"candidates":[{"content":{"role":"model","parts":[{"text":" go to the market."}]},"finishReason":"STOP","index":0}],"usageMetadata":{"promptTokenCount":36,"candidatesTokenCount":8,"totalTokenCount":44}}
+
+"#,
+            );
+    });
+
+    let now = Utc::now().naive_utc();
+    let provider = Provider {
+        id: Uuid::new_v4(),
+        name: "Gemini".to_string(),
+        slug: "gemini".to_string(),
+        base_url: format!("{}/v1beta", mock_server.base_url()),
+        kind: ProviderKind::Gemini,
+        auth_scheme: AuthScheme::Native,
+        auth_name: None,
+        headers: vec![],
+        query_params: vec![],
+        url_template: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let events = GeminiAdapter::default()
+        .chat_completion_stream(&provider, "gm-123", &chat_request())
+        .await?
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut content = String::new();
+    let mut usage = None;
+    for event in events {
+        match event? {
+            ChatStreamEvent::Content(delta) => content.push_str(&delta),
+            ChatStreamEvent::Usage(value) => usage = Some(value),
+            _ => {}
+        }
+    }
+
+    assert_that!(content, eq("She did not go to the market."));
+    assert_that!(
+        usage,
+        some(pat!(Usage {
+            input_tokens: eq(36),
+            output_tokens: eq(8),
+            total_tokens: eq(44),
+        }))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_gemini() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1beta/models/gemini-1.5-flash:generateContent");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(GENERATE_CONTENT_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "Gemini".to_string(),
+            slug: "gemini".to_string(),
+            base_url: format!("{}/v1beta", mock_server.base_url()),
+            kind: ProviderKind::Gemini,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gemini-1.5-flash".to_string(),
+            slug: "gemini-1.5-flash".to_string(),
+            description: "Gemini 1.5 Flash".to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "Gemini".to_string(),
+                key: "gm-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&serde_json::json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "variables": {
+                "sentence": "She did not go to the market."
+            }
+        }))
+        .await;
+    let execution = resp.json::<serde_json::Value>();
+    println!("{:?}", execution);
+
+    assert_that!(
+        execution["status"],
+        eq(json!(ExecutionStatus::Success.to_string()))
+    );
+    assert_that!(execution["usage"]["total_tokens"], eq(json!(44)));
+
+    Ok(())
+}
@@ -0,0 +1,363 @@
+use std::time::Duration;
+
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::json;
+use uuid::Uuid;
+
+use tokenspan_api::adapters::{
+    ChatRequest, OpenAIAdapter, ProviderAdapter, ProviderError, RetryPolicy, MAX_RETRY_ATTEMPTS,
+    RETRY_WINDOW,
+};
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{Model, Parameter, Pricing, Provider, ProviderKind, UserRole};
+use tokenspan_api::prompts::{ChatMessage, PromptRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const CHAT_COMPLETION_RESPONSE: &str = r#"{
+    "id": "chatcmpl-1",
+    "object": "chat.completion",
+    "created": 1705212532,
+    "model": "gpt-3.5-turbo-0613",
+    "choices": [{
+        "index": 0,
+        "message": { "role": "assistant", "content": "Hello from the fallback" },
+        "finish_reason": "stop"
+    }],
+    "usage": { "prompt_tokens": 20, "completion_tokens": 10, "total_tokens": 30 }
+}"#;
+
+#[tokio::test]
+async fn test_provider_error_retry_after() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+
+        then.status(429)
+            .header("content-type", "application/json")
+            .header("retry-after", "2")
+            .body(r#"{"error":{"message":"Rate limit reached"}}"#);
+    });
+
+    let now = Utc::now().naive_utc();
+    let pricing = Pricing {
+        price: 0.5,
+        tokens: 1000000,
+        currency: "USD".to_string(),
+    };
+    let request = ChatRequest {
+        model: Model {
+            id: Uuid::new_v4(),
+            name: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            context: 16000,
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+            training_at: now,
+            provider_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        },
+        messages: vec![ChatMessage::new(PromptRole::User, "Hello".to_string())],
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec![],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        functions: vec![],
+    };
+    let provider = Provider {
+        id: Uuid::new_v4(),
+        name: "OpenAI".to_string(),
+        slug: "openai".to_string(),
+        base_url: format!("{}/v1", mock_server.base_url()),
+        kind: ProviderKind::OpenAI,
+        auth_scheme: Default::default(),
+        auth_name: None,
+        headers: vec![],
+        query_params: vec![],
+        url_template: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let error = OpenAIAdapter::default()
+        .chat_completion(&provider, "sk-123", &request)
+        .await
+        .expect_err("rate limited request should fail");
+    mock.assert();
+
+    let provider_error = error
+        .downcast_ref::<ProviderError>()
+        .expect("provider error");
+    assert_that!(provider_error.status(), eq(429));
+    assert_that!(
+        provider_error.retry_after(),
+        some(eq(Duration::from_secs(2)))
+    );
+
+    assert_that!(RetryPolicy::default().backoff(1, &error), none());
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        jitter: false,
+        ..Default::default()
+    };
+    assert_that!(policy.backoff(1, &error), some(eq(Duration::from_secs(2))));
+    let policy = RetryPolicy {
+        max_backoff_ms: 1000,
+        ..policy
+    };
+    assert_that!(policy.backoff(1, &error), none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_policy_validate() -> anyhow::Result<()> {
+    assert_that!(RetryPolicy::default().validate(), ok(anything()));
+
+    let window_ms = RETRY_WINDOW.as_millis() as i64;
+    for policy in [
+        RetryPolicy {
+            max_attempts: 0,
+            ..Default::default()
+        },
+        RetryPolicy {
+            max_attempts: MAX_RETRY_ATTEMPTS + 1,
+            ..Default::default()
+        },
+        RetryPolicy {
+            max_backoff_ms: window_ms + 1,
+            ..Default::default()
+        },
+        RetryPolicy {
+            initial_backoff_ms: -1,
+            ..Default::default()
+        },
+        RetryPolicy {
+            multiplier: 0.5,
+            ..Default::default()
+        },
+    ] {
+        assert_that!(policy.validate(), err(anything()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_fallback() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let primary_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(json!({ "model": "gpt-4" }).to_string());
+
+        then.status(503)
+            .header("content-type", "application/json")
+            .body(r#"{"error":{"message":"The server is overloaded"}}"#);
+    });
+    let fallback_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(json!({ "model": "gpt-3.5-turbo" }).to_string());
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(CHAT_COMPLETION_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    let pricing = PricingInput {
+        currency: "USD".to_string(),
+        price: 0.06,
+        tokens: 1,
+    };
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-4".to_string(),
+            slug: "gpt-4".to_string(),
+            description: "GPT-4".to_string(),
+            provider_id: provider_fixture.id,
+            context: 8192,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: pricing.clone(),
+            output_pricing: pricing.clone(),
+        })
+        .await?;
+    let fallback_model_fixture = state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 16000,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "variables": {},
+            "retry": { "max_attempts": 2, "initial_backoff_ms": 1 },
+            "fallbacks": [{ "type": "model", "id": fallback_model_fixture.id }]
+        }))
+        .await;
+    let body = resp.json::<serde_json::Value>();
+    primary_mock.assert_hits(2);
+    fallback_mock.assert();
+
+    assert_that!(body["status"], eq(json!("success")));
+    assert_that!(
+        body["parameter"]["model_id"],
+        eq(json!(fallback_model_fixture.id))
+    );
+    let attempts = body["attempts"].as_array().cloned().unwrap_or_default();
+    assert_that!(attempts.len(), eq(3));
+    assert_that!(attempts[0]["status_code"], eq(json!(503)));
+    assert_that!(attempts[1]["attempt"], eq(json!(2)));
+    assert_that!(
+        attempts[2]["model_id"],
+        eq(json!(fallback_model_fixture.id))
+    );
+    assert_that!(attempts[2]["error"], eq(json!(null)));
+
+    // A stream the provider refuses keeps its status code.
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "stream": true,
+            "variables": {}
+        }))
+        .await;
+    primary_mock.assert_hits(3);
+    let body = resp.text();
+    let data = body
+        .split("event: completed")
+        .nth(1)
+        .and_then(|event| event.lines().find_map(|line| line.strip_prefix("data: ")))
+        .ok_or(anyhow::anyhow!("Completed event not found"))?;
+    let execution = serde_json::from_str::<serde_json::Value>(data)?;
+    assert_that!(execution["status"], eq(json!("failed")));
+    assert_that!(execution["attempts"][0]["status_code"], eq(json!(503)));
+
+    // Streamed executions are not retried, so they refuse a policy or fallbacks.
+    for (retry, fallbacks) in [
+        (json!({ "max_attempts": 2 }), json!([])),
+        (
+            json!(null),
+            json!([{ "type": "model", "id": fallback_model_fixture.id }]),
+        ),
+    ] {
+        let resp = server
+            .post("/api/v1/threads/execute")
+            .json(&json!({
+                "thread_version_id": thread_version_fixture.id,
+                "parameter_id": parameter_fixture.id,
+                "api_key_id": api_key_fixture.id,
+                "stream": true,
+                "variables": {},
+                "retry": retry,
+                "fallbacks": fallbacks
+            }))
+            .await;
+        resp.assert_status(axum_test::http::StatusCode::BAD_REQUEST);
+    }
+    primary_mock.assert_hits(3);
+
+    Ok(())
+}
@@ -0,0 +1,547 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::json;
+use uuid::Uuid;
+
+use tokenspan_api::adapters::{ChatRequest, OpenAIAdapter, ProviderAdapter};
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, FunctionCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput,
+    ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{Model, Parameter, Pricing, Provider, ProviderKind, UserRole};
+use tokenspan_api::prompts::{ChatMessage, PromptRole, ToolCall};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const TOOL_CALL_RESPONSE: &str = r#"{
+    "id": "chatcmpl-1",
+    "object": "chat.completion",
+    "created": 1705212532,
+    "model": "gpt-3.5-turbo-0613",
+    "choices": [{
+        "index": 0,
+        "message": {
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_abc",
+                "type": "function",
+                "function": { "name": "get_weather", "arguments": "{\"location\":\"Hanoi\"}" }
+            }]
+        },
+        "finish_reason": "tool_calls"
+    }],
+    "usage": { "prompt_tokens": 20, "completion_tokens": 10, "total_tokens": 30 }
+}"#;
+
+const FINAL_RESPONSE: &str = r#"{
+    "id": "chatcmpl-2",
+    "object": "chat.completion",
+    "created": 1705212533,
+    "model": "gpt-3.5-turbo-0613",
+    "choices": [{
+        "index": 0,
+        "message": { "role": "assistant", "content": "It is sunny in Hanoi." },
+        "finish_reason": "stop"
+    }],
+    "usage": { "prompt_tokens": 40, "completion_tokens": 6, "total_tokens": 46 }
+}"#;
+
+#[tokio::test]
+async fn test_openai_tool_messages() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(
+            json!({
+                "messages": [
+                    { "role": "user", "content": "What is the weather in Hanoi?", "name": "linh" },
+                    {
+                        "role": "assistant",
+                        "tool_calls": [{
+                            "id": "call_abc",
+                            "type": "function",
+                            "function": {
+                                "name": "get_weather",
+                                "arguments": "{\"location\":\"Hanoi\"}"
+                            }
+                        }]
+                    },
+                    { "role": "tool", "content": "sunny", "tool_call_id": "call_abc" }
+                ]
+            })
+            .to_string(),
+        );
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(FINAL_RESPONSE);
+    });
+
+    let now = Utc::now().naive_utc();
+    let pricing = Pricing {
+        price: 0.5,
+        tokens: 1000000,
+        currency: "USD".to_string(),
+    };
+    let request = ChatRequest {
+        model: Model {
+            id: Uuid::new_v4(),
+            name: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            context: 16000,
+            input_pricing: pricing.clone(),
+            output_pricing: pricing,
+            training_at: now,
+            provider_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        },
+        messages: vec![
+            ChatMessage {
+                name: Some("linh".to_string()),
+                ..ChatMessage::new(
+                    PromptRole::User,
+                    "What is the weather in Hanoi?".to_string(),
+                )
+            },
+            ChatMessage {
+                tool_calls: vec![ToolCall {
+                    id: "call_abc".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: r#"{"location":"Hanoi"}"#.to_string(),
+                }],
+                ..ChatMessage::assistant(String::new())
+            },
+            ChatMessage::tool("call_abc".to_string(), "sunny".to_string()),
+        ],
+        parameter: Parameter {
+            id: Uuid::new_v4(),
+            name: "default".to_string(),
+            temperature: 0.5,
+            max_tokens: 256,
+            stop_sequences: vec![],
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
+            extra: None,
+            model_id: Uuid::new_v4(),
+            thread_version_id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            is_default: true,
+            origin_id: None,
+        },
+        functions: vec![],
+    };
+    let provider = Provider {
+        id: Uuid::new_v4(),
+        name: "OpenAI".to_string(),
+        slug: "openai".to_string(),
+        base_url: format!("{}/v1", mock_server.base_url()),
+        kind: ProviderKind::OpenAI,
+        auth_scheme: Default::default(),
+        auth_name: None,
+        headers: vec![],
+        query_params: vec![],
+        url_template: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let response = OpenAIAdapter::default()
+        .chat_completion(&provider, "sk-123", &request)
+        .await?;
+    mock.assert();
+
+    assert_that!(response.messages[0].content, eq("It is sunny in Hanoi."));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_tool_loop() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let tool_call_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .matches(|request| {
+                let body = String::from_utf8_lossy(request.body.as_deref().unwrap_or_default());
+                !body.contains(r#""role":"tool""#)
+            });
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+    let final_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""tool_call_id":"call_abc""#)
+            .body_contains(r#"{\"temperature\":31}"#);
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(FINAL_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo is a language model that can generate text from a prompt."
+                .to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let function_fixture = state
+        .function_service
+        .create(
+            FunctionCreateInput {
+                name: "get_weather".to_string(),
+                description: "Get the current weather".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "location": { "type": "string" } },
+                }),
+                response: Some(json!({ "temperature": 31 })),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "tools": [{ "type": "function", "id": function_fixture.id }],
+            "max_tool_iterations": 3,
+            "variables": {}
+        }))
+        .await;
+    let body = resp.json::<serde_json::Value>();
+    tool_call_mock.assert();
+    final_mock.assert();
+
+    assert_that!(body["status"], eq(json!("success")));
+    assert_that!(body["usage"]["total_tokens"], eq(json!(76)));
+    assert_that!(body["cost"]["currency"], eq(json!("USD")));
+    assert_that!(body["cost"]["total"].as_f64(), some(approx_eq(4.56)));
+    let output_messages = body["output_messages"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    assert_that!(output_messages.len(), eq(3));
+    assert_that!(output_messages[0]["role"], eq(json!("assistant")));
+    assert_that!(
+        output_messages[0]["tool_calls"][0]["name"],
+        eq(json!("get_weather"))
+    );
+    assert_that!(output_messages[1]["tool_call_id"], eq(json!("call_abc")));
+    assert_that!(
+        output_messages[1]["content"],
+        eq(json!(r#"{"temperature":31}"#))
+    );
+    assert_that!(
+        output_messages[2]["content"],
+        eq(json!("It is sunny in Hanoi."))
+    );
+
+    Ok(())
+}
+
+const TOOL_CALL_STREAM: &str = r#"data: {"id":"chatcmpl-3","object":"chat.completion.chunk","created":1705212534,"model":"gpt-3.5-turbo-0613","choices":[{"index":0,"delta":{"role":"assistant","content":null,"tool_calls":[{"index":0,"id":"call_def","type":"function","function":{"name":"get_weather","arguments":""}}]},"finish_reason":null}]}
+
+data: {"id":"chatcmpl-3","object":"chat.completion.chunk","created":1705212534,"model":"gpt-3.5-turbo-0613","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"location\":\"Hanoi\"}"}}]},"finish_reason":"tool_calls"}]}
+
+data: [DONE]
+
+"#;
+
+#[tokio::test]
+async fn test_thread_execute_tool_call_reply() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let stream_mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(r#"{ "stream": true }"#);
+
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(TOOL_CALL_STREAM);
+    });
+    mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .matches(|request| {
+                let body = String::from_utf8_lossy(request.body.as_deref().unwrap_or_default());
+                !body.contains(r#""stream":true"#)
+            });
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(state, server);
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo is a language model that can generate text from a prompt."
+                .to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    // Without a tool loop, the calls are saved as the answer.
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "bypass_cache": true,
+            "variables": {}
+        }))
+        .await;
+    let body = resp.json::<serde_json::Value>();
+    let execution_id = body["id"]
+        .as_str()
+        .ok_or(anyhow::anyhow!("Execution id not found"))?
+        .parse::<Uuid>()?;
+
+    let execution = state
+        .execution_service
+        .find_by_id(&execution_id)
+        .await?
+        .ok_or(anyhow::anyhow!("Execution not found"))?;
+    assert_that!(execution.output_messages.len(), eq(1));
+    assert_that!(execution.output_messages[0].role, eq("assistant"));
+    assert_that!(
+        execution.output_messages[0].tool_calls,
+        elements_are![eq(ToolCall {
+            id: "call_abc".to_string(),
+            name: "get_weather".to_string(),
+            arguments: r#"{"location":"Hanoi"}"#.to_string(),
+        })]
+    );
+
+    // The streamed calls are put back together the same way.
+    let stream_execution = |body: String| -> anyhow::Result<Uuid> {
+        let data = body
+            .split("event: completed")
+            .nth(1)
+            .and_then(|event| event.lines().find_map(|line| line.strip_prefix("data: ")))
+            .ok_or(anyhow::anyhow!("Completed event not found"))?;
+        let execution = serde_json::from_str::<serde_json::Value>(data)?;
+        Ok(execution["id"]
+            .as_str()
+            .ok_or(anyhow::anyhow!("Execution id not found"))?
+            .parse::<Uuid>()?)
+    };
+    let stream_input = json!({
+        "thread_version_id": thread_version_fixture.id,
+        "parameter_id": parameter_fixture.id,
+        "api_key_id": api_key_fixture.id,
+        "stream": true,
+        "variables": {}
+    });
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&stream_input)
+        .await;
+    stream_mock.assert();
+    let execution = state
+        .execution_service
+        .find_by_id(&stream_execution(resp.text())?)
+        .await?
+        .ok_or(anyhow::anyhow!("Execution not found"))?;
+    assert_that!(
+        execution.output_messages[0].tool_calls,
+        elements_are![eq(ToolCall {
+            id: "call_def".to_string(),
+            name: "get_weather".to_string(),
+            arguments: r#"{"location":"Hanoi"}"#.to_string(),
+        })]
+    );
+
+    // Streamed executions do not run the tool loop, so they refuse to be asked to.
+    let resp = server
+        .post("/api/v1/threads/execute")
+        .json(&json!({
+            "thread_version_id": thread_version_fixture.id,
+            "parameter_id": parameter_fixture.id,
+            "api_key_id": api_key_fixture.id,
+            "stream": true,
+            "max_tool_iterations": 2,
+            "variables": {}
+        }))
+        .await;
+    resp.assert_status(axum_test::http::StatusCode::BAD_REQUEST);
+    stream_mock.assert();
+
+    Ok(())
+}
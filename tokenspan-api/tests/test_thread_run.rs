@@ -0,0 +1,312 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, ApiKeyUpdateInput, ModelCreateInput, ParameterCreateInput,
+    ParameterUpdateInput, PricingInput, ProviderCreateInput, ThreadCreateInput, ThreadRunInput,
+    ThreadVersionPublishInput,
+};
+use tokenspan_api::domains::errors::ThreadError;
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+fn run_input(semver: Option<&str>, label: Option<&str>) -> ThreadRunInput {
+    ThreadRunInput {
+        semver: semver.map(ToString::to_string),
+        label: label.map(ToString::to_string),
+        environment: None,
+        subject: None,
+        tools: vec![],
+        variables: Default::default(),
+        max_tool_iterations: None,
+        retry: None,
+        fallbacks: vec![],
+        bypass_cache: false,
+        expected_output: None,
+        stream: false,
+    }
+}
+
+#[tokio::test]
+async fn test_thread_run() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    mock_server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1705212532,
+                "model": "gpt-3.5-turbo-0613",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "Hello" },
+                    "logprobs": null,
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6 },
+                "system_fingerprint": null
+            }"#,
+            );
+    });
+
+    // Setup
+    let state: AppState;
+    let _server: TestServer;
+    setup!(state, _server);
+
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "greeter".to_string(),
+                slug: "greeter".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let published_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+    let draft_fixture = state
+        .thread_version_service
+        .publish(
+            &published_fixture.id,
+            ThreadVersionPublishInput {
+                semver: Some("0.1.0".to_string()),
+                bump: None,
+                release_note: "first".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    state
+        .thread_version_service
+        .set_label(
+            &published_fixture.id,
+            "stable".to_string(),
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_default(&published_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(api_key_fixture.is_default, eq(true));
+
+    // Latest published, by semver, by range and by label all select the published
+    // version.
+    for input in [
+        run_input(None, None),
+        run_input(None, Some("latest")),
+        run_input(Some("0.0.0"), None),
+        run_input(Some("^0"), None),
+        run_input(None, Some("stable")),
+    ] {
+        let resolved = state
+            .thread_service
+            .resolve_run("greeter".to_string(), input, auth_fixture.user.id)
+            .await?;
+        assert_that!(resolved.thread_version_id, eq(published_fixture.id));
+        assert_that!(resolved.parameter_id, eq(parameter_fixture.id));
+        assert_that!(resolved.api_key_id, eq(api_key_fixture.id));
+    }
+
+    // Drafts, unknown labels and unknown threads are not found.
+    for (slug, input) in [
+        ("greeter", run_input(Some(&draft_fixture.semver), None)),
+        ("greeter", run_input(None, Some("canary"))),
+        ("unknown", run_input(None, None)),
+    ] {
+        let error = state
+            .thread_service
+            .resolve_run(slug.to_string(), input, auth_fixture.user.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ThreadError::from_execution(error),
+            ThreadError::NotFound(_)
+        ));
+    }
+
+    let error = state
+        .thread_service
+        .resolve_run(
+            "greeter".to_string(),
+            run_input(Some("banana"), None),
+            auth_fixture.user.id,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        ThreadError::from_execution(error),
+        ThreadError::InvalidInput(_)
+    ));
+
+    // A key made the default takes over from the first one.
+    let second_api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI 2".to_string(),
+                key: "sk-456".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(second_api_key_fixture.is_default, eq(false));
+    state
+        .api_key_service
+        .update_by_id(
+            &second_api_key_fixture.id,
+            ApiKeyUpdateInput {
+                name: None,
+                is_default: Some(true),
+            },
+        )
+        .await?;
+
+    let resolved = state
+        .thread_service
+        .resolve_run(
+            "greeter".to_string(),
+            run_input(None, Some("stable")),
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(resolved.api_key_id, eq(second_api_key_fixture.id));
+
+    let execution = state
+        .thread_service
+        .execute(resolved, auth_fixture.user.id)
+        .await?;
+    assert_that!(execution.thread_version_id, eq(published_fixture.id));
+    assert_that!(execution.api_key_id, some(eq(second_api_key_fixture.id)));
+
+    // A version has one default parameter; making another the default moves it.
+    let draft_parameter_fixture = state
+        .parameter_service
+        .find_default(&draft_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+    let second_parameter_fixture = state
+        .parameter_service
+        .create(
+            ParameterCreateInput::builder()
+                .model_id(draft_parameter_fixture.model_id)
+                .thread_version_id(draft_fixture.id)
+                .is_default(true)
+                .build(),
+        )
+        .await?;
+    let default = state
+        .parameter_service
+        .find_default(&draft_fixture.id)
+        .await?;
+    assert_that!(
+        default.map(|parameter| parameter.id),
+        some(eq(second_parameter_fixture.id))
+    );
+
+    state
+        .parameter_service
+        .update_by_id(
+            &draft_parameter_fixture.id,
+            ParameterUpdateInput {
+                name: None,
+                temperature: None,
+                max_tokens: None,
+                stop_sequences: None,
+                top_p: None,
+                frequency_penalty: None,
+                presence_penalty: None,
+                extra: None,
+                is_default: Some(true),
+                model_id: None,
+            },
+        )
+        .await?;
+    let parameters = state
+        .parameter_service
+        .find_by_thread_version_id(&draft_fixture.id)
+        .await?;
+    assert_that!(
+        parameters
+            .iter()
+            .filter(|parameter| parameter.is_default)
+            .map(|parameter| parameter.id)
+            .collect::<Vec<_>>(),
+        elements_are![eq(draft_parameter_fixture.id)]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use axum_test::{TestServer, TestServerConfig};
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use googletest::prelude::*;
+use httpmock::prelude::*;
+use httpmock::MockServer;
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, ModelCreateInput, PricingInput, ProviderCreateInput, ThreadCreateInput,
+};
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+const EXECUTE_SUBSCRIPTION: &str = r#"
+subscription Execute($input: ThreadExecuteInput!) {
+    execute(input: $input) {
+        __typename
+        ... on ExecutionDelta { content }
+        ... on Execution { id status }
+    }
+}
+"#;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Opens `/ws` with the graphql-transport-ws protocol and sends `connection_init`
+/// with `payload`, waiting for the server's ack.
+async fn connect(server: &TestServer, payload: Value) -> anyhow::Result<Socket> {
+    let address = server
+        .server_address()
+        .ok_or(anyhow::anyhow!("server has no address"))?;
+    let mut request = format!(
+        "ws://{}:{}/ws",
+        address.host_str().unwrap_or("127.0.0.1"),
+        address.port().unwrap_or(80)
+    )
+    .into_client_request()?;
+    request.headers_mut().insert(
+        "sec-websocket-protocol",
+        HeaderValue::from_static("graphql-transport-ws"),
+    );
+    let (mut socket, _) = connect_async(request).await?;
+
+    socket
+        .send(Message::Text(
+            json!({ "type": "connection_init", "payload": payload }).to_string(),
+        ))
+        .await?;
+    let ack = receive(&mut socket).await?;
+    assert_that!(ack["type"], eq(json!("connection_ack")));
+
+    Ok(socket)
+}
+
+async fn receive(socket: &mut Socket) -> anyhow::Result<Value> {
+    while let Some(message) = socket.next().await {
+        if let Message::Text(text) = message? {
+            return Ok(serde_json::from_str(&text)?);
+        }
+    }
+
+    Err(anyhow::anyhow!("socket closed"))
+}
+
+/// Subscribes with `variables` and collects the `next` payloads until `complete`.
+async fn subscribe(socket: &mut Socket, variables: Value) -> anyhow::Result<Vec<Value>> {
+    socket
+        .send(Message::Text(
+            json!({
+                "id": "1",
+                "type": "subscribe",
+                "payload": { "query": EXECUTE_SUBSCRIPTION, "variables": variables }
+            })
+            .to_string(),
+        ))
+        .await?;
+
+    let mut payloads = vec![];
+    loop {
+        let message = receive(socket).await?;
+        match message["type"].as_str() {
+            Some("next") => payloads.push(message["payload"].clone()),
+            Some("complete") => return Ok(payloads),
+            _ => return Err(anyhow::anyhow!("unexpected message {}", message)),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_thread_execute_subscription() -> anyhow::Result<()> {
+    let mock_server = MockServer::start();
+    let mock = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(r#"{ "stream": true }"#);
+
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(
+                r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","created":1705212532,"model":"gpt-3.5-turbo-0613","choices":[{"index":0,"delta":{"role":"assistant","content":"She did not"},"logprobs":null,"finish_reason":null}]}
+
+data: {"id":"chatcmpl-1","object":"chat.completion.chunk","created":1705212532,"model":"gpt-3.5-turbo-0613","choices":[{"index":0,"delta":{"content":" go to the market."},"logprobs":null,"finish_reason":"stop"}]}
+
+data: {"id":"chatcmpl-1","object":"chat.completion.chunk","created":1705212532,"model":"gpt-3.5-turbo-0613","choices":[],"usage":{"prompt_tokens":36,"completion_tokens":8,"total_tokens":44}}
+
+data: [DONE]
+
+"#,
+            );
+    });
+
+    // Setup
+    let state: AppState;
+    let server: TestServer;
+    setup!(
+        state,
+        server,
+        TestServerConfig::builder().http_transport().build()
+    );
+
+    // Create new user
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            base_url: format!("{}/v1", mock_server.base_url()),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo is a language model that can generate text from a prompt."
+                .to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "thread".to_string(),
+                slug: "thread".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_version_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+
+    let parameter_fixture = state
+        .parameter_service
+        .find_by_thread_version_id(&thread_version_fixture.id)
+        .await?
+        .first()
+        .cloned()
+        .ok_or(anyhow::anyhow!("Parameter not found"))?;
+
+    let api_key_fixture = state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let variables = json!({
+        "input": {
+            "threadVersionId": thread_version_fixture.id,
+            "parameterId": parameter_fixture.id,
+            "apiKeyId": api_key_fixture.id,
+            "variables": {}
+        }
+    });
+
+    // The token is sent in the connection_init payload, as browsers do.
+    let mut socket = connect(
+        &server,
+        json!({ "Authorization": format!("Bearer {}", auth_fixture.token) }),
+    )
+    .await?;
+    let payloads = subscribe(&mut socket, variables).await?;
+    mock.assert();
+
+    let events = payloads
+        .iter()
+        .map(|payload| payload["data"]["execute"].clone())
+        .collect::<Vec<_>>();
+    let content = events
+        .iter()
+        .filter_map(|event| event["content"].as_str())
+        .collect::<String>();
+    assert_that!(content, eq("She did not go to the market."));
+    let completed = events.last().ok_or(anyhow::anyhow!("no events"))?;
+    assert_that!(completed["__typename"], eq(json!("Execution")));
+    assert_that!(completed["status"], eq(json!("SUCCESS")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_execute_subscription_without_token() -> anyhow::Result<()> {
+    // Setup
+    let _state: AppState;
+    let server: TestServer;
+    setup!(
+        _state,
+        server,
+        TestServerConfig::builder().http_transport().build()
+    );
+
+    let variables = json!({
+        "input": {
+            "threadVersionId": Uuid::new_v4(),
+            "parameterId": Uuid::new_v4(),
+            "apiKeyId": Uuid::new_v4(),
+            "variables": {}
+        }
+    });
+
+    // Without a token, or with one that does not decode, the subscription is refused.
+    for payload in [json!({}), json!({ "Authorization": "Bearer invalid" })] {
+        let mut socket = connect(&server, payload).await?;
+        let payloads = subscribe(&mut socket, variables.clone()).await?;
+        assert_that!(payloads.len(), eq(1));
+        assert_that!(payloads[0]["data"], eq(json!(null)));
+        assert_that!(payloads[0]["errors"][0]["message"], eq(json!("Forbidden")));
+    }
+
+    Ok(())
+}
@@ -3,7 +3,7 @@ use axum_test::TestServer;
 use graphql_client::{GraphQLQuery, Response};
 
 use crate::graphql::{publish_thread_version_mutation, PublishThreadVersionMutation};
-use tokenspan_api::domains::models::UserRole;
+use tokenspan_api::domains::models::{ProviderKind, UserRole};
 use tokenspan_api::state::AppState;
 
 mod common;
@@ -16,6 +16,9 @@ macro_rules! create_model {
             .create(tokenspan_api::domains::dto::ProviderCreateInput {
                 name: "OpenAI".to_string(),
                 slug: "openai".to_string(),
+                base_url: "https://api.openai.com/v1".to_string(),
+                kind: ProviderKind::OpenAI,
+                ..Default::default()
             })
             .await?;
 
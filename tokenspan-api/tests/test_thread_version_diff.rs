@@ -0,0 +1,218 @@
+use chrono::Utc;
+use googletest::prelude::*;
+use uuid::Uuid;
+
+use tokenspan_api::domains::models::{
+    DiffChangeKind, DiffLine, DiffTag, FieldChange, Message, Parameter,
+};
+use tokenspan_api::domains::services::{diff_messages, diff_parameters, diff_text};
+
+fn message(index: i32, role: &str, content: &str) -> Message {
+    Message {
+        id: Uuid::new_v4(),
+        thread_version_id: Uuid::new_v4(),
+        owner_id: Uuid::new_v4(),
+        raw: content.to_string(),
+        content: content.to_string(),
+        role: role.to_string(),
+        index,
+        name: None,
+        tool_call_id: None,
+        tool_calls: vec![],
+        created_at: Utc::now().naive_utc(),
+        updated_at: Utc::now().naive_utc(),
+    }
+}
+
+fn parameter(name: &str, temperature: f32) -> Parameter {
+    Parameter {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        temperature,
+        max_tokens: 256,
+        stop_sequences: vec![],
+        top_p: 1.0,
+        frequency_penalty: 0.0,
+        presence_penalty: 0.0,
+        extra: None,
+        model_id: Uuid::nil(),
+        thread_version_id: Uuid::new_v4(),
+        created_at: Utc::now().naive_utc(),
+        updated_at: Utc::now().naive_utc(),
+        is_default: false,
+        origin_id: None,
+    }
+}
+
+fn line(tag: DiffTag, old_line: Option<i32>, new_line: Option<i32>, content: &str) -> DiffLine {
+    DiffLine {
+        tag,
+        old_line,
+        new_line,
+        content: content.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_diff_text() -> anyhow::Result<()> {
+    let diff = diff_text("hello\nworld\n", "hello\nthere\n");
+    assert_that!(
+        diff,
+        eq(vec![
+            line(DiffTag::Equal, Some(1), Some(1), "hello"),
+            line(DiffTag::Delete, Some(2), None, "world"),
+            line(DiffTag::Insert, None, Some(2), "there"),
+        ])
+    );
+
+    assert_that!(diff_text("same", "same").len(), eq(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_diff_messages() -> anyhow::Result<()> {
+    let old = vec![
+        message(0, "system", "You are helpful."),
+        message(1, "user", "Hi"),
+        message(2, "assistant", "Hello!"),
+    ];
+
+    // Nothing changed.
+    assert_that!(diff_messages(&old, &old), empty());
+
+    // A message inserted at the top shifts the others without changing them.
+    let new = vec![
+        message(0, "system", "Be brief."),
+        message(1, "system", "You are helpful."),
+        message(2, "user", "Hi"),
+        message(3, "assistant", "Hello!"),
+    ];
+    let changes = diff_messages(&old, &new);
+    assert_that!(changes.len(), eq(1));
+    assert_that!(changes[0].kind, eq(DiffChangeKind::Added));
+    assert_that!(changes[0].new_index, some(eq(0)));
+
+    // Swapped messages are moved, an edited one is modified and a dropped one
+    // is removed.
+    let new = vec![
+        message(0, "user", "Hi"),
+        message(1, "system", "You are helpful."),
+        message(2, "user", "Hello!"),
+    ];
+    let changes = diff_messages(&old, &new);
+    let kinds: Vec<_> = changes.iter().map(|change| change.kind).collect();
+    assert_that!(
+        kinds,
+        eq(vec![DiffChangeKind::Moved, DiffChangeKind::Modified])
+    );
+    assert_that!(changes[0].old_index, some(eq(1)));
+    assert_that!(changes[0].new_index, some(eq(0)));
+    assert_that!(changes[1].old_index, some(eq(2)));
+    assert_that!(changes[1].new_index, some(eq(2)));
+    assert_that!(
+        changes[1].fields,
+        eq(vec![FieldChange {
+            field: "role".to_string(),
+            old: Some("assistant".to_string()),
+            new: Some("user".to_string()),
+        }])
+    );
+    assert_that!(changes[1].content_diff, empty());
+
+    let new = vec![message(0, "system", "You are helpful.")];
+    let changes = diff_messages(&old, &new);
+    let kinds: Vec<_> = changes.iter().map(|change| change.kind).collect();
+    assert_that!(
+        kinds,
+        eq(vec![DiffChangeKind::Removed, DiffChangeKind::Removed])
+    );
+    assert_that!(
+        changes[0].content_diff,
+        eq(vec![line(DiffTag::Delete, Some(1), None, "Hi")])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_diff_parameters() -> anyhow::Result<()> {
+    let old = vec![parameter("default", 0.7), parameter("creative", 1.2)];
+    let new = vec![parameter("default", 0.2), parameter("strict", 0.0)];
+
+    let changes = diff_parameters(&old, &new);
+    let kinds: Vec<_> = changes
+        .iter()
+        .map(|change| (change.name.as_str(), change.kind))
+        .collect();
+    assert_that!(
+        kinds,
+        eq(vec![
+            ("creative", DiffChangeKind::Removed),
+            ("default", DiffChangeKind::Modified),
+            ("strict", DiffChangeKind::Added),
+        ])
+    );
+    assert_that!(
+        changes[1].fields,
+        eq(vec![FieldChange {
+            field: "temperature".to_string(),
+            old: Some("0.7".to_string()),
+            new: Some("0.2".to_string()),
+        }])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_diff_parameters_same_name() -> anyhow::Result<()> {
+    let old = vec![parameter("untitled", 0.7), parameter("untitled", 0.5)];
+
+    // Copies made on publish are paired with the parameter they came from.
+    let copy = |parameter: &Parameter, temperature: f32| Parameter {
+        id: Uuid::new_v4(),
+        origin_id: Some(parameter.id),
+        temperature,
+        ..parameter.clone()
+    };
+    let new = vec![
+        copy(&old[1], 0.5),
+        copy(&old[0], 0.2),
+        parameter("untitled", 1.0),
+    ];
+    let changes = diff_parameters(&old, &new);
+    let pairs: Vec<_> = changes
+        .iter()
+        .map(|change| (change.kind, change.old_id, change.new_id))
+        .collect();
+    assert_that!(
+        pairs,
+        eq(vec![
+            (DiffChangeKind::Modified, Some(old[0].id), Some(new[1].id)),
+            (DiffChangeKind::Added, None, Some(new[2].id)),
+        ])
+    );
+    assert!(changes.iter().all(|change| change.duplicate_name));
+
+    // Without one, parameters sharing a name are paired in creation order.
+    let new = vec![
+        parameter("untitled", 0.7),
+        parameter("untitled", 0.1),
+        parameter("untitled", 1.0),
+    ];
+    let changes = diff_parameters(&old, &new);
+    let pairs: Vec<_> = changes
+        .iter()
+        .map(|change| (change.kind, change.old_id, change.new_id))
+        .collect();
+    assert_that!(
+        pairs,
+        eq(vec![
+            (DiffChangeKind::Modified, Some(old[1].id), Some(new[1].id)),
+            (DiffChangeKind::Added, None, Some(new[2].id)),
+        ])
+    );
+
+    Ok(())
+}
@@ -0,0 +1,367 @@
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+
+use tokenspan_api::domains::dto::{
+    EvaluatorCreateInput, MessageCreateInput, MessageUpdateInput, ModelCreateInput,
+    ParameterCreateInput, ParameterUpdateInput, PricingInput, ProviderCreateInput,
+    ThreadCreateInput, ThreadVersionPublishInput, ThreadVersionUnlockInput,
+    ThreadVersionUpdateInput,
+};
+use tokenspan_api::domains::errors::ThreadVersionError;
+use tokenspan_api::domains::models::{EvaluatorKind, ProviderKind, UserRole};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+fn is_read_only(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<ThreadVersionError>(),
+        Some(ThreadVersionError::ReadOnly { .. })
+    )
+}
+
+#[tokio::test]
+async fn test_thread_version_lock() -> anyhow::Result<()> {
+    // Setup
+    let state: AppState;
+    let _server: TestServer;
+    setup!(state, _server);
+
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    let model_fixture = state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "greeter".to_string(),
+                slug: "greeter".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let published_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+    let message_fixture = state
+        .message_service
+        .create(
+            MessageCreateInput {
+                raw: "Hello".to_string(),
+                content: "Hello".to_string(),
+                role: "user".to_string(),
+                thread_version_id: published_fixture.id,
+                index: None,
+                name: None,
+                tool_call_id: None,
+                tool_calls: vec![],
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    let parameter_fixture = state
+        .parameter_service
+        .create(
+            ParameterCreateInput::builder()
+                .model_id(model_fixture.id)
+                .thread_version_id(published_fixture.id)
+                .build(),
+        )
+        .await?;
+    let evaluator_fixture = state
+        .evaluation_service
+        .create_evaluator(EvaluatorCreateInput {
+            thread_version_id: published_fixture.id,
+            name: "greets".to_string(),
+            kind: EvaluatorKind::Contains,
+            value: Some("Hello".to_string()),
+            threshold: None,
+            weight: 1.0,
+            judge_parameter_id: None,
+            judge_api_key_id: None,
+            judge_max_score: None,
+        })
+        .await?;
+
+    let draft_fixture = state
+        .thread_version_service
+        .publish(
+            &published_fixture.id,
+            ThreadVersionPublishInput {
+                semver: Some("0.1.0".to_string()),
+                bump: None,
+                release_note: "first".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    // Nothing of a published version can be changed.
+    let message_update = || MessageUpdateInput {
+        raw: None,
+        content: Some("Hi".to_string()),
+        role: None,
+        index: None,
+        name: None,
+        tool_call_id: None,
+        tool_calls: None,
+    };
+    let error = state
+        .message_service
+        .update_by_id(&message_fixture.id, message_update())
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+    assert_that!(
+        error.to_string(),
+        contains_substring(draft_fixture.id.to_string())
+    );
+    let error = state
+        .message_service
+        .delete_by_id(&message_fixture.id)
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+    let error = state
+        .parameter_service
+        .update_by_id(
+            &parameter_fixture.id,
+            ParameterUpdateInput {
+                name: None,
+                temperature: Some(0.5),
+                max_tokens: None,
+                stop_sequences: None,
+                top_p: None,
+                frequency_penalty: None,
+                presence_penalty: None,
+                extra: None,
+                is_default: None,
+                model_id: None,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+    let error = state
+        .parameter_service
+        .create(
+            ParameterCreateInput::builder()
+                .model_id(model_fixture.id)
+                .thread_version_id(published_fixture.id)
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+    let error = state
+        .thread_version_service
+        .update_by_id(
+            &published_fixture.id,
+            ThreadVersionUpdateInput::builder()
+                .description(Some("changed".to_string()))
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+
+    // The draft copied from it still can.
+    let draft_messages = state
+        .message_service
+        .find_by_thread_version_id(&draft_fixture.id)
+        .await?;
+    state
+        .message_service
+        .update_by_id(&draft_messages[0].id, message_update())
+        .await?;
+    let draft_evaluators = state
+        .evaluation_service
+        .find_evaluators_by_thread_version_id(&draft_fixture.id)
+        .await?;
+    assert_that!(draft_evaluators.len(), eq(1));
+    assert_that!(draft_evaluators[0].id, not(eq(evaluator_fixture.id)));
+    assert_that!(draft_evaluators[0].name, eq("greets"));
+    assert_that!(draft_evaluators[0].value, some(eq("Hello")));
+
+    // Unlocking takes a reason and is recorded.
+    let error = state
+        .thread_version_service
+        .unlock(
+            &published_fixture.id,
+            ThreadVersionUnlockInput::builder()
+                .reason(" ".to_string())
+                .build(),
+            auth_fixture.user.id,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ThreadVersionError>(),
+        Some(ThreadVersionError::InvalidUnlock(_))
+    ));
+    let error = state
+        .thread_version_service
+        .unlock(
+            &draft_fixture.id,
+            ThreadVersionUnlockInput::builder()
+                .reason("typo".to_string())
+                .build(),
+            auth_fixture.user.id,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ThreadVersionError>(),
+        Some(ThreadVersionError::InvalidUnlock(_))
+    ));
+
+    let unlock = state
+        .thread_version_service
+        .unlock(
+            &published_fixture.id,
+            ThreadVersionUnlockInput::builder()
+                .reason("typo in the greeting".to_string())
+                .build(),
+            auth_fixture.user.id,
+        )
+        .await?;
+    assert_that!(unlock.unlocked_by_id, eq(auth_fixture.user.id));
+    assert_that!(unlock.locked_at, none());
+
+    let message = state
+        .message_service
+        .update_by_id(&message_fixture.id, message_update())
+        .await?;
+    assert_that!(message.content, eq("Hi"));
+
+    // Locking it again ends the unlock early.
+    let unlock = state
+        .thread_version_service
+        .lock(&published_fixture.id, auth_fixture.user.id)
+        .await?;
+    assert_that!(unlock.locked_by_id, some(eq(auth_fixture.user.id)));
+    let error = state
+        .message_service
+        .delete_by_id(&message_fixture.id)
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+
+    let unlocks = state
+        .thread_version_service
+        .find_unlocks_by_thread_version_id(&published_fixture.id)
+        .await?;
+    assert_that!(unlocks.len(), eq(1));
+    assert_that!(unlocks[0].reason, eq("typo in the greeting"));
+
+    // Deleting it takes an unlock too, and is recorded on the unlock.
+    let error = state
+        .thread_version_service
+        .delete_by_id(&published_fixture.id, auth_fixture.user.id)
+        .await
+        .unwrap_err();
+    assert!(is_read_only(&error));
+
+    state
+        .thread_version_service
+        .unlock(
+            &published_fixture.id,
+            ThreadVersionUnlockInput::builder()
+                .reason("released by mistake".to_string())
+                .build(),
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    // Even unlocked, a version a label points at is kept.
+    state
+        .thread_version_service
+        .set_label(
+            &published_fixture.id,
+            "stable".to_string(),
+            auth_fixture.user.id,
+        )
+        .await?;
+    let error = state
+        .thread_version_service
+        .delete_by_id(&published_fixture.id, auth_fixture.user.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ThreadVersionError>(),
+        Some(ThreadVersionError::InUse(_))
+    ));
+    assert_that!(error.to_string(), contains_substring("stable"));
+    state
+        .thread_version_service
+        .delete_label(&thread_fixture.id, "stable")
+        .await?;
+
+    let deleted = state
+        .thread_version_service
+        .delete_by_id(&published_fixture.id, auth_fixture.user.id)
+        .await?;
+    assert_that!(deleted.id, eq(published_fixture.id));
+    assert_that!(
+        state
+            .thread_version_service
+            .find_by_id(&published_fixture.id)
+            .await?,
+        none()
+    );
+
+    let unlocks = state
+        .thread_version_service
+        .find_unlocks_by_thread_version_id(&published_fixture.id)
+        .await?;
+    assert_that!(unlocks.len(), eq(2));
+    assert_that!(unlocks[0].reason, eq("released by mistake"));
+    assert_that!(unlocks[0].deleted_by_id, some(eq(auth_fixture.user.id)));
+    assert_that!(unlocks[0].deleted_at, some(anything()));
+    assert_that!(unlocks[1].deleted_at, none());
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use chrono::Utc;
+use googletest::prelude::*;
+use semver::Version;
+use uuid::Uuid;
+
+use tokenspan_api::domains::errors::ThreadVersionError;
+use tokenspan_api::domains::models::{SemverBump, ThreadVersion, ThreadVersionStatus};
+use tokenspan_api::domains::services::{bump_semver, check_next_semver, SemverSelector};
+
+fn thread_version(semver: &str, status: ThreadVersionStatus) -> ThreadVersion {
+    ThreadVersion {
+        id: Uuid::new_v4(),
+        semver: semver.to_string(),
+        version: 0,
+        release_note: None,
+        description: None,
+        document: None,
+        status,
+        thread_id: Uuid::nil(),
+        owner_id: Uuid::nil(),
+        published_at: None,
+        variables: vec![],
+        created_at: Utc::now().naive_utc(),
+        updated_at: Utc::now().naive_utc(),
+    }
+}
+
+fn thread_versions() -> Vec<ThreadVersion> {
+    vec![
+        thread_version("1.1.0", ThreadVersionStatus::Published),
+        thread_version("1.2.0", ThreadVersionStatus::Published),
+        thread_version("1.2.7", ThreadVersionStatus::Published),
+        thread_version("1.3.0", ThreadVersionStatus::Published),
+        thread_version("2.0.0", ThreadVersionStatus::Published),
+        thread_version("banana", ThreadVersionStatus::Published),
+        thread_version("2.1.0", ThreadVersionStatus::Draft),
+    ]
+}
+
+fn select(selector: &str) -> Option<String> {
+    let thread_versions = thread_versions();
+    SemverSelector::parse(selector)
+        .unwrap()
+        .select(&thread_versions)
+        .map(|thread_version| thread_version.semver.clone())
+}
+
+#[tokio::test]
+async fn test_select() -> anyhow::Result<()> {
+    // Exact versions match drafts too; ranges only published versions.
+    assert_that!(select("1.2.0"), some(eq("1.2.0")));
+    assert_that!(select("2.1.0"), some(eq("2.1.0")));
+    assert_that!(select("1.4.0"), none());
+
+    assert_that!(select("^1.2"), some(eq("1.3.0")));
+    assert_that!(select("~1.2.3"), some(eq("1.2.7")));
+    assert_that!(select("~1.2"), some(eq("1.2.7")));
+    assert_that!(select(">=2"), some(eq("2.0.0")));
+    assert_that!(select("*"), some(eq("2.0.0")));
+    assert_that!(select("^3"), none());
+
+    for selector in ["banana", "1.2.x.y", ""] {
+        assert!(matches!(
+            SemverSelector::parse(selector),
+            Err(ThreadVersionError::InvalidSemver { .. })
+        ));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bump_semver() -> anyhow::Result<()> {
+    let version = Version::parse("1.2.3-beta.1+build.5")?;
+
+    assert_that!(
+        bump_semver(&version, SemverBump::Major),
+        eq(Version::new(2, 0, 0))
+    );
+    assert_that!(
+        bump_semver(&version, SemverBump::Minor),
+        eq(Version::new(1, 3, 0))
+    );
+    assert_that!(
+        bump_semver(&version, SemverBump::Patch),
+        eq(Version::new(1, 2, 4))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_next_semver() -> anyhow::Result<()> {
+    let thread_versions = thread_versions();
+
+    assert_that!(
+        check_next_semver(&thread_versions, &Version::new(2, 1, 1)).is_ok(),
+        eq(true)
+    );
+    for taken in ["2.1.0", "2.0.5", "1.0.0", "2.1.0+build"] {
+        assert!(matches!(
+            check_next_semver(&thread_versions, &Version::parse(taken)?),
+            Err(ThreadVersionError::InvalidSemver { .. })
+        ));
+    }
+
+    Ok(())
+}
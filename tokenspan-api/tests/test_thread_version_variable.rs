@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+
+use tokenspan_api::domains::errors::VariableViolation;
+use tokenspan_api::domains::models::{VariableDefinition, VariableType};
+use tokenspan_api::domains::services::{resolve_variables, validate_definitions};
+
+fn definition(name: &str, r#type: VariableType) -> VariableDefinition {
+    VariableDefinition {
+        name: name.to_string(),
+        r#type,
+        description: None,
+        default: None,
+        required: false,
+        options: vec![],
+    }
+}
+
+fn definitions() -> Vec<VariableDefinition> {
+    vec![
+        VariableDefinition {
+            required: true,
+            ..definition("question", VariableType::String)
+        },
+        VariableDefinition {
+            default: Some("3".to_string()),
+            ..definition("max_items", VariableType::Number)
+        },
+        definition("verbose", VariableType::Boolean),
+        VariableDefinition {
+            options: vec!["en".to_string(), "vi".to_string()],
+            default: Some("en".to_string()),
+            ..definition("language", VariableType::Enum)
+        },
+        definition("context", VariableType::Json),
+    ]
+}
+
+fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn violation(name: &str, reason: &str) -> VariableViolation {
+    VariableViolation {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_validate_definitions() -> anyhow::Result<()> {
+    assert_that!(validate_definitions(&definitions()).is_ok(), eq(true));
+
+    let invalid = [
+        vec![definition("2fast", VariableType::String)],
+        vec![definition("user-name", VariableType::String)],
+        vec![
+            definition("name", VariableType::String),
+            definition("name", VariableType::Number),
+        ],
+        vec![definition("language", VariableType::Enum)],
+        vec![VariableDefinition {
+            options: vec!["a".to_string()],
+            ..definition("name", VariableType::String)
+        }],
+        vec![VariableDefinition {
+            default: Some("many".to_string()),
+            ..definition("count", VariableType::Number)
+        }],
+    ];
+    for definitions in invalid {
+        assert_that!(validate_definitions(&definitions).is_err(), eq(true));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resolve_variables() -> anyhow::Result<()> {
+    let resolved = resolve_variables(
+        &definitions(),
+        &variables(&[("question", "Why?"), ("verbose", "true")]),
+    );
+    assert_that!(
+        resolved,
+        ok(eq(variables(&[
+            ("question", "Why?"),
+            ("max_items", "3"),
+            ("verbose", "true"),
+            ("language", "en"),
+        ])))
+    );
+
+    let resolved = resolve_variables(
+        &definitions(),
+        &variables(&[
+            ("max_items", "a few"),
+            ("verbose", "yes"),
+            ("language", "fr"),
+            ("context", "{"),
+            ("questoin", "Why?"),
+            ("other", "x"),
+        ]),
+    );
+    assert_that!(
+        resolved,
+        err(eq(vec![
+            violation("question", "is required"),
+            violation("max_items", "must be a number"),
+            violation("verbose", "must be `true` or `false`"),
+            violation("language", "must be one of `en`, `vi`"),
+            violation(
+                "context",
+                "must be valid JSON: EOF while parsing an object at line 1 column 1"
+            ),
+            violation("other", "is not declared"),
+            violation("questoin", "is not declared, did you mean `question`?"),
+        ]))
+    );
+
+    // Without a schema anything goes.
+    let resolved = resolve_variables(&[], &variables(&[("anything", "x")]));
+    assert_that!(resolved, ok(eq(variables(&[("anything", "x")]))));
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use axum_test::TestServer;
+use chrono::Utc;
+use googletest::prelude::*;
+use uuid::Uuid;
+
+use tokenspan_api::domains::dto::{
+    ApiKeyCreateInput, EnvironmentPromoteInput, ModelCreateInput, PricingInput,
+    ProviderCreateInput, ThreadCreateInput, ThreadRunInput, ThreadVersionPublishInput,
+    ThreadVersionUnlockInput, TrafficSplitCreateInput,
+};
+use tokenspan_api::domains::errors::{ThreadVersionError, TrafficSplitError};
+use tokenspan_api::domains::models::{
+    ProviderKind, TrafficArm, TrafficSplit, TrafficSplitStatus, UserRole,
+};
+use tokenspan_api::state::AppState;
+
+mod common;
+
+fn arm(name: &str, weight: i32) -> TrafficArm {
+    TrafficArm {
+        name: name.to_string(),
+        thread_version_id: Uuid::new_v4(),
+        weight,
+    }
+}
+
+fn traffic_split(arms: Vec<TrafficArm>) -> TrafficSplit {
+    TrafficSplit {
+        id: Uuid::new_v4(),
+        name: "canary".to_string(),
+        thread_id: Uuid::new_v4(),
+        environment_id: Uuid::new_v4(),
+        arms,
+        status: TrafficSplitStatus::Active,
+        owner_id: Uuid::new_v4(),
+        ended_at: None,
+        created_at: Utc::now().naive_utc(),
+        updated_at: Utc::now().naive_utc(),
+    }
+}
+
+#[tokio::test]
+async fn test_assign() -> anyhow::Result<()> {
+    let split = traffic_split(vec![arm("canary", 10), arm("control", 90)]);
+
+    // The same subject always lands on the same arm.
+    for i in 0..100 {
+        let subject = format!("user-{}", i);
+        let first = split.assign(Some(&subject)).map(|arm| arm.name.clone());
+        for _ in 0..5 {
+            let again = split.assign(Some(&subject)).map(|arm| arm.name.clone());
+            assert_that!(again, eq(first.clone()));
+        }
+    }
+
+    // Subjects spread across the arms roughly by weight.
+    let mut counts: HashMap<String, i32> = HashMap::new();
+    for i in 0..10_000 {
+        let subject = format!("user-{}", i);
+        let arm = split.assign(Some(&subject)).unwrap();
+        *counts.entry(arm.name.clone()).or_default() += 1;
+    }
+    assert_that!(counts["canary"], ge(700));
+    assert_that!(counts["canary"], le(1_300));
+    assert_that!(counts["canary"] + counts["control"], eq(10_000));
+
+    // Growing the first arm keeps its subjects on it.
+    let grown = TrafficSplit {
+        arms: vec![arm("canary", 50), arm("control", 50)],
+        ..split.clone()
+    };
+    for i in 0..1_000 {
+        let subject = format!("user-{}", i);
+        if split.assign(Some(&subject)).unwrap().name == "canary" {
+            assert_that!(grown.assign(Some(&subject)).unwrap().name, eq("canary"));
+        }
+    }
+
+    // An arm without weight is never picked, with or without a subject.
+    let split = traffic_split(vec![arm("canary", 0), arm("control", 100)]);
+    for i in 0..100 {
+        let subject = format!("user-{}", i);
+        assert_that!(split.assign(Some(&subject)).unwrap().name, eq("control"));
+        assert_that!(split.assign(None).unwrap().name, eq("control"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_traffic_split() -> anyhow::Result<()> {
+    // Setup
+    let state: AppState;
+    let _server: TestServer;
+    setup!(state, _server);
+
+    let auth_fixture = state
+        .auth_service
+        .sign_up_with_role(
+            "linh@gmail.com".to_string(),
+            "linh".to_string(),
+            "123".to_string(),
+            UserRole::Admin,
+        )
+        .await?;
+
+    let provider_fixture = state
+        .provider_service
+        .create(ProviderCreateInput {
+            name: "OpenAI".to_string(),
+            slug: "openai".to_string(),
+            kind: ProviderKind::OpenAI,
+            ..Default::default()
+        })
+        .await?;
+
+    state
+        .model_service
+        .create(ModelCreateInput {
+            name: "gpt-3.5-turbo".to_string(),
+            slug: "gpt-3.5-turbo".to_string(),
+            description: "GPT-3.5 Turbo".to_string(),
+            provider_id: provider_fixture.id,
+            context: 256,
+            training_at: Utc::now().naive_utc(),
+            input_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+            output_pricing: PricingInput {
+                currency: "USD".to_string(),
+                price: 0.06,
+                tokens: 1,
+            },
+        })
+        .await?;
+
+    state
+        .api_key_service
+        .create(
+            ApiKeyCreateInput {
+                name: "OpenAI".to_string(),
+                key: "sk-123".to_string(),
+                provider_id: provider_fixture.id,
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let thread_fixture = state
+        .thread_service
+        .new(
+            ThreadCreateInput {
+                name: "greeter".to_string(),
+                slug: "greeter".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let control_fixture = state
+        .thread_version_service
+        .find_latest(&thread_fixture.id)
+        .await?
+        .ok_or(anyhow::anyhow!("Thread version not found"))?;
+    let canary_fixture = state
+        .thread_version_service
+        .publish(
+            &control_fixture.id,
+            ThreadVersionPublishInput {
+                semver: Some("1.2.0".to_string()),
+                bump: None,
+                release_note: "control".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+    state
+        .thread_version_service
+        .publish(
+            &canary_fixture.id,
+            ThreadVersionPublishInput {
+                semver: Some("1.3.0".to_string()),
+                bump: None,
+                release_note: "canary".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let promotion = state
+        .environment_service
+        .promote(
+            EnvironmentPromoteInput {
+                thread_version_id: control_fixture.id,
+                environment: "production".to_string(),
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    // Weights must add up to 100.
+    let error = state
+        .traffic_split_service
+        .create(
+            TrafficSplitCreateInput {
+                environment_id: promotion.environment_id,
+                name: "canary".to_string(),
+                arms: vec![
+                    TrafficArm {
+                        name: "canary".to_string(),
+                        thread_version_id: canary_fixture.id,
+                        weight: 10,
+                    },
+                    TrafficArm {
+                        name: "control".to_string(),
+                        thread_version_id: control_fixture.id,
+                        weight: 80,
+                    },
+                ],
+            },
+            auth_fixture.user.id,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<TrafficSplitError>(),
+        Some(TrafficSplitError::InvalidArms(_))
+    ));
+
+    let traffic_split = state
+        .traffic_split_service
+        .create(
+            TrafficSplitCreateInput {
+                environment_id: promotion.environment_id,
+                name: "canary".to_string(),
+                arms: vec![
+                    TrafficArm {
+                        name: "canary".to_string(),
+                        thread_version_id: canary_fixture.id,
+                        weight: 100,
+                    },
+                    TrafficArm {
+                        name: "control".to_string(),
+                        thread_version_id: control_fixture.id,
+                        weight: 0,
+                    },
+                ],
+            },
+            auth_fixture.user.id,
+        )
+        .await?;
+
+    let run_input = || ThreadRunInput {
+        semver: None,
+        label: None,
+        environment: Some("production".to_string()),
+        subject: Some("user-1".to_string()),
+        tools: vec![],
+        variables: Default::default(),
+        max_tool_iterations: None,
+        retry: None,
+        fallbacks: vec![],
+        bypass_cache: false,
+        expected_output: None,
+        stream: false,
+    };
+
+    let resolved = state
+        .thread_service
+        .resolve_run("greeter".to_string(), run_input(), auth_fixture.user.id)
+        .await?;
+    assert_that!(resolved.thread_version_id, eq(canary_fixture.id));
+    assert_that!(resolved.traffic_split_id, some(eq(traffic_split.id)));
+    assert_that!(resolved.traffic_arm, some(eq("canary")));
+
+    let metrics = state.traffic_split_service.metrics(&traffic_split).await?;
+    assert_that!(metrics.len(), eq(2));
+    assert_that!(metrics[0].executions, eq(0));
+
+    // A version an active split serves cannot be deleted, even unlocked.
+    state
+        .thread_version_service
+        .unlock(
+            &canary_fixture.id,
+            ThreadVersionUnlockInput::builder()
+                .reason("released by mistake".to_string())
+                .build(),
+            auth_fixture.user.id,
+        )
+        .await?;
+    let error = state
+        .thread_version_service
+        .delete_by_id(&canary_fixture.id, auth_fixture.user.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ThreadVersionError>(),
+        Some(ThreadVersionError::InUse(_))
+    ));
+
+    // Once ended, runs go back to the promoted version.
+    state
+        .traffic_split_service
+        .end_by_id(&traffic_split.id)
+        .await?;
+    let resolved = state
+        .thread_service
+        .resolve_run("greeter".to_string(), run_input(), auth_fixture.user.id)
+        .await?;
+    assert_that!(resolved.thread_version_id, eq(control_fixture.id));
+    assert_that!(resolved.traffic_split_id, none());
+
+    Ok(())
+}